@@ -0,0 +1,57 @@
+extern crate fuel;
+
+use fuel::{Control, Importer, OpenGlBackend, Result, Scene, Window};
+use std::thread::sleep;
+use std::time::Duration;
+
+const TITLE: &str = "Animation example";
+const WINDOW_WIDTH: f32 = 800.;
+const WINDOW_HEIGHT: f32 = 600.;
+const BOX_PATH: &str =
+    "src/assets/meshes/samples/box_animated/BoxAnimated.gltf";
+// Seconds before fading to the other animation
+const SWITCH_DELAY: f32 = 3.;
+const FADE_DURATION: f32 = 0.5;
+
+fn main() -> Result<()> {
+    let mut win = Window::new(TITLE, WINDOW_WIDTH, WINDOW_HEIGHT)?;
+    let mut control = Control::new();
+    let mut scene = Scene::new(WINDOW_WIDTH, WINDOW_HEIGHT, 45., 0.1, 100.);
+
+    win.make_current();
+    win.load_gl_methods();
+    let mut backend = OpenGlBackend::new();
+
+    let mut model = Importer::from_gltf(BOX_PATH)?;
+    let spin = model.find_animation("spin").unwrap_or(0);
+    let bounce = model.find_animation("bounce").unwrap_or(0);
+    model.play(spin);
+    let cube = scene.add(model);
+
+    let mut elapsed = 0.;
+    let mut next = bounce;
+    while control.is_running {
+        win.clear_gl();
+        win.compute_delta();
+        scene.camera.set_dt(win.get_dt());
+
+        elapsed += win.get_dt();
+        if elapsed > SWITCH_DELAY {
+            if let Some(model) = scene.get_object(cube).as_model_mut() {
+                model.cross_fade(next, FADE_DURATION);
+            }
+            next = if next == bounce { spin } else { bounce };
+            elapsed = 0.;
+        }
+
+        win.pull_events(&mut control);
+        win.on_resize(&control, |size| {
+            scene.resize(&mut backend, size.dimensions, size.framebuffer)
+        });
+        scene.update(win.get_dt());
+        scene.render(&mut backend)?;
+        sleep(Duration::from_millis(16));
+    }
+
+    Ok(())
+}
@@ -1,79 +1,50 @@
-#![cfg_attr(feature = "clippy", feature(plugin))]
-#![cfg_attr(feature = "clippy", plugin(clippy))]
-
 extern crate fuel;
 
-use fuel::gl;
-use fuel::na::{Isometry3, Vector3};
-use fuel::utils;
-use fuel::FirstPerson;
-use fuel::Pipeline;
-use fuel::Window;
-
+use fuel::na::Vector3;
+use fuel::{
+    Control, Importer, Light, OpenGlBackend, Result, Scene, SceneObject, Window,
+};
 use std::thread::sleep;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 const TITLE: &str = "Light example";
 const WINDOW_WIDTH: f32 = 800.;
 const WINDOW_HEIGHT: f32 = 600.;
+const BOX_PATH: &str = "src/assets/meshes/samples/box/Box.gltf";
 
-fn main() {
-    let now = Instant::now();
-    let mut win = Window::new(TITLE, WINDOW_WIDTH, WINDOW_HEIGHT);
-    let mut dt: f32;
-    let mut last_frame: f32 = 0.;
-    let mut control = utils::Control::new();
+fn main() -> Result<()> {
+    let mut win = Window::new(TITLE, WINDOW_WIDTH, WINDOW_HEIGHT)?;
+    let mut control = Control::new();
+    let mut scene = Scene::new(WINDOW_WIDTH, WINDOW_HEIGHT, 45., 0.1, 100.);
 
     win.make_current();
     win.load_gl_methods();
+    let mut backend = OpenGlBackend::new();
 
-    let mut cam =
-        FirstPerson::new((WINDOW_WIDTH, WINDOW_HEIGHT), 45., 0.1, 100.);
-    let projection = cam.get_projection();
-
-    win.gl_window
-        .window()
-        .set_cursor_position(cam.last_pos.0 as i32, cam.last_pos.1 as i32)
-        .expect("Failed to set cursor position at the center of the screen");
+    let cube = scene.add(Importer::from_gltf(BOX_PATH)?);
+    scene.get_object(cube).set_position(0., 0., 0.);
 
-    let cube_vertices = utils::get_cube_vertices();
-    let cube_positions: [Vector3<f32>; 2] =
-        [Vector3::new(0., 0., 0.), Vector3::new(2., 0., -2.)];
+    let mut lamp = Light::point(Vector3::new(1., 0.9, 0.8), 20.);
+    lamp.set_position(2., 1., -2.);
+    scene.add(lamp.with_range(10.));
 
-    let mut pipeline =
-        Pipeline::new(&cube_vertices, "cube.vs", "cube.fs", "container.jpg");
-    pipeline.set_texture_off();
-    pipeline.shader.use_program();
+    let mut sun = Light::directional(Vector3::new(0.4, 0.5, 0.8), 0.5);
+    sun.set_direction(Vector3::new(1., -1., 1.));
+    scene.add(sun);
 
     while control.is_running {
-        let current_frame = utils::duration_to_secs(now.elapsed()) as f32;
-        dt = current_frame - last_frame;
-        last_frame = current_frame;
-        // set delta time for each frame
-        cam.set_dt(dt);
+        win.clear_gl();
+        win.compute_delta();
+        scene.camera.set_dt(win.get_dt());
+        scene.update(win.get_dt());
 
-        win.event_loop.poll_events(|e| {
-            control.process_inputs(e, &mut cam, &mut pipeline);
+        win.pull_events(&mut control);
+        win.on_resize(&control, |size| {
+            scene.resize(&mut backend, size.dimensions, size.framebuffer)
         });
-
-        let view = cam.get_view();
-
-        unsafe {
-            gl::ClearColor(1., 1., 1., 1.);
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-        }
-
-        for (_, position) in cube_positions.iter().enumerate() {
-            let model = Isometry3::new(*position, Vector3::new(0., 5., 0.))
-                .to_homogeneous();
-
-            // Order is very important
-            let mvp = projection * view * model;
-            pipeline.shader.set_mvp(mvp);
-            pipeline.render();
-        }
-
-        win.clear_gl();
+        scene.render(&mut backend)?;
         sleep(Duration::from_millis(16));
     }
+
+    Ok(())
 }
@@ -0,0 +1,84 @@
+extern crate fuel;
+
+use fuel::glutin::VirtualKeyCode;
+use fuel::{
+    Control, Importer, OpenGlBackend, Orbit, OrbitDrag, ProjectionMode, Result,
+    Scene, Window,
+};
+use std::thread::sleep;
+use std::time::Duration;
+
+const TITLE: &str = "Orbit";
+const WINDOW_WIDTH: f32 = 800.;
+const WINDOW_HEIGHT: f32 = 600.;
+const FOVY: f32 = 0.8;
+// Seconds to switch between projections
+const TRANSITION: f32 = 0.5;
+
+// Rotate with the right button, pan with the middle
+// button and zoom with the scroll wheel. O and P switch
+// to orthographic and perspective projections.
+fn main() -> Result<()> {
+    let mut win = Window::new(TITLE, WINDOW_WIDTH, WINDOW_HEIGHT)?;
+    let mut control = Control::new();
+    let camera = Orbit::new((WINDOW_WIDTH, WINDOW_HEIGHT), FOVY, 0.1, 100.);
+    let mut scene = Scene::with_camera(camera);
+
+    win.make_current();
+    win.load_gl_methods();
+    let mut backend = OpenGlBackend::new();
+
+    let cube = Importer::from_gltf(
+        "src/assets/meshes/samples/textured/BoxTextured.gltf",
+    )?;
+    if let Some(aabb) = cube.get_bounding_box() {
+        scene.camera.frame(aabb.min, aabb.max);
+    }
+    scene.add(cube);
+
+    while control.is_running {
+        win.clear_gl();
+        win.compute_delta();
+        scene.update(win.get_dt());
+        win.pull_events(&mut control);
+        win.on_resize(&control, |size| {
+            scene.resize(&mut backend, size.dimensions, size.framebuffer)
+        });
+
+        let drag = if control.is_mouse_right_pressed {
+            Some(OrbitDrag::Rotate)
+        } else if control.is_mouse_middle_pressed {
+            Some(OrbitDrag::Pan)
+        } else {
+            None
+        };
+        let (pos_x, pos_y) = control.cursor_position;
+        scene.camera.drag(pos_x, pos_y, drag);
+        win.on_mouse_wheel(&control, |lines| scene.camera.dolly(lines));
+
+        {
+            let camera = &mut scene.camera;
+            win.on_pressed_key(&control, VirtualKeyCode::O, || {
+                // Same height as the perspective at the target
+                let mode = camera.lens.mode;
+                if let ProjectionMode::Perspective { .. } = mode {
+                    let height = mode.view_height(camera.distance);
+                    let ortho = ProjectionMode::orthographic(height, 0.1, 100.);
+                    camera.lens.transition_to(ortho, TRANSITION);
+                }
+            });
+            win.on_pressed_key(&control, VirtualKeyCode::P, || {
+                if let ProjectionMode::Orthographic { .. } = camera.lens.mode {
+                    let perspective =
+                        ProjectionMode::perspective(FOVY, 0.1, 100.);
+                    camera.lens.transition_to(perspective, TRANSITION);
+                }
+            });
+        }
+
+        scene.render(&mut backend)?;
+        sleep(Duration::from_millis(16));
+    }
+
+    Ok(())
+}
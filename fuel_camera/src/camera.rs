@@ -0,0 +1,49 @@
+use projection::Lens;
+use {Projection, View};
+
+/// What the scene needs from a camera to render.
+pub trait Camera {
+    fn get_view(&self) -> View;
+    fn get_projection(&self) -> Projection;
+    fn get_lens(&self) -> &Lens;
+    fn get_lens_mut(&mut self) -> &mut Lens;
+    fn get_win_dimensions(&self) -> (f32, f32);
+    /// Size of the window, for the aspect ratio.
+    fn set_win_dimensions(&mut self, dimensions: (f32, f32));
+
+    /// Advance the projection transition, if any.
+    fn update(&mut self, dt: f32) {
+        self.get_lens_mut().update(dt);
+    }
+}
+
+// Lets a scene switch between cameras at runtime
+impl<C: Camera + ?Sized> Camera for Box<C> {
+    fn get_view(&self) -> View {
+        (**self).get_view()
+    }
+
+    fn get_projection(&self) -> Projection {
+        (**self).get_projection()
+    }
+
+    fn get_lens(&self) -> &Lens {
+        (**self).get_lens()
+    }
+
+    fn get_lens_mut(&mut self) -> &mut Lens {
+        (**self).get_lens_mut()
+    }
+
+    fn get_win_dimensions(&self) -> (f32, f32) {
+        (**self).get_win_dimensions()
+    }
+
+    fn set_win_dimensions(&mut self, dimensions: (f32, f32)) {
+        (**self).set_win_dimensions(dimensions)
+    }
+
+    fn update(&mut self, dt: f32) {
+        (**self).update(dt)
+    }
+}
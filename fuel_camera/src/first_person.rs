@@ -1,13 +1,12 @@
-use na::{Matrix4, Perspective3, Point3, Vector3};
+use na::{Matrix4, Point3, Vector3};
+use projection::{Lens, ProjectionMode};
 use std::default::Default;
-use {CameraMovement, Projection, View};
+use {Camera, CameraMovement, Projection, View};
 
 pub struct FirstPerson {
     pub speed: f32,
     pub win_dimensions: (f32, f32),
-    fovy: f32,
-    znear: f32,
-    zfar: f32,
+    pub lens: Lens,
 
     delta_time: f32,
     sensibility: f32,
@@ -21,6 +20,8 @@ pub struct FirstPerson {
 }
 
 impl FirstPerson {
+    /// The field of view `fovy` is in degrees, like the yaw and
+    /// the pitch.
     pub fn new(
         win_dimensions: (f32, f32),
         fovy: f32,
@@ -30,9 +31,11 @@ impl FirstPerson {
         FirstPerson {
             win_dimensions,
             last_pos: (win_dimensions.0 / 2., win_dimensions.1 / 2.),
-            fovy,
-            znear,
-            zfar,
+            lens: Lens::new(ProjectionMode::perspective(
+                fovy.to_radians(),
+                znear,
+                zfar,
+            )),
             ..Default::default()
         }
     }
@@ -71,7 +74,8 @@ impl FirstPerson {
             self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
             self.pitch.to_radians().sin(),
             self.yaw.to_radians().sin() * self.pitch.to_radians().cos(),
-        ).normalize();
+        )
+        .normalize();
     }
     // Moving camera on the coords in 3d space
     pub fn move_direction(&mut self, direction: &CameraMovement) {
@@ -97,9 +101,16 @@ impl FirstPerson {
         self.delta_time = dt
     }
 
+    /// In degrees.
     #[allow(dead_code)]
     pub fn set_fovy(&mut self, fovy: f32) {
-        self.fovy = fovy;
+        if let ProjectionMode::Perspective {
+            fovy: ref mut value,
+            ..
+        } = self.lens.mode
+        {
+            *value = fovy.to_radians();
+        }
     }
 
     #[allow(dead_code)]
@@ -107,20 +118,37 @@ impl FirstPerson {
         self.position = position
     }
 
-    pub fn get_view(&self) -> View {
+    pub fn get_dt_speed(&self) -> f32 {
+        self.speed * self.delta_time
+    }
+}
+
+impl Camera for FirstPerson {
+    fn get_view(&self) -> View {
         let eye = Point3::from_coordinates(self.position);
         let target = Point3::from_coordinates(self.position + self.front);
 
         Matrix4::look_at_rh(&eye, &target, &self.up)
     }
 
-    pub fn get_projection(&self) -> Projection {
-        Perspective3::new(self.get_aspect(), self.fovy, self.znear, self.zfar)
-            .to_homogeneous()
+    fn get_projection(&self) -> Projection {
+        self.lens.to_matrix(self.get_aspect())
     }
 
-    pub fn get_dt_speed(&self) -> f32 {
-        self.speed * self.delta_time
+    fn get_lens(&self) -> &Lens {
+        &self.lens
+    }
+
+    fn get_lens_mut(&mut self) -> &mut Lens {
+        &mut self.lens
+    }
+
+    fn get_win_dimensions(&self) -> (f32, f32) {
+        self.win_dimensions
+    }
+
+    fn set_win_dimensions(&mut self, dimensions: (f32, f32)) {
+        self.win_dimensions = dimensions;
     }
 }
 
@@ -132,9 +160,11 @@ impl Default for FirstPerson {
             delta_time: 0.,
             speed: 2.5,
             win_dimensions,
-            fovy: 45.,
-            znear: 0.1,
-            zfar: 100.,
+            lens: Lens::new(ProjectionMode::perspective(
+                45f32.to_radians(),
+                0.1,
+                100.,
+            )),
 
             sensibility: 0.05,
             position: Vector3::new(0., 0., 3.),
@@ -149,3 +179,21 @@ impl Default for FirstPerson {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    #[test]
+    fn field_of_view_is_converted_to_radians() {
+        let camera = FirstPerson::new((800., 600.), 45., 0.1, 100.);
+        let height = camera.lens.mode.view_height(1.);
+        assert!((height - 2. * (FRAC_PI_4 / 2.).tan()).abs() < 1e-6);
+
+        let mut camera = FirstPerson::default();
+        assert!((camera.lens.mode.view_height(1.) - height).abs() < 1e-6);
+        camera.set_fovy(90.);
+        assert!((camera.lens.mode.view_height(1.) - 2.).abs() < 1e-5);
+    }
+}
@@ -1,8 +1,14 @@
 extern crate nalgebra as na;
 use na::Matrix4;
 
+mod camera;
 mod first_person;
+mod orbit;
+mod projection;
+pub use self::camera::Camera;
 pub use self::first_person::FirstPerson;
+pub use self::orbit::{Orbit, OrbitDrag};
+pub use self::projection::{Lens, ProjectionMode};
 
 pub type Projection = Matrix4<f32>;
 pub type View = Matrix4<f32>;
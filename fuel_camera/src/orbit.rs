@@ -0,0 +1,361 @@
+use na::{Matrix4, Point3, Vector3};
+use projection::{Lens, ProjectionMode};
+use std::default::Default;
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+use {Camera, Projection, View};
+
+// Keep the camera away from the poles, where up and
+// front would be aligned
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// What moving the cursor does to an Orbit camera.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrbitDrag {
+    Rotate,
+    Pan,
+}
+
+/// Camera turning around a target point, to inspect a model.
+/// Yaw and pitch are in radians, with both at zero the camera
+/// is on the z axis of the target, looking towards -z.
+pub struct Orbit {
+    pub win_dimensions: (f32, f32),
+    pub target: Vector3<f32>,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    // Radians per pixel
+    pub sensibility: f32,
+    // Part of the distance covered by one line of scroll
+    pub zoom_speed: f32,
+    pub lens: Lens,
+
+    up: Vector3<f32>,
+    last_pos: Option<(f32, f32)>,
+}
+
+impl Orbit {
+    pub fn new(
+        win_dimensions: (f32, f32),
+        fovy: f32,
+        znear: f32,
+        zfar: f32,
+    ) -> Orbit {
+        Orbit {
+            win_dimensions,
+            lens: Lens::new(ProjectionMode::perspective(fovy, znear, zfar)),
+            ..Default::default()
+        }
+    }
+
+    pub fn get_aspect(&self) -> f32 {
+        let (width, height) = self.win_dimensions;
+        width / height
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        let direction = Vector3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        );
+        self.target + direction * self.distance
+    }
+
+    // Unit vectors pointing to the right and to the top of
+    // the screen, in world space
+    fn get_axes(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let front = (self.target - self.get_position()).normalize();
+        let right = front.cross(&self.up).normalize();
+        (right, right.cross(&front))
+    }
+
+    /// Offsets are in pixels, dragging to the right turns the
+    /// camera to the left of the target.
+    pub fn rotate(&mut self, x_offset: f32, y_offset: f32) {
+        self.yaw -= x_offset * self.sensibility;
+        self.pitch += y_offset * self.sensibility;
+
+        if self.pitch > MAX_PITCH {
+            self.pitch = MAX_PITCH
+        }
+        if self.pitch < -MAX_PITCH {
+            self.pitch = -MAX_PITCH
+        }
+    }
+
+    /// Move the target in the plane of the screen, so the point
+    /// under the cursor follows it. Offsets are in pixels.
+    pub fn pan(&mut self, x_offset: f32, y_offset: f32) {
+        let (right, up) = self.get_axes();
+        let view_height = self.lens.mode.view_height(self.distance);
+        let pixel_size = view_height / self.win_dimensions.1;
+
+        self.target += (up * y_offset - right * x_offset) * pixel_size;
+    }
+
+    /// Move towards the target, `lines` are the ones given by
+    /// the scroll wheel, positive when scrolling forward. The
+    /// orthographic view shrinks the same way.
+    pub fn dolly(&mut self, lines: f32) {
+        let distance = self.distance * (1. - self.zoom_speed).powf(lines);
+        let distance = distance.max(self.min_distance);
+
+        if let ProjectionMode::Orthographic { ref mut height, .. } =
+            self.lens.mode
+        {
+            *height *= distance / self.distance;
+        }
+        self.distance = distance;
+    }
+
+    /// Rotate or pan from the last cursor position, the cursor
+    /// is only followed while `drag` is some.
+    pub fn drag(&mut self, pos_x: f32, pos_y: f32, drag: Option<OrbitDrag>) {
+        let last_pos = self.last_pos;
+        self.last_pos = drag.map(|_| (pos_x, pos_y));
+
+        let (last_pos_x, last_pos_y) = match last_pos {
+            Some(last_pos) => last_pos,
+            None => return,
+        };
+        let (x_offset, y_offset) = (pos_x - last_pos_x, pos_y - last_pos_y);
+
+        match drag {
+            Some(OrbitDrag::Rotate) => self.rotate(x_offset, y_offset),
+            Some(OrbitDrag::Pan) => self.pan(x_offset, y_offset),
+            None => (),
+        }
+    }
+
+    /// Look at the center of a bounding box, close enough for
+    /// the whole box to be visible. The far plane is pushed
+    /// back if the box doesn't fit in. Boxes smaller than the
+    /// minimum distance are framed as if they were that large.
+    pub fn frame(&mut self, min: Vector3<f32>, max: Vector3<f32>) {
+        let radius = ((max - min).norm() / 2.).max(self.min_distance);
+        let aspect = self.get_aspect();
+        self.target = (min + max) / 2.;
+
+        match self.lens.mode {
+            ProjectionMode::Perspective { fovy, .. } => {
+                let fovx = 2. * ((fovy / 2.).tan() * aspect).atan();
+                let fov = fovy.min(fovx);
+                self.distance = radius / (fov / 2.).sin();
+            }
+            ProjectionMode::Orthographic {
+                ref mut height,
+                znear,
+                ..
+            } => {
+                // The width is the smallest side on tall windows
+                *height = 2. * radius * aspect.recip().max(1.);
+                self.distance = radius + znear;
+            }
+        }
+
+        self.distance = self.distance.max(self.min_distance);
+        let far = self.lens.mode.get_far().max(self.distance + radius);
+        self.lens.mode.set_far(far);
+    }
+
+    pub fn set_fovy(&mut self, fovy: f32) {
+        if let ProjectionMode::Perspective {
+            fovy: ref mut value,
+            ..
+        } = self.lens.mode
+        {
+            *value = fovy;
+        }
+    }
+}
+
+impl Camera for Orbit {
+    fn get_view(&self) -> View {
+        let eye = Point3::from_coordinates(self.get_position());
+        let target = Point3::from_coordinates(self.target);
+
+        Matrix4::look_at_rh(&eye, &target, &self.up)
+    }
+
+    fn get_projection(&self) -> Projection {
+        self.lens.to_matrix(self.get_aspect())
+    }
+
+    fn get_lens(&self) -> &Lens {
+        &self.lens
+    }
+
+    fn get_lens_mut(&mut self) -> &mut Lens {
+        &mut self.lens
+    }
+
+    fn get_win_dimensions(&self) -> (f32, f32) {
+        self.win_dimensions
+    }
+
+    fn set_win_dimensions(&mut self, dimensions: (f32, f32)) {
+        self.win_dimensions = dimensions;
+    }
+}
+
+// Default values
+impl Default for Orbit {
+    fn default() -> Orbit {
+        Orbit {
+            win_dimensions: (800., 600.),
+            target: Vector3::zeros(),
+            distance: 3.,
+            min_distance: 0.01,
+            yaw: 0.,
+            pitch: 0.,
+            sensibility: 0.01,
+            zoom_speed: 0.1,
+            lens: Lens::new(ProjectionMode::perspective(FRAC_PI_4, 0.1, 100.)),
+
+            up: Vector3::new(0., 1., 0.),
+            last_pos: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vector_eq(actual: Vector3<f32>, expected: Vector3<f32>) {
+        assert!(
+            (actual - expected).norm() < 1e-5,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn position_starts_on_the_z_axis_of_the_target() {
+        let mut orbit = Orbit::default();
+        orbit.target = Vector3::new(1., 2., 3.);
+        assert_vector_eq(orbit.get_position(), Vector3::new(1., 2., 6.));
+    }
+
+    #[test]
+    fn rotate_turns_around_the_target() {
+        let mut orbit = Orbit::default();
+        let pixels = FRAC_PI_2 / orbit.sensibility;
+        // A quarter turn to the left, dragging to the right
+        orbit.rotate(pixels, 0.);
+        assert_vector_eq(orbit.get_position(), Vector3::new(-3., 0., 0.));
+        assert_vector_eq(orbit.target, Vector3::zeros());
+
+        let mut orbit = Orbit::default();
+        orbit.rotate(0., pixels / 2.);
+        let position = orbit.get_position();
+        assert!((position.norm() - 3.).abs() < 1e-5);
+        assert!((position.y - position.z).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rotate_clamps_the_pitch() {
+        let mut orbit = Orbit::default();
+        orbit.rotate(0., 1000.);
+        assert!((orbit.pitch - MAX_PITCH).abs() < 1e-6);
+        orbit.rotate(0., -5000.);
+        assert!((orbit.pitch + MAX_PITCH).abs() < 1e-6);
+        assert!(orbit.get_view().iter().all(|value| value.is_finite()));
+    }
+
+    #[test]
+    fn pan_follows_the_cursor() {
+        let mut orbit = Orbit::default();
+        orbit
+            .lens
+            .set_mode(ProjectionMode::orthographic(6., 0.1, 100.));
+        // One pixel is 6 / 600 world units
+        orbit.pan(100., 50.);
+        assert_vector_eq(orbit.target, Vector3::new(-1., 0.5, 0.));
+        assert_vector_eq(orbit.get_position(), Vector3::new(-1., 0.5, 3.));
+
+        let mut orbit = Orbit::default();
+        let height = orbit.lens.mode.view_height(orbit.distance);
+        orbit.pan(0., 600.);
+        assert_vector_eq(orbit.target, Vector3::new(0., height, 0.));
+    }
+
+    #[test]
+    fn dolly_moves_towards_the_target() {
+        let mut orbit = Orbit::default();
+        orbit.dolly(1.);
+        assert!((orbit.distance - 2.7).abs() < 1e-5);
+        orbit.dolly(-1.);
+        assert!((orbit.distance - 3.).abs() < 1e-5);
+        orbit.dolly(1000.);
+        assert!((orbit.distance - orbit.min_distance).abs() < 1e-6);
+    }
+
+    #[test]
+    fn dolly_shrinks_the_orthographic_view() {
+        let mut orbit = Orbit::default();
+        orbit
+            .lens
+            .set_mode(ProjectionMode::orthographic(6., 0.1, 100.));
+        orbit.dolly(1.);
+        assert!((orbit.lens.mode.view_height(0.) - 5.4).abs() < 1e-5);
+    }
+
+    #[test]
+    fn drag_only_follows_the_cursor_while_dragging() {
+        let mut orbit = Orbit::default();
+        orbit.drag(10., 10., None);
+        orbit.drag(20., 10., Some(OrbitDrag::Rotate));
+        assert!(orbit.yaw.abs() < 1e-6);
+        orbit.drag(30., 10., Some(OrbitDrag::Rotate));
+        assert!((orbit.yaw + 10. * orbit.sensibility).abs() < 1e-6);
+        orbit.drag(100., 10., None);
+        orbit.drag(0., 10., Some(OrbitDrag::Pan));
+        assert_vector_eq(orbit.target, Vector3::zeros());
+    }
+
+    #[test]
+    fn frame_fits_the_box() {
+        let (min, max) =
+            (Vector3::new(-1., -1., -1.), Vector3::new(3., 1., 1.));
+        let mut orbit = Orbit::default();
+        orbit.frame(min, max);
+        assert_vector_eq(orbit.target, Vector3::new(1., 0., 0.));
+        let radius = (max - min).norm() / 2.;
+        // The bounding sphere touches the sides of the view
+        assert!(
+            (orbit.distance * (FRAC_PI_4 / 2.).sin() - radius).abs() < 1e-4
+        );
+        assert!(orbit.lens.mode.get_far() >= orbit.distance + radius);
+
+        let mut orbit = Orbit::default();
+        orbit
+            .lens
+            .set_mode(ProjectionMode::orthographic(1., 0.1, 1.));
+        orbit.frame(min, max);
+        assert!((orbit.lens.mode.view_height(0.) - 2. * radius).abs() < 1e-4);
+        assert!((orbit.distance - radius - 0.1).abs() < 1e-4);
+        assert!(orbit.lens.mode.get_far() >= orbit.distance + radius);
+    }
+
+    #[test]
+    fn frame_keeps_an_extent_for_empty_boxes() {
+        let point = Vector3::new(1., 2., 3.);
+        let mut orbit = Orbit::default();
+        orbit
+            .lens
+            .set_mode(ProjectionMode::orthographic(1., 0.1, 100.));
+        orbit.frame(point, point);
+        assert_eq!(orbit.target, point);
+        assert!(orbit.lens.mode.view_height(orbit.distance) > 0.);
+        assert!(orbit.get_projection().iter().all(|value| value.is_finite()));
+
+        let mut orbit = Orbit::default();
+        orbit.frame(point, point);
+        assert!(orbit.distance >= orbit.min_distance);
+        assert!(orbit.get_view().iter().all(|value| value.is_finite()));
+    }
+}
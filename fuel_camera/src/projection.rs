@@ -0,0 +1,148 @@
+use na::{Orthographic3, Perspective3};
+use Projection;
+
+/// How a camera projects the scene on the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProjectionMode {
+    Perspective {
+        fovy: f32,
+        znear: f32,
+        zfar: f32,
+    },
+    /// Height of the view in world units, the width follows
+    /// the aspect of the window.
+    Orthographic {
+        height: f32,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+impl ProjectionMode {
+    pub fn perspective(fovy: f32, znear: f32, zfar: f32) -> Self {
+        ProjectionMode::Perspective { fovy, znear, zfar }
+    }
+
+    pub fn orthographic(height: f32, znear: f32, zfar: f32) -> Self {
+        ProjectionMode::Orthographic {
+            height,
+            znear,
+            zfar,
+        }
+    }
+
+    /// Height of the view at `distance` from the camera, in
+    /// world units.
+    pub fn view_height(&self, distance: f32) -> f32 {
+        match *self {
+            ProjectionMode::Perspective { fovy, .. } => {
+                2. * distance * (fovy / 2.).tan()
+            }
+            ProjectionMode::Orthographic { height, .. } => height,
+        }
+    }
+
+    pub fn get_far(&self) -> f32 {
+        match *self {
+            ProjectionMode::Perspective { zfar, .. }
+            | ProjectionMode::Orthographic { zfar, .. } => zfar,
+        }
+    }
+
+    pub fn set_far(&mut self, far: f32) {
+        match *self {
+            ProjectionMode::Perspective { ref mut zfar, .. }
+            | ProjectionMode::Orthographic { ref mut zfar, .. } => *zfar = far,
+        }
+    }
+
+    pub fn to_matrix(&self, aspect: f32) -> Projection {
+        match *self {
+            ProjectionMode::Perspective { fovy, znear, zfar } => {
+                Perspective3::new(aspect, fovy, znear, zfar).to_homogeneous()
+            }
+            ProjectionMode::Orthographic {
+                height,
+                znear,
+                zfar,
+            } => {
+                let (top, right) = (height / 2., height * aspect / 2.);
+                Orthographic3::new(-right, right, -top, top, znear, zfar)
+                    .to_homogeneous()
+            }
+        }
+    }
+}
+
+/// Projection of a camera, which can move from one mode to
+/// another over time. The transition blends the matrices of
+/// both modes, it looks best when the orthographic height
+/// matches the `view_height` of the perspective at the target.
+#[derive(Clone, Debug)]
+pub struct Lens {
+    pub mode: ProjectionMode,
+    // Mode the transition started from
+    from: Option<ProjectionMode>,
+    // Seconds
+    elapsed: f32,
+    duration: f32,
+}
+
+impl Lens {
+    pub fn new(mode: ProjectionMode) -> Self {
+        Lens {
+            mode,
+            from: None,
+            elapsed: 0.,
+            duration: 0.,
+        }
+    }
+
+    /// Switch right away, stopping any transition.
+    pub fn set_mode(&mut self, mode: ProjectionMode) {
+        self.mode = mode;
+        self.from = None;
+    }
+
+    /// Move to a mode in `duration` seconds. A transition in
+    /// progress jumps to its end first.
+    pub fn transition_to(&mut self, mode: ProjectionMode, duration: f32) {
+        if duration <= 0. {
+            return self.set_mode(mode);
+        }
+
+        self.from = Some(self.mode);
+        self.mode = mode;
+        self.elapsed = 0.;
+        self.duration = duration;
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.from.is_some()
+    }
+
+    /// Advance the transition by the delta time, in seconds.
+    pub fn update(&mut self, dt: f32) {
+        if self.from.is_none() {
+            return;
+        }
+
+        self.elapsed += dt;
+        if self.elapsed >= self.duration {
+            self.from = None;
+        }
+    }
+
+    pub fn to_matrix(&self, aspect: f32) -> Projection {
+        let to = self.mode.to_matrix(aspect);
+        match self.from {
+            Some(from) => {
+                let t = (self.elapsed / self.duration).min(1.);
+                // Ease in and out
+                let t = t * t * (3. - 2. * t);
+                from.to_matrix(aspect) * (1. - t) + to * t
+            }
+            None => to,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::error;
+use std::fmt;
+use uuid::Uuid;
+
+/// Why the hierarchy of a scene couldn't be changed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneError {
+    ObjectNotFound(Uuid),
+    /// The new parent is the object itself or one of its children.
+    Cycle {
+        child: Uuid,
+        parent: Uuid,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::ObjectNotFound(key) => {
+                write!(f, "Object {} is not in the scene", key)
+            }
+            SceneError::Cycle { child, parent } => write!(
+                f,
+                "Object {} can't be a child of {}, it is one of its parents",
+                child, parent
+            ),
+        }
+    }
+}
+
+impl error::Error for SceneError {}
@@ -1,8 +1,11 @@
 extern crate fuel_camera;
-extern crate gl;
+extern crate fuel_render;
 extern crate nalgebra as na;
 extern crate uuid;
 
+mod error;
 mod scene;
 
-pub use scene::{ObjectTypes, Scene, SceneObject};
+pub use error::SceneError;
+pub use fuel_render::{ObjectTypes, SceneObject};
+pub use scene::{Hierarchy, Pick, Scene};
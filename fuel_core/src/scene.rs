@@ -1,81 +1,459 @@
-use fuel_camera::{FirstPerson, Projection, View};
-use gl;
-use na::Vector3;
+use error::SceneError;
+use fuel_camera::{Camera, FirstPerson};
+use fuel_render::backend::PolygonMode;
+use fuel_render::{
+    Culling, CullingStats, Frustum, LightSource, ObjectTypes, Ray,
+    RenderBackend, SceneObject, ShaderError,
+};
+use na::{Matrix4, Vector3};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-// Enum of all type that an object
-// can have
-pub enum ObjectTypes {
-    POLYGON,
-    LIGHT,
-    MODEL,
+/// Object of the scene with its place in the hierarchy.
+struct Node {
+    object: Box<SceneObject>,
+    parent: Option<Uuid>,
+    children: Vec<Uuid>,
 }
 
-// Object to put in the scene
-// Position in the spacial scene with render method
-// is needed
-pub trait SceneObject {
-    fn set_position(&mut self, f32, f32, f32);
-    fn get_type(&self) -> ObjectTypes;
-    fn render(&self, Projection, View);
-    fn set_color(&self, name: &str, Vector3<f32>);
-    fn set_scale(&mut self, scale: f32);
-}
-
-#[derive(Default)]
-pub struct Scene {
-    objects: HashMap<Uuid, Box<SceneObject>>,
-    pub camera: FirstPerson,
+/// Objects are organized in a hierarchy, the transform of an
+/// object is relative to its parent. Objects without parent
+/// are relative to the world.
+///
+/// The camera can be any of `fuel_camera`, or a `Box<Camera>`
+/// to switch between them.
+///
+/// Primitives outside of the view of the camera are not drawn,
+/// unless frustum culling is disabled. They are lit by the lights
+/// of the scene, or by a default directional light without any.
+///
+/// Objects are drawn and iterated in the order of the hierarchy,
+/// not the one of their storage, so frames are reproducible.
+pub struct Scene<C: Camera = FirstPerson> {
+    nodes: HashMap<Uuid, Node>,
+    // Objects without parent, in the order they were added
+    roots: Vec<Uuid>,
+    pub camera: C,
+    polygon_mode: PolygonMode,
+    frustum_culling: bool,
+    culling_stats: CullingStats,
 }
 
 impl Scene {
     pub fn new(width: f32, height: f32, fov: f32, near: f32, far: f32) -> Self {
         let camera = FirstPerson::new((width, height), fov, near, far);
+        Scene::with_camera(camera)
+    }
+}
+
+impl<C: Camera + Default> Default for Scene<C> {
+    fn default() -> Self {
+        Scene::with_camera(C::default())
+    }
+}
+
+impl<C: Camera> Scene<C> {
+    pub fn with_camera(camera: C) -> Self {
         Scene {
+            nodes: HashMap::new(),
+            roots: vec![],
             camera,
-            ..Default::default()
+            polygon_mode: PolygonMode::default(),
+            frustum_culling: true,
+            culling_stats: CullingStats::default(),
+        }
+    }
+
+    /// Follow a resize of the window, the camera gets the size
+    /// of the window and the viewport the one of the frame
+    /// buffer. They differ on HiDPI screens. Minimized windows
+    /// have no size, they are ignored.
+    pub fn resize(
+        &mut self,
+        backend: &mut RenderBackend,
+        dimensions: (f32, f32),
+        framebuffer: (u32, u32),
+    ) {
+        if framebuffer.0 == 0 || framebuffer.1 == 0 {
+            return;
         }
+
+        self.camera.set_win_dimensions(dimensions);
+        backend.set_viewport(framebuffer.0, framebuffer.1);
     }
 
     // Draw wireframe polygons
-    pub fn set_line_mode(&self) {
-        unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE) }
+    pub fn set_line_mode(&mut self) {
+        self.polygon_mode = PolygonMode::Line;
     }
 
-    pub fn set_point_mode(&self) {
-        unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, gl::POINT) }
+    pub fn set_point_mode(&mut self) {
+        self.polygon_mode = PolygonMode::Point;
     }
 
-    pub fn set_fill_mode(&self) {
-        unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL) }
+    pub fn set_fill_mode(&mut self) {
+        self.polygon_mode = PolygonMode::Fill;
+    }
+
+    pub fn get_polygon_mode(&self) -> PolygonMode {
+        self.polygon_mode
+    }
+
+    pub fn set_frustum_culling(&mut self, enabled: bool) {
+        self.frustum_culling = enabled;
+    }
+
+    /// Primitives drawn and culled by the last render.
+    pub fn get_culling_stats(&self) -> CullingStats {
+        self.culling_stats
     }
 
     pub fn add(&mut self, element: impl SceneObject + 'static) -> Uuid {
         let key = Uuid::new_v4();
-        self.objects.insert(key, Box::new(element));
+        self.nodes.insert(
+            key,
+            Node {
+                object: Box::new(element),
+                parent: None,
+                children: vec![],
+            },
+        );
+        self.roots.push(key);
         key
     }
 
+    /// Add an object with a transform relative to `parent`.
+    pub fn add_child(
+        &mut self,
+        parent: Uuid,
+        element: impl SceneObject + 'static,
+    ) -> Result<Uuid, SceneError> {
+        if !self.nodes.contains_key(&parent) {
+            return Err(SceneError::ObjectNotFound(parent));
+        }
+
+        let key = self.add(element);
+        self.set_parent(key, parent)?;
+        Ok(key)
+    }
+
     pub fn get_object(&mut self, key: Uuid) -> &mut SceneObject {
-        self.objects
-            .get_mut(&key)
-            .map(|x| &mut **x)
-            .expect("Failed to retrieve object")
+        self.try_get_mut(key).expect("Failed to retrieve object")
     }
 
-    // Draw all object into the created scene
-    pub fn render(&self) {
-        unsafe {
-            gl::ClearColor(0., 0., 0., 0.);
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+    pub fn try_get(&self, key: Uuid) -> Option<&SceneObject> {
+        self.nodes.get(&key).map(|node| &*node.object)
+    }
+
+    pub fn try_get_mut(&mut self, key: Uuid) -> Option<&mut SceneObject> {
+        match self.nodes.get_mut(&key) {
+            Some(node) => Some(&mut *node.object),
+            None => None,
+        }
+    }
+
+    pub fn contains(&self, key: Uuid) -> bool {
+        self.nodes.contains_key(&key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Every object with its key, in the order of `hierarchy`.
+    pub fn iter<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (Uuid, &'a SceneObject)> + 'a {
+        let nodes = &self.nodes;
+        self.hierarchy().map(move |key| (key, &*nodes[&key].object))
+    }
+
+    /// Objects of a given type, in the order of `iter`.
+    pub fn filter_by_type<'a>(
+        &'a self,
+        object_type: ObjectTypes,
+    ) -> impl Iterator<Item = (Uuid, &'a SceneObject)> + 'a {
+        self.iter()
+            .filter(move |(_, object)| object.get_type() == object_type)
+    }
+
+    pub fn count_type(&self, object_type: ObjectTypes) -> usize {
+        self.filter_by_type(object_type).count()
+    }
+
+    /// Remove an object with its children, what they sent to
+    /// the graphic card is deleted by the next render.
+    pub fn remove(&mut self, key: Uuid) -> Result<(), SceneError> {
+        if !self.nodes.contains_key(&key) {
+            return Err(SceneError::ObjectNotFound(key));
+        }
+
+        if !self.unlink(key) {
+            self.roots.retain(|root| *root != key);
+        }
+        let mut stack = vec![key];
+        while let Some(key) = stack.pop() {
+            if let Some(node) = self.nodes.remove(&key) {
+                stack.extend(node.children);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove every object, deleting what they sent to the graphic
+    /// card with the other resources dropped since the last render.
+    pub fn clear(&mut self, backend: &mut RenderBackend) {
+        self.nodes.clear();
+        self.roots.clear();
+        backend.collect_garbage();
+    }
+
+    pub fn get_parent(&self, key: Uuid) -> Option<Uuid> {
+        self.nodes.get(&key).and_then(|node| node.parent)
+    }
+
+    pub fn get_children(&self, key: Uuid) -> &[Uuid] {
+        self.nodes
+            .get(&key)
+            .map(|node| node.children.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Move an object and its children under a new parent.
+    /// The local transform is kept, so the object moves with
+    /// its new parent.
+    pub fn set_parent(
+        &mut self,
+        child: Uuid,
+        parent: Uuid,
+    ) -> Result<(), SceneError> {
+        for key in &[child, parent] {
+            if !self.nodes.contains_key(key) {
+                return Err(SceneError::ObjectNotFound(*key));
+            }
+        }
+        // Walk up from the new parent, we shouldn't meet the child
+        let mut ancestor = Some(parent);
+        while let Some(key) = ancestor {
+            if key == child {
+                return Err(SceneError::Cycle { child, parent });
+            }
+            ancestor = self.get_parent(key);
         }
 
+        self.unlink(child);
+        self.roots.retain(|key| *key != child);
+        self.nodes.get_mut(&parent).unwrap().children.push(child);
+        self.nodes.get_mut(&child).unwrap().parent = Some(parent);
+        Ok(())
+    }
+
+    /// Make an object a root of the scene, with its children.
+    /// The local transform is kept, it becomes relative to the world.
+    pub fn detach(&mut self, key: Uuid) -> Result<(), SceneError> {
+        if !self.nodes.contains_key(&key) {
+            return Err(SceneError::ObjectNotFound(key));
+        }
+
+        if self.unlink(key) {
+            self.roots.push(key);
+        }
+        Ok(())
+    }
+
+    // Remove the link between an object and its parent,
+    // return false if it doesn't have any
+    fn unlink(&mut self, key: Uuid) -> bool {
+        let parent = match self.nodes.get_mut(&key) {
+            Some(node) => node.parent.take(),
+            None => None,
+        };
+
+        match parent.and_then(|parent| self.nodes.get_mut(&parent)) {
+            Some(parent) => {
+                parent.children.retain(|child| *child != key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Transform of an object relative to the world.
+    pub fn get_world_matrix(&self, key: Uuid) -> Matrix4<f32> {
+        let mut matrix = Matrix4::identity();
+        let mut ancestor = Some(key);
+        while let Some(node) = ancestor.and_then(|key| self.nodes.get(&key)) {
+            matrix = node.object.get_transform().to_matrix() * matrix;
+            ancestor = node.parent;
+        }
+        matrix
+    }
+
+    /// Every object, parents before their children. Roots and
+    /// children are in the order they were added to the scene.
+    pub fn hierarchy(&self) -> Hierarchy {
+        Hierarchy {
+            nodes: &self.nodes,
+            stack: self.roots.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Move the camera and the objects forward in time, `dt` is
+    /// the time since the last update in seconds, like the delta
+    /// of the window.
+    pub fn update(&mut self, dt: f32) {
+        self.camera.update(dt);
+        let keys: Vec<Uuid> = self.hierarchy().collect();
+        for key in keys {
+            self.nodes.get_mut(&key).unwrap().object.update(dt);
+        }
+    }
+
+    // Draw all object into the created scene
+    // with the given backend, primitives drawn for the
+    // first time fail if their shader can't be setup
+    pub fn render(
+        &mut self,
+        backend: &mut RenderBackend,
+    ) -> Result<(), ShaderError> {
+        // Objects dropped since the last frame
+        backend.collect_garbage();
+        backend.clear([0., 0., 0., 0.]);
+        backend.set_polygon_mode(self.polygon_mode);
+        backend.set_depth_test(true);
+
         let projection = self.camera.get_projection();
         let view = self.camera.get_view();
+        let frustum = if self.frustum_culling {
+            Some(Frustum::from_matrix(&(projection * view)))
+        } else {
+            None
+        };
+        let mut culling = Culling::new(frustum);
+        let world_matrices = self.get_world_matrices();
+        let lights = self.get_light_sources(&world_matrices);
+
+        for (key, world) in world_matrices {
+            let node = self.nodes.get_mut(&key).unwrap();
+            node.object.render(
+                backend,
+                projection,
+                view,
+                &world,
+                &lights,
+                &mut culling,
+            )?;
+        }
+        self.culling_stats = culling.stats;
+        Ok(())
+    }
+
+    /// Ray going through a position of the window, from the
+    /// camera. Positions are the ones of the cursor.
+    pub fn get_cursor_ray(&self, position: (f32, f32)) -> Option<Ray> {
+        Ray::from_screen(
+            position,
+            self.camera.get_win_dimensions(),
+            &self.camera.get_projection(),
+            &self.camera.get_view(),
+        )
+    }
+
+    /// Closest object under a position of the window.
+    pub fn pick(&self, position: (f32, f32)) -> Option<Pick> {
+        self.cast_ray(&self.get_cursor_ray(position)?)
+    }
+
+    /// Closest object hit by a world space ray. Bounding volumes
+    /// are tested before the triangles of the primitives.
+    pub fn cast_ray(&self, ray: &Ray) -> Option<Pick> {
+        let ray = Ray::new(ray.origin, ray.direction.normalize());
+
+        self.get_world_matrices()
+            .into_iter()
+            .filter_map(|(key, world)| {
+                let hit =
+                    self.nodes[&key].object.intersect_ray(&ray, &world)?;
+                Some(Pick {
+                    key,
+                    point: hit.point,
+                    normal: hit.normal,
+                    distance: hit.distance,
+                })
+            })
+            .fold(None, |closest, pick| match closest {
+                Some(closest) if closest.distance <= pick.distance => {
+                    Some(closest)
+                }
+                _ => Some(pick),
+            })
+    }
+
+    /// Enabled lights of the scene in world space, in the order
+    /// of `hierarchy`. Only the first `MAX_LIGHTS` are rendered.
+    pub fn get_lights(&self) -> Vec<LightSource> {
+        self.get_light_sources(&self.get_world_matrices())
+    }
+
+    fn get_light_sources(
+        &self,
+        world_matrices: &[(Uuid, Matrix4<f32>)],
+    ) -> Vec<LightSource> {
+        world_matrices
+            .iter()
+            .flat_map(|(key, world)| {
+                self.nodes[key].object.get_light_sources(world)
+            })
+            .collect()
+    }
+
+    // Parents come first, so their world matrix is known
+    fn get_world_matrices(&self) -> Vec<(Uuid, Matrix4<f32>)> {
+        let mut world_matrices: HashMap<Uuid, Matrix4<f32>> = HashMap::new();
+        self.hierarchy()
+            .map(|key| {
+                let node = &self.nodes[&key];
+                let parent = node
+                    .parent
+                    .and_then(|parent| world_matrices.get(&parent))
+                    .cloned()
+                    .unwrap_or_else(Matrix4::identity);
+                let world = parent * node.object.get_transform().to_matrix();
+
+                world_matrices.insert(key, world);
+                (key, world)
+            })
+            .collect()
+    }
+}
+
+/// Object under the cursor, the hit is in world space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pick {
+    pub key: Uuid,
+    pub point: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+/// Depth first iterator over the objects of a scene.
+pub struct Hierarchy<'a> {
+    nodes: &'a HashMap<Uuid, Node>,
+    stack: Vec<Uuid>,
+}
+
+impl<'a> Iterator for Hierarchy<'a> {
+    type Item = Uuid;
 
-        for object in self.objects.values() {
-            object.render(projection, view);
+    fn next(&mut self) -> Option<Uuid> {
+        let key = self.stack.pop()?;
+        if let Some(node) = self.nodes.get(&key) {
+            self.stack.extend(node.children.iter().rev());
         }
+        Some(key)
     }
 }
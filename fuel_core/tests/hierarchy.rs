@@ -0,0 +1,159 @@
+extern crate fuel_core;
+extern crate fuel_render;
+extern crate nalgebra as na;
+
+use fuel_core::{Scene, SceneError};
+use fuel_render::Model;
+use na::Vector3;
+
+fn empty() -> Model {
+    Model::new(Default::default(), vec![], vec![])
+}
+
+fn scene() -> Scene {
+    Scene::new(800., 600., 45., 0.1, 100.)
+}
+
+#[test]
+fn set_parent_moves_the_subtree() {
+    let mut scene = scene();
+    let (a, b) = (scene.add(empty()), scene.add(empty()));
+    let c = scene.add_child(b, empty()).unwrap();
+
+    scene.set_parent(b, a).unwrap();
+    assert_eq!(scene.get_parent(b), Some(a));
+    assert_eq!(scene.get_children(a), &[b]);
+    assert_eq!(scene.get_parent(c), Some(b));
+    let order: Vec<_> = scene.hierarchy().collect();
+    assert_eq!(order, vec![a, b, c]);
+
+    // Moving again leaves the previous parent
+    scene.set_parent(c, a).unwrap();
+    assert!(scene.get_children(b).is_empty());
+    assert_eq!(scene.get_children(a), &[b, c]);
+}
+
+#[test]
+fn detach_makes_the_last_root() {
+    let mut scene = scene();
+    let a = scene.add(empty());
+    let b = scene.add_child(a, empty()).unwrap();
+    let c = scene.add_child(b, empty()).unwrap();
+    let d = scene.add(empty());
+
+    scene.detach(b).unwrap();
+    assert_eq!(scene.get_parent(b), None);
+    assert!(scene.get_children(a).is_empty());
+    let order: Vec<_> = scene.hierarchy().collect();
+    assert_eq!(order, vec![a, d, b, c]);
+
+    // Roots stay where they are
+    scene.detach(a).unwrap();
+    let order: Vec<_> = scene.hierarchy().collect();
+    assert_eq!(order, vec![a, d, b, c]);
+}
+
+#[test]
+fn cycles_are_refused() {
+    let mut scene = scene();
+    let a = scene.add(empty());
+    let b = scene.add_child(a, empty()).unwrap();
+    let c = scene.add_child(b, empty()).unwrap();
+
+    for (child, parent) in &[(a, c), (a, b), (b, b)] {
+        match scene.set_parent(*child, *parent) {
+            Err(SceneError::Cycle { .. }) => {}
+            other => panic!("Expected a cycle, got {:?}", other),
+        }
+    }
+    // Nothing moved
+    assert_eq!(scene.get_parent(a), None);
+    assert_eq!(scene.get_children(b), &[c]);
+}
+
+#[test]
+fn unknown_objects_are_refused() {
+    let mut scene = scene();
+    let a = scene.add(empty());
+    let removed = scene.add(empty());
+    scene.remove(removed).unwrap();
+
+    let not_found = |result: Result<(), SceneError>| match result {
+        Err(SceneError::ObjectNotFound(key)) => assert_eq!(key, removed),
+        other => panic!("Expected an unknown object, got {:?}", other),
+    };
+    not_found(scene.set_parent(a, removed));
+    not_found(scene.set_parent(removed, a));
+    not_found(scene.detach(removed));
+    not_found(scene.remove(removed));
+    not_found(scene.add_child(removed, empty()).map(|_| ()));
+}
+
+#[test]
+fn world_matrices_compose_the_parents() {
+    let mut scene = scene();
+    let a = scene.add(empty());
+    let b = scene.add_child(a, empty()).unwrap();
+    let c = scene.add_child(b, empty()).unwrap();
+    scene.get_object(a).set_position(1., 0., 0.);
+    scene.get_object(a).set_scale(2., 2., 2.);
+    scene.get_object(b).set_position(0., 1., 0.);
+    scene.get_object(c).set_position(0., 0., 1.);
+
+    // Where the origin of an object is in the world
+    let origin = |scene: &Scene, key| {
+        let matrix = scene.get_world_matrix(key);
+        Vector3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)])
+    };
+    let expected = [
+        (a, Vector3::new(1., 0., 0.)),
+        (b, Vector3::new(1., 2., 0.)),
+        (c, Vector3::new(1., 2., 2.)),
+    ];
+    for (key, position) in &expected {
+        assert!((origin(&scene, *key) - position).norm() < 1e-5);
+    }
+
+    // Detached, the local transform becomes relative to the world
+    scene.detach(b).unwrap();
+    assert!((origin(&scene, b) - Vector3::y()).norm() < 1e-5);
+}
+
+#[test]
+fn roots_and_children_keep_their_order() {
+    let mut scene = scene();
+    let a = scene.add(empty());
+    let b = scene.add(empty());
+    let a1 = scene.add_child(a, empty()).unwrap();
+    let b1 = scene.add_child(b, empty()).unwrap();
+    let a2 = scene.add_child(a, empty()).unwrap();
+    let a11 = scene.add_child(a1, empty()).unwrap();
+
+    // Depth first, in the order objects were added
+    let expected = vec![a, a1, a11, a2, b, b1];
+    assert_eq!(scene.hierarchy().collect::<Vec<_>>(), expected);
+    let keys: Vec<_> = scene.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn remove_drops_the_subtree() {
+    let mut scene = scene();
+    let a = scene.add(empty());
+    let b = scene.add_child(a, empty()).unwrap();
+    let c = scene.add_child(b, empty()).unwrap();
+    let d = scene.add_child(a, empty()).unwrap();
+    let e = scene.add(empty());
+
+    scene.remove(b).unwrap();
+    for key in &[b, c] {
+        assert!(!scene.contains(*key));
+    }
+    assert_eq!(scene.get_children(a), &[d]);
+    assert_eq!(scene.len(), 3);
+
+    scene.remove(a).unwrap();
+    assert!(!scene.contains(d));
+    let order: Vec<_> = scene.hierarchy().collect();
+    assert_eq!(order, vec![e]);
+}
@@ -0,0 +1,69 @@
+extern crate fuel_core;
+extern crate fuel_render;
+extern crate nalgebra as na;
+
+use fuel_core::Scene;
+use fuel_render::{Attribute, Mesh, Model, Primitive, Ray, Vertex};
+use na::Vector3;
+
+// Triangle around the origin, facing +z
+fn triangle() -> Model {
+    let vertices = vec![
+        Vector3::new(-1., -1., 0.),
+        Vector3::new(1., -1., 0.),
+        Vector3::new(0., 1., 0.),
+    ]
+    .into_iter()
+    .map(|position| Vertex {
+        position,
+        normal: Vector3::z(),
+        ..Default::default()
+    })
+    .collect();
+    let primitive =
+        Primitive::new(vertices, Some(vec![0, 1, 2]), &[Attribute::Position]);
+    Model::new(Default::default(), vec![Mesh::new(vec![primitive])], vec![])
+}
+
+#[test]
+fn pick_returns_the_nearest_object_under_the_screen_center() {
+    // The camera is at (0, 0, 3), looking down -z
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    let far = scene.add(triangle());
+    scene.get_object(far).set_position(0., 0., -2.);
+    let near = scene.add(triangle());
+
+    let pick = scene.pick((400., 300.)).unwrap();
+    assert_eq!(pick.key, near);
+    assert!(pick.point.norm() < 1e-4, "{:?}", pick.point);
+    assert!((pick.normal - Vector3::z()).norm() < 1e-4);
+    // From the near plane of the camera
+    assert!((pick.distance - 2.9).abs() < 1e-3, "{}", pick.distance);
+
+    scene.remove(near).unwrap();
+    assert_eq!(scene.pick((400., 300.)).unwrap().key, far);
+}
+
+#[test]
+fn pick_misses_beside_the_objects() {
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    scene.add(triangle());
+    assert!(scene.pick((0., 0.)).is_none());
+    assert!(scene.pick((800., 600.)).is_none());
+}
+
+#[test]
+fn cast_ray_hits_moved_objects() {
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    let key = scene.add(triangle());
+    scene.get_object(key).set_position(5., 0., 0.);
+
+    let ray = Ray::new(Vector3::new(5., 0., 10.), -Vector3::z() * 2.);
+    let pick = scene.cast_ray(&ray).unwrap();
+    assert_eq!(pick.key, key);
+    assert!((pick.distance - 10.).abs() < 1e-4);
+    assert!((pick.point - Vector3::new(5., 0., 0.)).norm() < 1e-4);
+
+    let ray = Ray::new(Vector3::new(0., 0., 10.), -Vector3::z());
+    assert!(scene.cast_ray(&ray).is_none());
+}
@@ -0,0 +1,152 @@
+extern crate fuel_camera;
+extern crate fuel_core;
+extern crate fuel_render;
+extern crate nalgebra as na;
+
+use fuel_camera::{Camera, ProjectionMode};
+use fuel_core::Scene;
+use fuel_render::backend::Command;
+use fuel_render::CullingStats;
+use fuel_render::{
+    Attribute, HeadlessBackend, Mesh, Model, Primitive, RenderBackend, Vertex,
+};
+use na::{Matrix4, Vector3};
+
+fn triangle() -> Model {
+    let vertices = vec![
+        Vector3::new(0., 0., 0.),
+        Vector3::new(1., 0., 0.),
+        Vector3::new(0., 1., 0.),
+    ]
+    .into_iter()
+    .map(|position| Vertex {
+        position,
+        normal: Vector3::z(),
+        ..Default::default()
+    })
+    .collect();
+    let attributes = [Attribute::Position, Attribute::Normal];
+    let primitive = Primitive::new(vertices, Some(vec![0, 1, 2]), &attributes);
+    Model::new(Default::default(), vec![Mesh::new(vec![primitive])], vec![])
+}
+
+fn assert_matrix_eq(actual: &Matrix4<f32>, expected: &Matrix4<f32>) {
+    assert!(
+        (actual - expected).iter().all(|value| value.abs() < 1e-5),
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn render_records_draw_elements_with_mvp() {
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    scene.set_frustum_culling(false);
+    scene.add(triangle());
+    let moved = scene.add(triangle());
+    scene.get_object(moved).set_position(1., 2., 3.);
+
+    let mut backend = HeadlessBackend::new();
+    scene.render(&mut backend).unwrap();
+
+    let draw_calls = backend.draw_calls();
+    assert_eq!(draw_calls.len(), 2);
+    let view_projection =
+        scene.camera.get_projection() * scene.camera.get_view();
+    let models = [
+        Matrix4::identity(),
+        Matrix4::new_translation(&Vector3::new(1., 2., 3.)),
+    ];
+    for (draw_call, model) in draw_calls.iter().zip(&models) {
+        match draw_call.command {
+            Command::DrawElements { count, .. } => assert_eq!(count, 3),
+            ref command => panic!("Expected DrawElements, got {:?}", command),
+        }
+        let mvp = draw_call.mvp().expect("Draw call without mvp");
+        assert_matrix_eq(&mvp, &(view_projection * model));
+    }
+}
+
+#[test]
+fn render_uploads_once() {
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    scene.set_frustum_culling(false);
+    scene.add(triangle());
+
+    let mut backend = HeadlessBackend::new();
+    scene.render(&mut backend).unwrap();
+    backend.take_commands();
+    scene.render(&mut backend).unwrap();
+
+    let uploads = backend.commands().iter().filter(|command| match command {
+        Command::CreateVertexArray(_) | Command::CreateProgram { .. } => true,
+        _ => false,
+    });
+    assert_eq!(uploads.count(), 0);
+    assert_eq!(backend.draw_calls().len(), 1);
+}
+
+#[test]
+fn culling_stats_count_objects_behind_the_camera() {
+    // The camera is at (0, 0, 3), looking down -z
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    scene.add(triangle());
+    let behind = scene.add(triangle());
+    scene.get_object(behind).set_position(0., 0., 10.);
+
+    let mut backend = HeadlessBackend::new();
+    scene.render(&mut backend).unwrap();
+    assert_eq!(
+        scene.get_culling_stats(),
+        CullingStats {
+            drawn: 1,
+            culled: 1,
+        }
+    );
+    assert_eq!(backend.draw_calls().len(), 1);
+
+    scene.set_frustum_culling(false);
+    scene.render(&mut backend).unwrap();
+    assert_eq!(
+        scene.get_culling_stats(),
+        CullingStats {
+            drawn: 2,
+            culled: 0,
+        }
+    );
+}
+
+#[test]
+fn removed_objects_are_deleted_by_the_next_render() {
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    scene.set_frustum_culling(false);
+    let parent = scene.add(triangle());
+    scene.add_child(parent, triangle()).unwrap();
+    scene.add(triangle());
+
+    let mut backend = HeadlessBackend::new();
+    scene.render(&mut backend).unwrap();
+    assert_eq!(backend.live_resources().vertex_arrays, 3);
+    backend.take_commands();
+
+    scene.remove(parent).unwrap();
+    assert!(backend.commands().is_empty());
+    scene.render(&mut backend).unwrap();
+    assert_eq!(backend.live_resources().vertex_arrays, 1);
+    assert_eq!(backend.draw_calls().len(), 1);
+
+    scene.clear(&mut backend);
+    assert_eq!(backend.live_resources().total(), 0);
+}
+
+#[test]
+fn update_advances_the_camera() {
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    let mode = ProjectionMode::orthographic(2., 0.1, 100.);
+    scene.camera.lens.transition_to(mode, 1.);
+    scene.update(0.5);
+    assert!(scene.camera.lens.is_transitioning());
+    scene.update(0.5);
+    assert!(!scene.camera.lens.is_transitioning());
+}
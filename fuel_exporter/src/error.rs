@@ -0,0 +1,67 @@
+use image;
+use serde_json;
+use std::error;
+use std::fmt;
+use std::io;
+
+/// Why a scene couldn't be exported.
+#[derive(Debug)]
+pub enum ExportError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The extension of the file isn't a known format.
+    UnsupportedFormat(String),
+    /// The pixels of a texture couldn't be encoded, with its index
+    /// in the file.
+    Image(usize, image::ImageError),
+    /// The .glb file would be larger than its 32 bits length, in
+    /// bytes.
+    TooLarge(usize),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::Io(error) => {
+                write!(f, "Failed to write file: {}", error)
+            }
+            ExportError::Json(error) => {
+                write!(f, "Failed to write glTF: {}", error)
+            }
+            ExportError::UnsupportedFormat(extension) => {
+                write!(f, "Files .{} can't be exported", extension)
+            }
+            ExportError::Image(index, error) => {
+                write!(f, "Failed to encode image {}: {}", index, error)
+            }
+            ExportError::TooLarge(length) => {
+                write!(f, "The .glb file would be {} bytes, over 4 GiB", length)
+            }
+        }
+    }
+}
+
+impl error::Error for ExportError {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self {
+            ExportError::Io(error) => Some(error),
+            ExportError::Json(error) => Some(error),
+            ExportError::UnsupportedFormat(_) | ExportError::TooLarge(_) => {
+                None
+            }
+            ExportError::Image(_, error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(error: io::Error) -> Self {
+        ExportError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(error: serde_json::Error) -> Self {
+        ExportError::Json(error)
+    }
+}
@@ -0,0 +1,574 @@
+use fuel_camera::Camera;
+use fuel_core::Scene;
+use fuel_render::backend::{DrawMode, Filter, Sampler, Wrap};
+use fuel_render::{
+    AlphaMode, Attribute, Material, MaterialTexture, Mesh, Model, Primitive,
+    Texture, Vertex,
+};
+use fuel_types::bytes::{align, write_f32, write_u32};
+use fuel_types::Transform;
+use image::png::PNGEncoder;
+use image::{ColorType, ImageError};
+use na::Vector3;
+use serde_json::{self, Map, Value};
+use std::collections::HashMap;
+use std::ops::Range;
+use ExportError;
+
+// Enums of the glTF specification
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Write the objects of a scene to glTF 2.0, with the meshes,
+/// materials and textures of the Models.
+///
+/// Each object is a node with its transform, the nodes of a
+/// Model are under the one of the Model. Nodes are in their
+/// current pose, meshes in their bind pose. Lights, skins and
+/// animations aren't exported.
+///
+/// Vertices, indices and textures, as PNG images, share a
+/// single buffer.
+#[derive(Default)]
+pub struct GltfWriter {
+    nodes: Vec<Value>,
+    roots: Vec<usize>,
+    meshes: Vec<Value>,
+    materials: Vec<Value>,
+    textures: Vec<Value>,
+    images: Vec<Value>,
+    samplers: Vec<Sampler>,
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    buffer: Vec<u8>,
+}
+
+impl GltfWriter {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<C: Camera>(scene: &Scene<C>) -> Result<Self, ExportError> {
+        let mut writer = Self::default();
+
+        // Node of each object, with the roots of its Model
+        let mut objects = HashMap::new();
+        for (key, object) in scene.iter() {
+            let node = writer.nodes.len();
+            writer.nodes.push(get_node(None, object.get_transform()));
+            let roots = match object.as_model() {
+                Some(model) => writer.push_model(model)?,
+                None => vec![],
+            };
+            objects.insert(key, (node, roots));
+        }
+
+        for key in scene.hierarchy() {
+            let (node, roots) = &objects[&key];
+            let children = scene
+                .get_children(key)
+                .iter()
+                .filter_map(|child| objects.get(child))
+                .map(|(child, _)| *child);
+            let children: Vec<usize> =
+                roots.iter().cloned().chain(children).collect();
+            writer.nodes[*node]["children"] = non_empty(&children);
+            if scene.get_parent(key).is_none() {
+                writer.roots.push(*node);
+            }
+        }
+
+        Ok(writer)
+    }
+
+    /// Content of a .gltf file, its buffer is the content of the
+    /// .bin file at `buffer_uri`.
+    pub fn to_gltf(&self, buffer_uri: &str) -> Result<Vec<u8>, ExportError> {
+        Ok(serde_json::to_vec_pretty(&self.get_json(Some(buffer_uri)))?)
+    }
+
+    /// Content of the .bin file of a .gltf one.
+    pub fn get_buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Content of a .glb file, the buffer is its binary chunk.
+    pub fn to_glb(&self) -> Result<Vec<u8>, ExportError> {
+        let mut json = serde_json::to_vec(&self.get_json(None))?;
+        align(&mut json, b' ');
+        let mut bin = self.buffer.clone();
+        align(&mut bin, 0);
+
+        let mut length = 12 + 8 + json.len();
+        if !bin.is_empty() {
+            length += 8 + bin.len();
+        }
+        // Chunks are smaller than the whole file
+        let length = get_glb_length(length)?;
+        let mut glb = Vec::with_capacity(length as usize);
+        glb.extend_from_slice(b"glTF");
+        write_u32(&mut glb, 2);
+        write_u32(&mut glb, length);
+        write_u32(&mut glb, json.len() as u32);
+        glb.extend_from_slice(b"JSON");
+        glb.extend(json);
+        if !bin.is_empty() {
+            write_u32(&mut glb, bin.len() as u32);
+            glb.extend_from_slice(b"BIN\0");
+            glb.extend(bin);
+        }
+
+        Ok(glb)
+    }
+
+    // Document of the file, the buffer is in the binary chunk
+    // of .glb files when there is no `buffer_uri`
+    fn get_json(&self, buffer_uri: Option<&str>) -> Value {
+        let buffers = if self.buffer.is_empty() {
+            vec![]
+        } else {
+            vec![json!({
+                "byteLength": self.buffer.len(),
+                "uri": buffer_uri,
+            })]
+        };
+        let samplers: Vec<Value> =
+            self.samplers.iter().cloned().map(get_sampler).collect();
+
+        let mut root = json!({
+            "asset": { "version": "2.0", "generator": "fuel" },
+            "scene": 0,
+            "scenes": [{ "nodes": self.roots }],
+            "nodes": non_empty(&self.nodes),
+            "meshes": non_empty(&self.meshes),
+            "materials": non_empty(&self.materials),
+            "textures": non_empty(&self.textures),
+            "images": non_empty(&self.images),
+            "samplers": non_empty(&samplers),
+            "accessors": non_empty(&self.accessors),
+            "bufferViews": non_empty(&self.buffer_views),
+            "buffers": non_empty(&buffers),
+        });
+        remove_nulls(&mut root);
+        root
+    }
+
+    // Nodes of a Model, returns the indices of its roots
+    fn push_model(&mut self, model: &Model) -> Result<Vec<usize>, ExportError> {
+        let textures = self.textures.len()
+            ..self.textures.len() + model.get_textures().len();
+        for texture in model.get_textures() {
+            self.push_texture(texture)?;
+        }
+
+        // Primitives with the same material share it. Meshes
+        // without primitive are left out, with their index
+        let mut materials: Vec<(&Material, usize)> = vec![];
+        let mut meshes = vec![];
+        for mesh in model.get_meshes() {
+            let mut primitives = vec![];
+            for primitive in mesh.get_primitives() {
+                if is_empty(primitive) {
+                    continue;
+                }
+                let known = materials
+                    .iter()
+                    .find(|(material, _)| **material == primitive.material)
+                    .map(|(_, index)| *index);
+                let material = match known {
+                    Some(index) => index,
+                    None => {
+                        self.materials
+                            .push(get_material(&primitive.material, &textures));
+                        let index = self.materials.len() - 1;
+                        materials.push((&primitive.material, index));
+                        index
+                    }
+                };
+                primitives.push(self.push_primitive(primitive, material));
+            }
+            if primitives.is_empty() {
+                meshes.push(None);
+                continue;
+            }
+            self.meshes.push(get_mesh(mesh, &primitives));
+            meshes.push(Some(self.meshes.len() - 1));
+        }
+
+        let offset = self.nodes.len();
+        let count = model.get_nodes().len();
+        for node in model.get_nodes() {
+            let mut value = get_node(node.name.as_ref(), &node.transform);
+            let mesh = node.mesh.and_then(|mesh| *meshes.get(mesh)?);
+            value["mesh"] = json!(mesh);
+            let children: Vec<usize> = node
+                .children
+                .iter()
+                .filter(|child| **child < count)
+                .map(|child| offset + child)
+                .collect();
+            value["children"] = non_empty(&children);
+            // Weights are the ones of the morph targets of the mesh
+            if mesh.is_some() {
+                value["weights"] = non_empty(&node.weights);
+            }
+            self.nodes.push(value);
+        }
+
+        Ok(model
+            .get_roots()
+            .iter()
+            .filter(|root| **root < count)
+            .map(|root| offset + root)
+            .collect())
+    }
+
+    // Joints and weights are left out with the skins
+    fn push_primitive(
+        &mut self,
+        primitive: &Primitive,
+        material: usize,
+    ) -> Value {
+        let vertices = primitive.get_vertices();
+        let mut attributes = Map::new();
+        for attribute in primitive.get_layout().attributes() {
+            let (semantic, field) = match get_semantic(attribute) {
+                Some(semantic) => semantic,
+                None => continue,
+            };
+            let accessor = self.push_floats(
+                vertices.iter().map(field),
+                attribute.components(),
+                attribute == Attribute::Position,
+            );
+            attributes.insert(semantic.to_owned(), json!(accessor));
+        }
+        let indices = primitive
+            .get_indices()
+            .map(|indices| self.push_indices(indices));
+
+        let mut targets = vec![];
+        for target in primitive.get_morph_targets() {
+            targets.push(json!({
+                "POSITION": self.push_deltas(&target.positions, true),
+                "NORMAL": self.push_deltas(&target.normals, false),
+                "TANGENT": self.push_deltas(&target.tangents, false),
+            }));
+        }
+
+        json!({
+            "attributes": attributes,
+            "indices": indices,
+            "material": material,
+            "mode": get_mode(primitive.get_draw_mode()),
+            "targets": non_empty(&targets),
+        })
+    }
+
+    fn push_texture(&mut self, texture: &Texture) -> Result<(), ExportError> {
+        let index = self.textures.len();
+        let png = encode_png(texture)
+            .map_err(|error| ExportError::Image(index, error))?;
+        let view = self.push_view(&png, None);
+        self.images.push(json!({
+            "bufferView": view,
+            "mimeType": "image/png",
+            "name": texture.name,
+        }));
+
+        let sampler = self
+            .samplers
+            .iter()
+            .position(|sampler| *sampler == texture.sampler)
+            .unwrap_or_else(|| {
+                self.samplers.push(texture.sampler);
+                self.samplers.len() - 1
+            });
+        self.textures.push(json!({
+            "sampler": sampler,
+            "source": self.images.len() - 1,
+            "name": texture.name,
+        }));
+        Ok(())
+    }
+
+    // Accessor of vectors of floats, positions need their bounds
+    fn push_floats<'a, I>(
+        &mut self,
+        vectors: I,
+        components: usize,
+        bounds: bool,
+    ) -> usize
+    where
+        I: Iterator<Item = &'a [f32]>,
+    {
+        let mut data = vec![];
+        let mut count = 0;
+        let mut min = vec![::std::f32::INFINITY; components];
+        let mut max = vec![::std::f32::NEG_INFINITY; components];
+        for vector in vectors {
+            for (component, value) in vector.iter().enumerate() {
+                write_f32(&mut data, *value);
+                min[component] = min[component].min(*value);
+                max[component] = max[component].max(*value);
+            }
+            count += 1;
+        }
+
+        let view = self.push_view(&data, Some(ARRAY_BUFFER));
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": count,
+            "type": get_type(components),
+        });
+        if bounds {
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    // Differences of a morph target, if it has them
+    fn push_deltas(&mut self, deltas: &[Vector3<f32>], bounds: bool) -> Value {
+        if deltas.is_empty() {
+            return Value::Null;
+        }
+        let vectors = deltas.iter().map(|delta| delta.as_slice());
+        json!(self.push_floats(vectors, 3, bounds))
+    }
+
+    fn push_indices(&mut self, indices: &[u32]) -> usize {
+        let mut data = Vec::with_capacity(indices.len() * 4);
+        for index in indices {
+            write_u32(&mut data, *index);
+        }
+
+        let view = self.push_view(&data, Some(ELEMENT_ARRAY_BUFFER));
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+
+    // Views start on words, for the accessors
+    fn push_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        align(&mut self.buffer, 0);
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": data.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(data);
+        self.buffer_views.len() - 1
+    }
+}
+
+// Lengths of .glb files and their chunks are 32 bits
+fn get_glb_length(length: usize) -> Result<u32, ExportError> {
+    if length > u32::max_value() as usize {
+        return Err(ExportError::TooLarge(length));
+    }
+    Ok(length as u32)
+}
+
+// Accessors can't be empty
+fn is_empty(primitive: &Primitive) -> bool {
+    primitive.get_vertices().is_empty()
+        || primitive.get_indices().map_or(false, |i| i.is_empty())
+}
+
+fn get_node(name: Option<&String>, transform: &Transform) -> Value {
+    let (translation, rotation, scale) = transform.get();
+    json!({
+        "name": name,
+        "translation": translation.as_slice(),
+        "rotation": rotation.coords.as_slice(),
+        "scale": scale.as_slice(),
+    })
+}
+
+fn get_mesh(mesh: &Mesh, primitives: &[Value]) -> Value {
+    json!({
+        "primitives": primitives,
+        "weights": non_empty(mesh.get_weights()),
+    })
+}
+
+// Values of an attribute in a vertex
+type Field = fn(&Vertex) -> &[f32];
+
+fn get_semantic(attribute: Attribute) -> Option<(&'static str, Field)> {
+    let semantic: (&str, Field) = match attribute {
+        Attribute::Position => ("POSITION", |v| v.position.as_slice()),
+        Attribute::Normal => ("NORMAL", |v| v.normal.as_slice()),
+        Attribute::Tangent => ("TANGENT", |v| v.tangent.as_slice()),
+        Attribute::TexCoord0 => ("TEXCOORD_0", |v| v.tex_coord_0.as_slice()),
+        Attribute::TexCoord1 => ("TEXCOORD_1", |v| v.tex_coord_1.as_slice()),
+        Attribute::Color0 => ("COLOR_0", |v| v.color_0.as_slice()),
+        _ => return None,
+    };
+    Some(semantic)
+}
+
+fn get_type(components: usize) -> &'static str {
+    match components {
+        1 => "SCALAR",
+        2 => "VEC2",
+        3 => "VEC3",
+        _ => "VEC4",
+    }
+}
+
+fn get_mode(draw_mode: DrawMode) -> u32 {
+    match draw_mode {
+        DrawMode::Points => 0,
+        DrawMode::Lines => 1,
+        DrawMode::LineLoop => 2,
+        DrawMode::LineStrip => 3,
+        DrawMode::Triangles => 4,
+        DrawMode::TriangleStrip => 5,
+        DrawMode::TriangleFan => 6,
+    }
+}
+
+// Indices of the textures are relative to the Model, the ones
+// it doesn't have are left out
+fn get_material(material: &Material, textures: &Range<usize>) -> Value {
+    let texture = |texture: Option<MaterialTexture>| {
+        texture
+            .filter(|texture| texture.index < textures.len())
+            .map(|texture| {
+                json!({
+                    "index": textures.start + texture.index,
+                    "texCoord": texture.tex_coord,
+                })
+            })
+            .unwrap_or(Value::Null)
+    };
+    let mut normal_texture = texture(material.normal_texture);
+    if let Some(normal_texture) = normal_texture.as_object_mut() {
+        normal_texture.insert("scale".to_owned(), json!(material.normal_scale));
+    }
+    let mut occlusion_texture = texture(material.occlusion_texture);
+    if let Some(occlusion_texture) = occlusion_texture.as_object_mut() {
+        occlusion_texture
+            .insert("strength".to_owned(), json!(material.occlusion_strength));
+    }
+    let (alpha_mode, alpha_cutoff) = match material.alpha_mode {
+        AlphaMode::Opaque => ("OPAQUE", None),
+        AlphaMode::Mask => ("MASK", Some(material.alpha_cutoff)),
+        AlphaMode::Blend => ("BLEND", None),
+    };
+
+    json!({
+        "name": material.name,
+        "pbrMetallicRoughness": {
+            "baseColorFactor": material.base_color_factor.as_slice(),
+            "baseColorTexture": texture(material.base_color_texture),
+            "metallicFactor": material.metallic_factor,
+            "roughnessFactor": material.roughness_factor,
+            "metallicRoughnessTexture":
+                texture(material.metallic_roughness_texture),
+        },
+        "normalTexture": normal_texture,
+        "occlusionTexture": occlusion_texture,
+        "emissiveTexture": texture(material.emissive_texture),
+        "emissiveFactor": material.emissive_factor.as_slice(),
+        "alphaMode": alpha_mode,
+        "alphaCutoff": alpha_cutoff,
+        "doubleSided": material.double_sided,
+    })
+}
+
+fn get_sampler(sampler: Sampler) -> Value {
+    let filter = |filter: Filter| match filter {
+        Filter::Nearest => 9728,
+        Filter::Linear => 9729,
+    };
+    let min_filter = match (sampler.min_filter, sampler.mipmap_filter) {
+        (min_filter, None) => filter(min_filter),
+        (Filter::Nearest, Some(Filter::Nearest)) => 9984,
+        (Filter::Linear, Some(Filter::Nearest)) => 9985,
+        (Filter::Nearest, Some(Filter::Linear)) => 9986,
+        (Filter::Linear, Some(Filter::Linear)) => 9987,
+    };
+    let wrap = |wrap: Wrap| match wrap {
+        Wrap::Repeat => 10497,
+        Wrap::MirroredRepeat => 33648,
+        Wrap::ClampToEdge => 33071,
+    };
+
+    json!({
+        "magFilter": filter(sampler.mag_filter),
+        "minFilter": min_filter,
+        "wrapS": wrap(sampler.wrap_s),
+        "wrapT": wrap(sampler.wrap_t),
+    })
+}
+
+fn encode_png(texture: &Texture) -> Result<Vec<u8>, ImageError> {
+    let size = texture.width as usize * texture.height as usize * 4;
+    if size == 0 || texture.pixels.len() != size {
+        return Err(ImageError::DimensionError);
+    }
+
+    let mut png = vec![];
+    PNGEncoder::new(&mut png).encode(
+        &texture.pixels,
+        texture.width,
+        texture.height,
+        ColorType::RGBA(8),
+    )?;
+    Ok(png)
+}
+
+// Arrays of glTF can't be empty, they are left out instead
+fn non_empty<T: Clone + Into<Value>>(values: &[T]) -> Value {
+    if values.is_empty() {
+        Value::Null
+    } else {
+        Value::Array(values.iter().cloned().map(Into::into).collect())
+    }
+}
+
+// Properties without value are left out
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            let nulls: Vec<String> = object
+                .iter()
+                .filter(|(_, value)| value.is_null())
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in nulls {
+                object.remove(&key);
+            }
+            object.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(values) => values.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glb_length_fits_in_32_bits() {
+        let max = u32::max_value();
+        assert_eq!(get_glb_length(12).unwrap(), 12);
+        assert_eq!(get_glb_length(max as usize).unwrap(), max);
+        match get_glb_length(max as usize + 1) {
+            Err(ExportError::TooLarge(length)) => {
+                assert_eq!(length, max as usize + 1)
+            }
+            other => panic!("Expected a size error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+extern crate fuel_camera;
+extern crate fuel_core;
+extern crate fuel_render;
+extern crate fuel_types;
+extern crate image;
+extern crate nalgebra as na;
+#[macro_use]
+extern crate serde_json;
+
+mod error;
+mod gltf_writer;
+use fuel_camera::Camera;
+use fuel_core::Scene;
+use std::fs;
+use std::path::Path;
+
+pub use error::ExportError;
+pub use gltf_writer::GltfWriter;
+
+/// Export the objects of a scene to 3D files, which can be
+/// imported again by the Importer.
+pub struct Exporter;
+impl Exporter {
+    /// Export to a file, its format is chosen by its extension.
+    pub fn write_file<C: Camera>(
+        scene: &Scene<C>,
+        file_path: &str,
+    ) -> Result<(), ExportError> {
+        let extension = Path::new(file_path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match &extension[..] {
+            "gltf" => Self::write_gltf(scene, file_path),
+            "glb" => Self::write_glb(scene, file_path),
+            _ => Err(ExportError::UnsupportedFormat(extension)),
+        }
+    }
+
+    /// Write a .gltf file, its buffer is written next to it in a
+    /// .bin file of the same name.
+    pub fn write_gltf<C: Camera>(
+        scene: &Scene<C>,
+        file_path: &str,
+    ) -> Result<(), ExportError> {
+        let writer = GltfWriter::new(scene)?;
+        let buffer_path = Path::new(file_path).with_extension("bin");
+        let buffer_uri = buffer_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        fs::write(file_path, writer.to_gltf(&buffer_uri)?)?;
+        if !writer.get_buffer().is_empty() {
+            fs::write(buffer_path, writer.get_buffer())?;
+        }
+        Ok(())
+    }
+
+    /// Write a .glb file, with its buffer inside.
+    pub fn write_glb<C: Camera>(
+        scene: &Scene<C>,
+        file_path: &str,
+    ) -> Result<(), ExportError> {
+        fs::write(file_path, Self::get_glb(scene)?)?;
+        Ok(())
+    }
+
+    /// Content of a .glb file, like the one read by
+    /// `Importer::from_gltf_slice`.
+    pub fn get_glb<C: Camera>(
+        scene: &Scene<C>,
+    ) -> Result<Vec<u8>, ExportError> {
+        GltfWriter::new(scene)?.to_glb()
+    }
+}
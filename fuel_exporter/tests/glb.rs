@@ -0,0 +1,86 @@
+extern crate fuel_core;
+extern crate fuel_exporter;
+extern crate fuel_importer;
+extern crate fuel_render;
+extern crate nalgebra as na;
+
+use fuel_core::Scene;
+use fuel_exporter::Exporter;
+use fuel_importer::Importer;
+use fuel_render::backend::DrawMode;
+use fuel_render::{Attribute, Material, Mesh, Model, Primitive, Vertex};
+use na::{Vector3, Vector4};
+
+fn primitive(draw_mode: DrawMode, material: Material) -> Primitive {
+    let vertices = vec![
+        Vector3::new(0., 0., 0.),
+        Vector3::new(1., 0., 0.),
+        Vector3::new(0., 1., 0.),
+    ]
+    .into_iter()
+    .map(|position| Vertex {
+        position,
+        normal: Vector3::z(),
+        ..Default::default()
+    })
+    .collect();
+    let attributes = [Attribute::Position, Attribute::Normal];
+    Primitive::new(vertices, Some(vec![0, 1, 2]), &attributes)
+        .with_draw_mode(draw_mode)
+        .with_material(material)
+}
+
+fn assert_same_primitive(imported: &Primitive, exported: &Primitive) {
+    assert_eq!(imported.get_draw_mode(), exported.get_draw_mode());
+    assert_eq!(imported.get_indices(), exported.get_indices());
+    assert_eq!(imported.get_vertices().len(), exported.get_vertices().len());
+    let vertices = imported.get_vertices().iter().zip(exported.get_vertices());
+    for (imported, exported) in vertices {
+        assert_eq!(imported.position, exported.position);
+        assert_eq!(imported.normal, exported.normal);
+    }
+
+    assert_eq!(imported.material, exported.material);
+}
+
+// Meshes are kept by their index, the one in between is empty
+fn model() -> Model {
+    let material = Material {
+        base_color_factor: Vector4::new(0.25, 0.5, 0.75, 1.),
+        metallic_factor: 0.25,
+        roughness_factor: 0.75,
+        emissive_factor: Vector3::new(0.5, 0.25, 0.),
+        double_sided: true,
+        ..Default::default()
+    };
+    let meshes = vec![
+        Mesh::new(vec![primitive(DrawMode::Triangles, material)]),
+        Mesh::new(vec![Primitive::new(vec![], None, &[])]),
+        Mesh::new(vec![primitive(DrawMode::Lines, Material::default())]),
+    ];
+    Model::new(Default::default(), meshes, vec![])
+}
+
+#[test]
+fn glb_round_trip() {
+    let mut scene = Scene::new(800., 600., 45., 0.1, 100.);
+    scene.add(model());
+
+    let glb = Exporter::get_glb(&scene).unwrap();
+    let imported = Importer::from_gltf_slice(&glb).unwrap();
+
+    assert_eq!(imported.get_meshes().len(), 2);
+    let model = model();
+    let exported = [&model.get_meshes()[0], &model.get_meshes()[2]];
+    for (imported, exported) in imported.get_meshes().iter().zip(&exported) {
+        let primitives = imported.get_primitives();
+        assert_eq!(primitives.len(), 1);
+        assert_same_primitive(&primitives[0], &exported.get_primitives()[0]);
+    }
+
+    // The node of the object, then the ones of the Model, the
+    // empty mesh is left out
+    let meshes: Vec<Option<usize>> =
+        imported.get_nodes().iter().map(|node| node.mesh).collect();
+    assert_eq!(meshes, vec![None, Some(0), None, Some(1)]);
+}
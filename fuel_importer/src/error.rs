@@ -0,0 +1,89 @@
+use fuel_render::KeyframesError;
+use gltf;
+use image;
+use std::error;
+use std::fmt;
+use std::io;
+
+/// Why a file couldn't be imported.
+#[derive(Debug)]
+pub enum ImportError {
+    Gltf(gltf::Error),
+    Io(io::Error),
+    /// The content of the file is invalid, with the line at fault.
+    Parse(String),
+    /// The extension of the file isn't a known format.
+    UnsupportedFormat(String),
+    SceneNotFound(usize),
+    /// An external buffer or image couldn't be read.
+    Resolve(String, io::Error),
+    /// The image of a texture couldn't be decoded.
+    Image(String, image::ImageError),
+    /// An embedded image doesn't fit in its buffer view, by the
+    /// index of the image.
+    ImageOutsideBuffer(usize),
+    /// Keyframes of an animation are invalid, by the index of
+    /// the animation.
+    Animation(usize, KeyframesError),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Gltf(error) => {
+                write!(f, "Failed to import glTF: {}", error)
+            }
+            ImportError::Io(error) => {
+                write!(f, "Failed to read file: {}", error)
+            }
+            ImportError::Parse(error) => {
+                write!(f, "Failed to parse file: {}", error)
+            }
+            ImportError::UnsupportedFormat(extension) => {
+                write!(f, "Files .{} can't be imported", extension)
+            }
+            ImportError::SceneNotFound(index) => {
+                write!(f, "There is no scene {} in the file", index)
+            }
+            ImportError::Resolve(uri, error) => {
+                write!(f, "Failed to read {}: {}", uri, error)
+            }
+            ImportError::Image(uri, error) => {
+                write!(f, "Failed to decode {}: {}", uri, error)
+            }
+            ImportError::ImageOutsideBuffer(index) => {
+                write!(f, "Image {} is outside of its buffer view", index)
+            }
+            ImportError::Animation(index, error) => {
+                write!(f, "Invalid animation {}: {}", index, error)
+            }
+        }
+    }
+}
+
+impl error::Error for ImportError {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self {
+            ImportError::Gltf(error) => Some(error),
+            ImportError::Io(error) => Some(error),
+            ImportError::Parse(_) | ImportError::UnsupportedFormat(_) => None,
+            ImportError::SceneNotFound(_)
+            | ImportError::ImageOutsideBuffer(_) => None,
+            ImportError::Resolve(_, error) => Some(error),
+            ImportError::Image(_, error) => Some(error),
+            ImportError::Animation(_, error) => Some(error),
+        }
+    }
+}
+
+impl From<gltf::Error> for ImportError {
+    fn from(error: gltf::Error) -> Self {
+        ImportError::Gltf(error)
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(error: io::Error) -> Self {
+        ImportError::Io(error)
+    }
+}
@@ -0,0 +1,101 @@
+use error::ImportError;
+use fuel_render::{
+    Animation, Channel, Interpolation, Keyframes, KeyframesError, Property,
+    Skin,
+};
+use gltf;
+use gltf::animation::util::ReadOutputs;
+use gltf::animation::Interpolation as GltfInterpolation;
+use na::Matrix4;
+
+type Buffers = [gltf::buffer::Data];
+
+/// Joints of a skin, with their inverse bind matrices. They
+/// are identities when the file doesn't give them.
+pub fn get_skin(skin: &gltf::Skin, buffers: &Buffers) -> Skin {
+    let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+    let joints: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+    let inverse_bind_matrices = match reader.read_inverse_bind_matrices() {
+        // Matrices are given column by column
+        Some(matrices) => matrices
+            .map(|matrix| Matrix4::from_fn(|row, column| matrix[column][row]))
+            .collect(),
+        None => vec![Matrix4::identity(); joints.len()],
+    };
+
+    Skin {
+        name: skin.name().map(|name| name.to_owned()),
+        joints,
+        inverse_bind_matrices,
+    }
+}
+
+/// Channels moving the nodes and weighting their morph targets,
+/// the ones without data are skipped.
+pub fn get_animation(
+    animation: &gltf::Animation,
+    buffers: &Buffers,
+) -> Result<Animation, ImportError> {
+    let channels = animation
+        .channels()
+        .filter_map(|channel| get_channel(&channel, buffers))
+        .collect::<Result<_, _>>()
+        .map_err(|error| ImportError::Animation(animation.index(), error))?;
+
+    let name = animation.name().map(|name| name.to_owned());
+    Ok(Animation::new(name, channels))
+}
+
+fn get_channel(
+    channel: &gltf::animation::Channel,
+    buffers: &Buffers,
+) -> Option<Result<Channel, KeyframesError>> {
+    let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+    let times: Vec<f32> = reader.read_inputs()?.collect();
+    let (property, values): (Property, Vec<f32>) =
+        match reader.read_outputs()? {
+            ReadOutputs::Translations(translations) => (
+                Property::Translation,
+                translations.flat_map(|value| value.to_vec()).collect(),
+            ),
+            ReadOutputs::Rotations(rotations) => (
+                Property::Rotation,
+                rotations
+                    .into_f32()
+                    .flat_map(|value| value.to_vec())
+                    .collect(),
+            ),
+            ReadOutputs::Scales(scales) => (
+                Property::Scale,
+                scales.flat_map(|value| value.to_vec()).collect(),
+            ),
+            ReadOutputs::MorphTargetWeights(weights) => {
+                (Property::Weights, weights.into_f32().collect())
+            }
+        };
+    let interpolation = match channel.sampler().interpolation() {
+        GltfInterpolation::Step => Interpolation::Step,
+        GltfInterpolation::CubicSpline => Interpolation::CubicSpline,
+        // Catmull-Rom splines were dropped from glTF 2.0
+        _ => Interpolation::Linear,
+    };
+    // Each keyframe has a weight for each morph target
+    let components = property.components().unwrap_or_else(|| {
+        let values_per_keyframe = match interpolation {
+            Interpolation::CubicSpline => 3,
+            _ => 1,
+        };
+        values.len() / (times.len() * values_per_keyframe).max(1)
+    });
+
+    let node = channel.target().node().index();
+    Some(
+        Keyframes::new(interpolation, times, values, components).map(
+            |keyframes| Channel {
+                node,
+                property,
+                keyframes,
+            },
+        ),
+    )
+}
@@ -0,0 +1,142 @@
+use base64;
+use gltf;
+use gltf::buffer::{Data as BufferData, Source as BufferSource};
+use gltf::image::{Data as ImageData, Format, Source as ImageSource};
+use gltf::{Document, Gltf};
+use image::{self, DynamicImage, GenericImage, ImageFormat};
+use std::io;
+use ImportError;
+
+/// Document of a glTF with its buffers and decoded images.
+pub type Import = (Document, Vec<BufferData>, Vec<ImageData>);
+
+/// Read the content of a .gltf or a .glb file. Data URIs and the
+/// binary chunk are decoded, other URIs are given to `resolver`.
+pub fn import_slice<F>(
+    data: &[u8],
+    resolver: &mut F,
+) -> Result<Import, ImportError>
+where
+    F: FnMut(&str) -> io::Result<Vec<u8>>,
+{
+    let Gltf { document, blob } = Gltf::from_slice(data)?;
+    let buffers = import_buffers(&document, blob, resolver)?;
+    let images = import_images(&document, &buffers, resolver)?;
+
+    Ok((document, buffers, images))
+}
+
+fn read_uri<F>(uri: &str, resolver: &mut F) -> Result<Vec<u8>, ImportError>
+where
+    F: FnMut(&str) -> io::Result<Vec<u8>>,
+{
+    if uri.starts_with("data:") {
+        let encoded = uri.splitn(2, ";base64,").nth(1).unwrap_or("");
+        return Ok(base64::decode(encoded).map_err(gltf::Error::Base64)?);
+    }
+
+    resolver(uri).map_err(|error| ImportError::Resolve(uri.to_owned(), error))
+}
+
+fn import_buffers<F>(
+    document: &Document,
+    mut blob: Option<Vec<u8>>,
+    resolver: &mut F,
+) -> Result<Vec<BufferData>, ImportError>
+where
+    F: FnMut(&str) -> io::Result<Vec<u8>>,
+{
+    let mut buffers = vec![];
+    for buffer in document.buffers() {
+        let mut data = match buffer.source() {
+            BufferSource::Uri(uri) => read_uri(uri, resolver)?,
+            BufferSource::Bin => blob.take().ok_or(gltf::Error::MissingBlob)?,
+        };
+        if data.len() < buffer.length() {
+            return Err(ImportError::Gltf(gltf::Error::BufferLength {
+                buffer: buffer.index(),
+                expected: buffer.length(),
+                actual: data.len(),
+            }));
+        }
+        // Accessors are read by words
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+        buffers.push(BufferData(data));
+    }
+
+    Ok(buffers)
+}
+
+fn import_images<F>(
+    document: &Document,
+    buffers: &[BufferData],
+    resolver: &mut F,
+) -> Result<Vec<ImageData>, ImportError>
+where
+    F: FnMut(&str) -> io::Result<Vec<u8>>,
+{
+    document
+        .images()
+        .map(|image| match image.source() {
+            ImageSource::View { view, mime_type } => {
+                let buffer = &buffers[view.buffer().index()].0;
+                let data = view
+                    .offset()
+                    .checked_add(view.length())
+                    .and_then(|end| buffer.get(view.offset()..end))
+                    .ok_or_else(|| {
+                        ImportError::ImageOutsideBuffer(image.index())
+                    })?;
+                decode_image(data, Some(mime_type))
+            }
+            ImageSource::Uri { uri, mime_type } => {
+                // Data URIs carry their own media type
+                let mime_type = mime_type.or_else(|| {
+                    if uri.starts_with("data:") {
+                        uri["data:".len()..].split(';').next()
+                    } else {
+                        None
+                    }
+                });
+                decode_image(&read_uri(uri, resolver)?, mime_type)
+            }
+        })
+        .collect()
+}
+
+// Images without media type are guessed from their content
+fn decode_image(
+    data: &[u8],
+    mime_type: Option<&str>,
+) -> Result<ImageData, ImportError> {
+    let image = match mime_type {
+        Some("image/png") => {
+            image::load_from_memory_with_format(data, ImageFormat::PNG)
+        }
+        Some("image/jpeg") => {
+            image::load_from_memory_with_format(data, ImageFormat::JPEG)
+        }
+        Some(_) => {
+            return Err(ImportError::Gltf(gltf::Error::UnsupportedImageEncoding))
+        }
+        None => image::load_from_memory(data),
+    }
+    .map_err(gltf::Error::Image)?;
+
+    let format = match image {
+        DynamicImage::ImageLuma8(_) => Format::R8,
+        DynamicImage::ImageLumaA8(_) => Format::R8G8,
+        DynamicImage::ImageRgb8(_) => Format::R8G8B8,
+        DynamicImage::ImageRgba8(_) => Format::R8G8B8A8,
+    };
+    let (width, height) = image.dimensions();
+
+    Ok(ImageData {
+        pixels: image.raw_pixels(),
+        format,
+        width,
+        height,
+    })
+}
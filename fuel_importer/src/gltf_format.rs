@@ -1,8 +1,23 @@
-use fuel_render::{Mesh, Meshes, Model, Primitive, Vertex};
+use fuel_render::backend::DrawMode;
+use fuel_render::{
+    flat_normals, generate_tangents, smooth_normals, unindex, Aabb, AlphaMode,
+    Animation, Attribute, Light, Material, MaterialTexture, Mesh, Meshes,
+    Model, MorphTarget, Node, Normals, Primitive, Skin, Texture, Vertex,
+};
 use fuel_types::Transform;
 use gltf;
-use na::{Vector2, Vector3};
-use Formater;
+use gltf::material::AlphaMode as GltfAlphaMode;
+use gltf::mesh::Mode;
+use gltf::Semantic;
+use gltf_animation::{get_animation, get_skin};
+use gltf_data::import_slice;
+use gltf_lights::get_lights;
+use na::{Quaternion, UnitQuaternion, Vector2, Vector3, Vector4};
+use resolver::file_resolver;
+use std::fs;
+use std::io;
+use std::path::Path;
+use {Formater, ImportError, ImportOptions};
 
 type TexCoords<'a> = Option<gltf::mesh::util::ReadTexCoords<'a>>;
 type GltfPositions<'a> = gltf::accessor::Iter<'a, [f32; 3]>;
@@ -11,67 +26,338 @@ type Vertices = Vec<Vertex>;
 type Indices = Vec<u32>;
 
 /// Format glTF file to fit with the render engine.
+///
+/// Nodes keep the indices of the glTF document, `roots` are
+/// the nodes of the imported scene. Lights come from the
+/// KHR_lights_punctual extension.
+///
+/// Skins and animations keep their indices too, animations
+/// only move the nodes.
 pub struct GltfFormater {
     pub meshes: Meshes,
+    pub textures: Vec<Texture>,
+    pub lights: Vec<Light>,
+    pub skins: Vec<Skin>,
+    pub animations: Vec<Animation>,
+    pub nodes: Vec<Node>,
+    pub roots: Vec<usize>,
     pub transform: Transform,
 }
 
 impl GltfFormater {
-    pub fn new(gltf_file_path: &str) -> Self {
-        let (document, buffers, _images) = gltf::import(gltf_file_path)
-            .expect("glTF file not found or not valid.");
+    /// Import the default scene of the file, or its first one.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(gltf_file_path: &str) -> Result<Self, ImportError> {
+        Self::with_options(gltf_file_path, ImportOptions::default())
+    }
+
+    pub fn from_scene(
+        gltf_file_path: &str,
+        scene: usize,
+    ) -> Result<Self, ImportError> {
+        let options = ImportOptions {
+            scene: Some(scene),
+            ..Default::default()
+        };
+        Self::with_options(gltf_file_path, options)
+    }
+
+    /// Import a .gltf or a .glb file, its external buffers and
+    /// images are read next to it.
+    pub fn with_options(
+        gltf_file_path: &str,
+        options: ImportOptions,
+    ) -> Result<Self, ImportError> {
+        let data = fs::read(gltf_file_path).map_err(gltf::Error::Io)?;
+        let base = Path::new(gltf_file_path)
+            .parent()
+            .unwrap_or_else(|| Path::new("./"));
+        Self::from_slice(&data, options, file_resolver(base))
+    }
+
+    /// Import the content of a .gltf or a .glb file. `resolver`
+    /// gives the bytes of the buffers and images referenced by
+    /// an URI, data URIs are decoded without it.
+    pub fn from_slice<F>(
+        data: &[u8],
+        options: ImportOptions,
+        mut resolver: F,
+    ) -> Result<Self, ImportError>
+    where
+        F: FnMut(&str) -> io::Result<Vec<u8>>,
+    {
+        let (document, buffers, images) = import_slice(data, &mut resolver)?;
+
+        let scene = match options.scene {
+            Some(index) => Some(
+                document
+                    .scenes()
+                    .nth(index)
+                    .ok_or_else(|| ImportError::SceneNotFound(index))?,
+            ),
+            None => document
+                .default_scene()
+                .or_else(|| document.scenes().next()),
+        };
+        let (lights, node_lights) = get_lights(data)?;
+        let nodes: Vec<Node> = document
+            .nodes()
+            .map(|node| Node {
+                light: node_lights.get(node.index()).and_then(|light| *light),
+                ..get_node(&node)
+            })
+            .collect();
+        let roots = match scene {
+            Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+            // Without scene, every node without parent is a root
+            None => (0..nodes.len())
+                .filter(|index| {
+                    !nodes.iter().any(|node| node.children.contains(index))
+                })
+                .collect(),
+        };
 
         let meshes: Meshes = document
             .meshes()
             .map(|mesh| {
-                let primitives: Primitives = mesh.primitives()
-                    .map(|prim| {
-                        let reader = prim.reader(|buffer| {
-                            Some(&buffers[buffer.index()])
-                        });
-
-                        let mut vertices: Vertices = reader
-                            .read_positions()
-                            .map(|positions| get_vertex(positions))
-                            .unwrap_or(vec![]);
-
-                        [0, 1].iter().for_each(|index| {
-                            let coords = reader.read_tex_coords(*index);
-                            tex_coords_on_vertices(
-                                coords,
-                                *index,
-                                &mut vertices,
-                            );
-                        });
-
-                        let indices: Option<Indices> = reader
-                            .read_indices()
-                            .map(|indices| indices.into_u32().collect());
-
-                        Primitive::new(&vertices, indices)
-                    })
+                let primitives: Primitives = mesh
+                    .primitives()
+                    .map(|prim| get_primitive(&prim, &buffers, &options))
                     .collect();
 
-                Mesh::new(primitives)
+                let weights = mesh.weights().unwrap_or(&[]).to_vec();
+                Mesh::new(primitives).with_weights(weights)
             })
             .collect();
 
-        // TODO: Texture
-        // document
-        //     .textures()
-        //     .for_each(|texture| Texture::from_gltf(texture, &buffers));
+        let textures = document
+            .textures()
+            .map(|texture| Texture::from_gltf(&texture, &images))
+            .collect();
+
+        let skins = document
+            .skins()
+            .map(|skin| get_skin(&skin, &buffers))
+            .collect();
+        let animations = document
+            .animations()
+            .map(|animation| get_animation(&animation, &buffers))
+            .collect::<Result<_, _>>()?;
 
         let transform = Transform {
             ..Default::default()
         };
 
-        Self { meshes, transform }
+        Ok(Self {
+            meshes,
+            textures,
+            lights,
+            skins,
+            animations,
+            nodes,
+            roots,
+            transform,
+        })
     }
 }
 
 impl Formater for GltfFormater {
     fn to_model(self) -> Model {
-        Model::new(self.transform, self.meshes)
+        Model::new(self.transform, self.meshes, self.textures)
+            .with_lights(self.lights)
+            .with_skins(self.skins)
+            .with_animations(self.animations)
+            .with_nodes(self.nodes, self.roots)
+    }
+}
+
+fn get_primitive(
+    prim: &gltf::Primitive,
+    buffers: &[gltf::buffer::Data],
+    options: &ImportOptions,
+) -> Primitive {
+    let reader = prim.reader(|buffer| Some(&buffers[buffer.index()]));
+    let material = get_material(&prim.material());
+
+    let mut attributes = vec![];
+    let mut vertices: Vertices =
+        reader.read_positions().map(get_vertex).unwrap_or_default();
+    if !vertices.is_empty() {
+        attributes.push(Attribute::Position);
+    }
+
+    [0, 1].iter().for_each(|index| {
+        let coords = reader.read_tex_coords(*index);
+        if let Some(attribute) =
+            tex_coords_on_vertices(coords, *index, &mut vertices)
+        {
+            attributes.push(attribute);
+        }
+    });
+
+    let has_normals = match reader.read_normals() {
+        Some(normals) => {
+            for (normal, vertex) in normals.zip(vertices.iter_mut()) {
+                vertex.normal = Vector3::from(normal);
+            }
+            true
+        }
+        None => false,
+    };
+    let has_tangents = match reader.read_tangents() {
+        Some(tangents) => {
+            for (tangent, vertex) in tangents.zip(vertices.iter_mut()) {
+                vertex.tangent = Vector4::from(tangent);
+            }
+            attributes.push(Attribute::Tangent);
+            true
+        }
+        None => false,
+    };
+    if let Some(colors) = reader.read_colors(0) {
+        for (color, vertex) in colors.into_rgba_f32().zip(vertices.iter_mut()) {
+            vertex.color_0 = Vector4::from(color);
+        }
+        attributes.push(Attribute::Color0);
+    }
+    // Both are needed to skin the vertices
+    if let (Some(joints), Some(weights)) =
+        (reader.read_joints(0), reader.read_weights(0))
+    {
+        for ((joints, weights), vertex) in joints
+            .into_u16()
+            .zip(weights.into_f32())
+            .zip(vertices.iter_mut())
+        {
+            vertex.joints_0 = Vector4::from(joints);
+            vertex.weights_0 = Vector4::from(weights);
+        }
+        attributes.push(Attribute::Joints0);
+        attributes.push(Attribute::Weights0);
+    }
+
+    let mut indices: Option<Indices> = reader
+        .read_indices()
+        .map(|indices| indices.into_u32().collect());
+    let mut morph_targets: Vec<MorphTarget> = reader
+        .read_morph_targets()
+        .map(|(positions, normals, tangents)| MorphTarget {
+            positions: get_deltas(positions),
+            normals: get_deltas(normals),
+            tangents: get_deltas(tangents),
+        })
+        .collect();
+
+    if !vertices.is_empty() {
+        attributes.push(Attribute::Normal);
+    }
+    // Generated normals and tangents need a list of triangles, the
+    // shader has fallbacks for points, lines, strips and fans
+    let is_triangles = prim.mode() == Mode::Triangles;
+    if !has_normals && is_triangles {
+        match options.normals {
+            Normals::Flat => {
+                let triangles = indices.take();
+                let triangles = triangles.as_ref().map(|i| &i[..]);
+                vertices = flat_normals(&vertices, triangles);
+                for target in &mut morph_targets {
+                    target.positions = unindex(&target.positions, triangles);
+                    target.normals = unindex(&target.normals, triangles);
+                    target.tangents = unindex(&target.tangents, triangles);
+                }
+            }
+            Normals::Smooth => {
+                smooth_normals(&mut vertices, indices.as_ref().map(|i| &i[..]))
+            }
+        }
+    }
+    // Normal maps need tangents, the shader has a fallback
+    // for the others
+    let normal_texture = material
+        .normal_texture
+        .filter(|_| !has_tangents && is_triangles);
+    if let Some(normal_texture) = normal_texture {
+        generate_tangents(
+            &mut vertices,
+            indices.as_ref().map(|i| &i[..]),
+            normal_texture.tex_coord,
+        );
+        attributes.push(Attribute::Tangent);
+    }
+
+    let primitive = Primitive::new(vertices, indices, &attributes)
+        .with_material(material)
+        .with_draw_mode(get_draw_mode(prim.mode()));
+    let primitive = match get_bounding_box(prim) {
+        Some(aabb) => primitive.with_bounding_box(aabb),
+        None => primitive,
+    };
+    primitive.with_morph_targets(morph_targets)
+}
+
+fn get_draw_mode(mode: Mode) -> DrawMode {
+    match mode {
+        Mode::Points => DrawMode::Points,
+        Mode::Lines => DrawMode::Lines,
+        Mode::LineLoop => DrawMode::LineLoop,
+        Mode::LineStrip => DrawMode::LineStrip,
+        Mode::Triangles => DrawMode::Triangles,
+        Mode::TriangleStrip => DrawMode::TriangleStrip,
+        Mode::TriangleFan => DrawMode::TriangleFan,
+    }
+}
+
+fn get_deltas<I>(deltas: Option<I>) -> Vec<Vector3<f32>>
+where
+    I: Iterator<Item = [f32; 3]>,
+{
+    deltas
+        .map(|deltas| deltas.map(Vector3::from).collect())
+        .unwrap_or_default()
+}
+
+// Bounds of the positions given by their accessor, they are
+// required by the specification but files can miss them
+fn get_bounding_box(prim: &gltf::Primitive) -> Option<Aabb> {
+    let accessor = prim.get(&Semantic::Positions)?;
+    let to_vector = |value: Option<gltf::json::Value>| {
+        let value = value?;
+        let values = value.as_array()?;
+        if values.len() != 3 {
+            return None;
+        }
+        let mut vector = Vector3::zeros();
+        for (component, value) in vector.iter_mut().zip(values) {
+            *component = value.as_f64()? as f32;
+        }
+        Some(vector)
+    };
+
+    Some(Aabb::new(
+        to_vector(accessor.min())?,
+        to_vector(accessor.max())?,
+    ))
+}
+
+fn get_node(node: &gltf::Node) -> Node {
+    let (translation, rotation, scale) = node.transform().decomposed();
+    let [x, y, z, w] = rotation;
+    let rotation = UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z));
+
+    let transform = Transform::new(
+        Vector3::from(translation),
+        rotation,
+        Vector3::from(scale),
+    );
+
+    Node {
+        name: node.name().map(|name| name.to_owned()),
+        transform,
+        mesh: node.mesh().map(|mesh| mesh.index()),
+        light: None,
+        skin: node.skin().map(|skin| skin.index()),
+        weights: node.weights().unwrap_or(&[]).to_vec(),
+        children: node.children().map(|child| child.index()).collect(),
     }
 }
 
@@ -79,24 +365,78 @@ fn get_vertex(positions: GltfPositions) -> Vertices {
     positions
         .map(|position| Vertex {
             position: Vector3::from(position),
-            normal: Vector3::from([1., 1., 1.]),
             ..Default::default()
         })
         .collect()
 }
 
+// Texture indices are the same as the ones of the glTF document
+fn get_material(material: &gltf::Material) -> Material {
+    let pbr = material.pbr_metallic_roughness();
+    let texture = |info: Option<gltf::texture::Info>| {
+        info.map(|info| MaterialTexture {
+            index: info.texture().index(),
+            tex_coord: info.tex_coord(),
+        })
+    };
+    let normal = material.normal_texture();
+    let occlusion = material.occlusion_texture();
+
+    Material {
+        name: material.name().map(|name| name.to_owned()),
+        base_color_factor: Vector4::from(pbr.base_color_factor()),
+        base_color_texture: texture(pbr.base_color_texture()),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        metallic_roughness_texture: texture(pbr.metallic_roughness_texture()),
+        normal_texture: normal.as_ref().map(|normal| MaterialTexture {
+            index: normal.texture().index(),
+            tex_coord: normal.tex_coord(),
+        }),
+        normal_scale: normal.as_ref().map_or(1., |normal| normal.scale()),
+        occlusion_texture: occlusion.as_ref().map(|occlusion| {
+            MaterialTexture {
+                index: occlusion.texture().index(),
+                tex_coord: occlusion.tex_coord(),
+            }
+        }),
+        occlusion_strength: occlusion
+            .as_ref()
+            .map_or(1., |occlusion| occlusion.strength()),
+        emissive_texture: texture(material.emissive_texture()),
+        emissive_factor: Vector3::from(material.emissive_factor()),
+        alpha_mode: match material.alpha_mode() {
+            GltfAlphaMode::Opaque => AlphaMode::Opaque,
+            GltfAlphaMode::Mask => AlphaMode::Mask,
+            GltfAlphaMode::Blend => AlphaMode::Blend,
+        },
+        alpha_cutoff: material.alpha_cutoff(),
+        double_sided: material.double_sided(),
+    }
+}
+
+// Return the attribute filled, if any
 fn tex_coords_on_vertices(
     tex_coords: TexCoords,
     index: u32,
     vertices: &mut Vertices,
-) {
-    tex_coords
-        .map(|tex_coords| tex_coords.into_f32().enumerate())
-        .map(|tex_coords| {
-            tex_coords.for_each(|(i, coord)| match index {
-                0 => vertices[i].tex_coord_0 = Vector2::from(coord),
-                1 => vertices[i].tex_coord_1 = Vector2::from(coord),
-                _ => println!("No tex_coord > 1 is permitted."),
-            })
-        });
+) -> Option<Attribute> {
+    let attribute = match index {
+        0 => Attribute::TexCoord0,
+        1 => Attribute::TexCoord1,
+        // Vertices only have two sets, the others are skipped
+        _ => return None,
+    };
+
+    tex_coords.map(|tex_coords| {
+        tex_coords.into_f32().zip(vertices.iter_mut()).for_each(
+            |(coord, vertex)| match attribute {
+                Attribute::TexCoord1 => {
+                    vertex.tex_coord_1 = Vector2::from(coord)
+                }
+                _ => vertex.tex_coord_0 = Vector2::from(coord),
+            },
+        );
+        attribute
+    })
 }
@@ -0,0 +1,81 @@
+use fuel_render::{Light, LightKind};
+use gltf;
+use gltf::binary::Glb;
+use gltf::json::{self, Value};
+use na::Vector3;
+use std::borrow::Cow;
+use std::f32::consts::FRAC_PI_4;
+use ImportError;
+
+const EXTENSION: &str = "KHR_lights_punctual";
+
+/// Lights of the KHR_lights_punctual extension, which isn't read
+/// by `gltf`, with the index of the light used by each node.
+/// `data` is the content of a .gltf or a .glb file.
+pub fn get_lights(
+    data: &[u8],
+) -> Result<(Vec<Light>, Vec<Option<usize>>), ImportError> {
+    let json = if data.starts_with(b"glTF") {
+        Glb::from_slice(data)?.json
+    } else {
+        Cow::Borrowed(data)
+    };
+    let root: Value = json::deserialize::from_slice(&json)
+        .map_err(gltf::Error::Deserialize)?;
+
+    let lights = root["extensions"][EXTENSION]["lights"]
+        .as_array()
+        .map(|lights| lights.iter().map(get_light).collect())
+        .unwrap_or_default();
+    let node_lights = root["nodes"]
+        .as_array()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .map(|node| {
+                    let light = &node["extensions"][EXTENSION]["light"];
+                    light.as_u64().map(|light| light as usize)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok((lights, node_lights))
+}
+
+// Lights of an unknown type are kept disabled, so the
+// indices of the nodes stay valid
+fn get_light(light: &Value) -> Light {
+    let number = |value: &Value, default: f32| {
+        value.as_f64().map_or(default, |value| value as f32)
+    };
+    let color = match light["color"].as_array() {
+        Some(color) if color.len() == 3 => Vector3::new(
+            number(&color[0], 1.),
+            number(&color[1], 1.),
+            number(&color[2], 1.),
+        ),
+        _ => Vector3::repeat(1.),
+    };
+    let intensity = number(&light["intensity"], 1.);
+    let spot = &light["spot"];
+
+    let (kind, enabled) = match light["type"].as_str() {
+        Some("directional") => (LightKind::Directional, true),
+        Some("point") => (LightKind::Point, true),
+        Some("spot") => (
+            LightKind::Spot {
+                inner_cone_angle: number(&spot["innerConeAngle"], 0.),
+                outer_cone_angle: number(&spot["outerConeAngle"], FRAC_PI_4),
+            },
+            true,
+        ),
+        _ => (LightKind::Point, false),
+    };
+
+    let mut imported = Light::new(kind, color, intensity)
+        .with_name(light["name"].as_str().map(|name| name.to_owned()));
+    imported.range = light["range"].as_f64().map(|range| range as f32);
+    imported.set_enabled(enabled);
+    imported
+}
@@ -1,12 +1,32 @@
+extern crate base64;
 extern crate fuel_render;
 extern crate fuel_types;
 extern crate gltf;
+extern crate image;
 extern crate nalgebra as na;
 
+mod error;
+mod gltf_animation;
+mod gltf_data;
 mod gltf_format;
+mod gltf_lights;
+mod obj_format;
+mod obj_material;
+mod options;
+mod ply_format;
+mod resolver;
+mod stl_format;
 use fuel_render::Model;
+use resolver::no_resolver;
+use std::io::{self, Read};
+use std::path::Path;
 
+pub use error::ImportError;
 pub use gltf_format::GltfFormater;
+pub use obj_format::ObjFormater;
+pub use options::ImportOptions;
+pub use ply_format::PlyFormater;
+pub use stl_format::StlFormater;
 
 /// Used to convert formats to Model.
 pub trait Formater {
@@ -17,7 +37,99 @@ pub trait Formater {
 /// to fit the engine. It will create a new Model.
 pub struct Importer;
 impl Importer {
-    pub fn from_gltf(ressource_path: &str) -> Model {
-        GltfFormater::new(ressource_path).to_model()
+    /// Import a file, its format is chosen by its extension.
+    pub fn from_file(ressource_path: &str) -> Result<Model, ImportError> {
+        let extension = Path::new(ressource_path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match &extension[..] {
+            "gltf" | "glb" => Self::from_gltf(ressource_path),
+            "obj" => Self::from_obj(ressource_path),
+            "stl" => Self::from_stl(ressource_path),
+            "ply" => Self::from_ply(ressource_path),
+            _ => Err(ImportError::UnsupportedFormat(extension)),
+        }
+    }
+
+    /// Import a .gltf or a .glb file.
+    pub fn from_gltf(ressource_path: &str) -> Result<Model, ImportError> {
+        Ok(GltfFormater::new(ressource_path)?.to_model())
+    }
+
+    /// Import one of the scenes of a glTF file instead of the
+    /// default one.
+    pub fn from_gltf_scene(
+        ressource_path: &str,
+        scene: usize,
+    ) -> Result<Model, ImportError> {
+        Ok(GltfFormater::from_scene(ressource_path, scene)?.to_model())
+    }
+
+    pub fn from_gltf_with_options(
+        ressource_path: &str,
+        options: ImportOptions,
+    ) -> Result<Model, ImportError> {
+        Ok(GltfFormater::with_options(ressource_path, options)?.to_model())
+    }
+
+    /// Import the content of a .gltf or a .glb file, which can
+    /// only use data URIs and the binary chunk.
+    pub fn from_gltf_slice(data: &[u8]) -> Result<Model, ImportError> {
+        let options = ImportOptions::default();
+        Ok(GltfFormater::from_slice(data, options, no_resolver)?.to_model())
+    }
+
+    /// Same as `from_gltf_slice`, reading the content first.
+    pub fn from_gltf_reader<R: Read>(
+        mut reader: R,
+    ) -> Result<Model, ImportError> {
+        let mut data = vec![];
+        reader.read_to_end(&mut data).map_err(gltf::Error::Io)?;
+        Self::from_gltf_slice(&data)
+    }
+
+    /// Import the content of a .gltf or a .glb file, `resolver`
+    /// gives the bytes of the buffers and images referenced by
+    /// an URI, like `textures/wood.png`.
+    pub fn from_gltf_with_resolver<F>(
+        data: &[u8],
+        options: ImportOptions,
+        resolver: F,
+    ) -> Result<Model, ImportError>
+    where
+        F: FnMut(&str) -> io::Result<Vec<u8>>,
+    {
+        Ok(GltfFormater::from_slice(data, options, resolver)?.to_model())
+    }
+
+    /// Import a Wavefront OBJ file, with its MTL materials.
+    pub fn from_obj(ressource_path: &str) -> Result<Model, ImportError> {
+        Ok(ObjFormater::new(ressource_path)?.to_model())
+    }
+
+    pub fn from_obj_with_options(
+        ressource_path: &str,
+        options: ImportOptions,
+    ) -> Result<Model, ImportError> {
+        Ok(ObjFormater::with_options(ressource_path, options)?.to_model())
+    }
+
+    /// Import an STL file, ASCII or binary.
+    pub fn from_stl(ressource_path: &str) -> Result<Model, ImportError> {
+        Ok(StlFormater::new(ressource_path)?.to_model())
+    }
+
+    /// Import a PLY file, ASCII or binary. Files without faces
+    /// are point clouds.
+    pub fn from_ply(ressource_path: &str) -> Result<Model, ImportError> {
+        Ok(PlyFormater::new(ressource_path)?.to_model())
+    }
+
+    pub fn from_ply_with_options(
+        ressource_path: &str,
+        options: ImportOptions,
+    ) -> Result<Model, ImportError> {
+        Ok(PlyFormater::with_options(ressource_path, options)?.to_model())
     }
 }
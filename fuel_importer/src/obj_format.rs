@@ -0,0 +1,487 @@
+use fuel_render::{
+    flat_normals, generate_tangents, smooth_normals, triangulate, Attribute,
+    Material, Mesh, Meshes, Model, Node, Normals, Primitive, Texture, Vertex,
+};
+use fuel_types::Transform;
+use na::{Vector2, Vector3};
+use obj_material::{default_material, parse_mtl, Textures};
+use resolver::path_resolver;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use {Formater, ImportError, ImportOptions};
+
+/// Format Wavefront OBJ files to fit with the render engine.
+///
+/// Each object and group is a mesh, with a primitive for each
+/// material it uses and a node named after it. Polygons are
+/// split in triangles, and the materials of the MTL files are
+/// converted to PBR ones.
+pub struct ObjFormater {
+    pub meshes: Meshes,
+    pub textures: Vec<Texture>,
+    pub nodes: Vec<Node>,
+    pub transform: Transform,
+}
+
+impl ObjFormater {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(obj_file_path: &str) -> Result<Self, ImportError> {
+        Self::with_options(obj_file_path, ImportOptions::default())
+    }
+
+    /// Import an OBJ file, its MTL files and textures are read
+    /// next to it. Scenes of the options are ignored.
+    pub fn with_options(
+        obj_file_path: &str,
+        options: ImportOptions,
+    ) -> Result<Self, ImportError> {
+        let data = fs::read(obj_file_path)?;
+        let base = Path::new(obj_file_path)
+            .parent()
+            .unwrap_or_else(|| Path::new("./"));
+        Self::from_slice(&data, options, path_resolver(base))
+    }
+
+    /// Import the content of an OBJ file. `resolver` gives the
+    /// bytes of the MTL files and textures, by their path.
+    pub fn from_slice<F>(
+        data: &[u8],
+        options: ImportOptions,
+        mut resolver: F,
+    ) -> Result<Self, ImportError>
+    where
+        F: FnMut(&str) -> io::Result<Vec<u8>>,
+    {
+        let obj = parse_obj(&String::from_utf8_lossy(data))?;
+
+        let mut textures = Textures::default();
+        let mut materials: HashMap<String, Material> = HashMap::new();
+        for library in &obj.libraries {
+            let data = resolver(library).map_err(|error| {
+                ImportError::Resolve(library.to_owned(), error)
+            })?;
+            let source = String::from_utf8_lossy(&data);
+            for material in
+                parse_mtl(&source, library, &mut textures, &mut resolver)?
+            {
+                let name = material.name.clone().unwrap_or_default();
+                materials.insert(name, material);
+            }
+        }
+
+        let groups = obj.groups.iter().filter(|group| !group.faces.is_empty());
+        let mut meshes = vec![];
+        let mut nodes = vec![];
+        for group in groups {
+            let primitives = split_materials(&group.faces)
+                .into_iter()
+                .map(|(material, faces)| {
+                    let material = material
+                        .and_then(|material| materials.get(material))
+                        .cloned()
+                        .unwrap_or_else(default_material);
+                    get_primitive(&obj, &faces, material, &options)
+                })
+                .collect();
+
+            nodes.push(Node {
+                name: group.name.clone(),
+                mesh: Some(meshes.len()),
+                ..Default::default()
+            });
+            meshes.push(Mesh::new(primitives));
+        }
+
+        Ok(Self {
+            meshes,
+            textures: textures.textures,
+            nodes,
+            transform: Transform::default(),
+        })
+    }
+}
+
+impl Formater for ObjFormater {
+    fn to_model(self) -> Model {
+        let roots = (0..self.nodes.len()).collect();
+        Model::new(self.transform, self.meshes, self.textures)
+            .with_nodes(self.nodes, roots)
+    }
+}
+
+// Indices of a corner of a face, in the lists of the file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    tex_coord: Option<usize>,
+    normal: Option<usize>,
+}
+
+struct Face {
+    material: Option<String>,
+    corners: Vec<Corner>,
+}
+
+#[derive(Default)]
+struct Group {
+    name: Option<String>,
+    faces: Vec<Face>,
+}
+
+#[derive(Default)]
+struct Obj {
+    positions: Vec<Vector3<f32>>,
+    tex_coords: Vec<Vector2<f32>>,
+    normals: Vec<Vector3<f32>>,
+    groups: Vec<Group>,
+    libraries: Vec<String>,
+}
+
+// Lines other than geometry, groups and materials are ignored
+fn parse_obj(source: &str) -> Result<Obj, ImportError> {
+    let mut obj = Obj::default();
+    let mut group = Group::default();
+    let mut material = None;
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments: Vec<&str> = words.collect();
+        let error = |message: &str| {
+            ImportError::Parse(format!("line {}: {}", number + 1, message))
+        };
+        let numbers = || -> Result<Vec<f32>, ImportError> {
+            arguments
+                .iter()
+                .map(|argument| argument.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| error("Invalid number"))
+        };
+
+        match keyword {
+            "v" => {
+                let values = numbers()?;
+                if values.len() < 3 {
+                    return Err(error("Position without 3 coordinates"));
+                }
+                obj.positions
+                    .push(Vector3::new(values[0], values[1], values[2]));
+            }
+            "vt" => {
+                let values = numbers()?;
+                let u = values.get(0).cloned().unwrap_or(0.);
+                let v = values.get(1).cloned().unwrap_or(0.);
+                // The origin of OBJ textures is at the bottom
+                obj.tex_coords.push(Vector2::new(u, 1. - v));
+            }
+            "vn" => {
+                let values = numbers()?;
+                if values.len() < 3 {
+                    return Err(error("Normal without 3 coordinates"));
+                }
+                obj.normals
+                    .push(Vector3::new(values[0], values[1], values[2]));
+            }
+            "f" => {
+                let corners = arguments
+                    .iter()
+                    .map(|corner| parse_corner(corner, &obj))
+                    .collect::<Option<Vec<Corner>>>()
+                    .ok_or_else(|| error("Invalid face"))?;
+                if corners.len() < 3 {
+                    return Err(error("Face with less than 3 corners"));
+                }
+                group.faces.push(Face {
+                    material: material.clone(),
+                    corners,
+                });
+            }
+            "o" | "g" => {
+                let name =
+                    Some(arguments.join(" ")).filter(|name| !name.is_empty());
+                obj.groups.push(group);
+                group = Group {
+                    name,
+                    ..Default::default()
+                };
+            }
+            "usemtl" => material = Some(arguments.join(" ")),
+            "mtllib" => obj
+                .libraries
+                .extend(split_libraries(line[keyword.len()..].trim())),
+            _ => {}
+        }
+    }
+    obj.groups.push(group);
+
+    Ok(obj)
+}
+
+// Names of a `mtllib` line can contain spaces, each one ends
+// with the `.mtl` extension
+fn split_libraries(arguments: &str) -> Vec<String> {
+    let mut libraries = vec![];
+    let mut words: Vec<&str> = vec![];
+    for word in arguments.split(' ') {
+        words.push(word);
+        if word.to_lowercase().ends_with(".mtl") {
+            libraries.push(words.join(" ").trim().to_owned());
+            words.clear();
+        }
+    }
+    libraries.push(words.join(" ").trim().to_owned());
+    libraries.retain(|library| !library.is_empty());
+    libraries
+}
+
+// Indices start at 1, negative ones are relative to the end
+fn get_index(index: &str, count: usize) -> Option<usize> {
+    let index: i64 = index.parse().ok()?;
+    let index = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if index >= 0 && (index as usize) < count {
+        Some(index as usize)
+    } else {
+        None
+    }
+}
+
+// Corners are `v`, `v/vt`, `v//vn` or `v/vt/vn`
+fn parse_corner(corner: &str, obj: &Obj) -> Option<Corner> {
+    let mut indices = corner.split('/');
+    let position = get_index(indices.next()?, obj.positions.len())?;
+    let optional = |index: Option<&str>, count: usize| match index {
+        None | Some("") => Some(None),
+        Some(index) => get_index(index, count).map(Some),
+    };
+    let tex_coord = optional(indices.next(), obj.tex_coords.len())?;
+    let normal = optional(indices.next(), obj.normals.len())?;
+
+    Some(Corner {
+        position,
+        tex_coord,
+        normal,
+    })
+}
+
+// Faces of each material, in the order they are first used
+fn split_materials(faces: &[Face]) -> Vec<(Option<&str>, Vec<&Face>)> {
+    let mut materials: Vec<(Option<&str>, Vec<&Face>)> = vec![];
+    for face in faces {
+        let material = face.material.as_ref().map(|name| &name[..]);
+        match materials.iter().position(|(name, _)| *name == material) {
+            Some(index) => materials[index].1.push(face),
+            None => materials.push((material, vec![face])),
+        }
+    }
+    materials
+}
+
+// Corners used by several faces share their vertex
+fn get_primitive(
+    obj: &Obj,
+    faces: &[&Face],
+    material: Material,
+    options: &ImportOptions,
+) -> Primitive {
+    let corners = || faces.iter().flat_map(|face| &face.corners);
+    let has_tex_coords = corners().all(|corner| corner.tex_coord.is_some());
+    let has_normals = corners().all(|corner| corner.normal.is_some());
+
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    let mut shared: HashMap<Corner, u32> = HashMap::new();
+    for face in faces {
+        let polygon: Vec<Vector3<f32>> = face
+            .corners
+            .iter()
+            .map(|corner| obj.positions[corner.position])
+            .collect();
+        for triangle in triangulate(&polygon) {
+            for index in &triangle {
+                let corner = face.corners[*index];
+                let index = *shared.entry(corner).or_insert_with(|| {
+                    vertices.push(Vertex {
+                        position: obj.positions[corner.position],
+                        tex_coord_0: corner
+                            .tex_coord
+                            .map_or_else(Vector2::zeros, |t| obj.tex_coords[t]),
+                        normal: corner
+                            .normal
+                            .map_or_else(Vector3::zeros, |n| obj.normals[n]),
+                        ..Default::default()
+                    });
+                    vertices.len() as u32 - 1
+                });
+                indices.push(index);
+            }
+        }
+    }
+
+    let mut attributes = vec![Attribute::Position, Attribute::Normal];
+    if has_tex_coords {
+        attributes.push(Attribute::TexCoord0);
+    }
+    let mut indices = Some(indices);
+    if !has_normals {
+        match options.normals {
+            Normals::Flat => {
+                let triangles = indices.take();
+                vertices =
+                    flat_normals(&vertices, triangles.as_ref().map(|i| &i[..]));
+            }
+            Normals::Smooth => {
+                smooth_normals(&mut vertices, indices.as_ref().map(|i| &i[..]))
+            }
+        }
+    }
+    if material.normal_texture.is_some() && has_tex_coords {
+        generate_tangents(&mut vertices, indices.as_ref().map(|i| &i[..]), 0);
+        attributes.push(Attribute::Tangent);
+    }
+
+    Primitive::new(vertices, indices, &attributes).with_material(material)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use resolver::no_resolver;
+
+    fn import(source: &str) -> ObjFormater {
+        import_with(source, Normals::Flat)
+    }
+
+    fn import_with(source: &str, normals: Normals) -> ObjFormater {
+        let options = ImportOptions {
+            normals,
+            ..Default::default()
+        };
+        ObjFormater::from_slice(source.as_bytes(), options, no_resolver)
+            .unwrap()
+    }
+
+    fn first_primitive(obj: &ObjFormater) -> &Primitive {
+        &obj.meshes[0].get_primitives()[0]
+    }
+
+    const SQUARE: &str = "\
+                          v 0 0 0\n\
+                          v 1 0 0\n\
+                          v 1 1 0\n\
+                          v 0 1 0\n";
+
+    #[test]
+    fn polygons_are_split_in_triangles() {
+        let source = format!("{}v 0.5 1.5 0\nf 1 2 3 4\nf 1 2 3 5 4\n", SQUARE);
+        // A quad and a pentagon, sharing their corners
+        let obj = import_with(&source, Normals::Smooth);
+        let primitive = first_primitive(&obj);
+        assert_eq!(primitive.get_indices().unwrap().len(), 6 + 9);
+        assert_eq!(primitive.get_vertices().len(), 5);
+        for vertex in primitive.get_vertices() {
+            assert!((vertex.normal - Vector3::z()).norm() < 1e-5);
+        }
+
+        // Flat normals need a vertex by corner of triangle
+        let obj = import(&source);
+        let primitive = first_primitive(&obj);
+        assert!(primitive.get_indices().is_none());
+        assert_eq!(primitive.get_vertices().len(), 6 + 9);
+    }
+
+    #[test]
+    fn negative_indices_are_relative_to_the_end() {
+        let obj = parse_obj(&format!("{}f -4 -3 -2 -1\n", SQUARE)).unwrap();
+        let corners = &obj.groups[0].faces[0].corners;
+        let positions: Vec<usize> =
+            corners.iter().map(|corner| corner.position).collect();
+        assert_eq!(positions, vec![0, 1, 2, 3]);
+
+        for face in &["f 0 1 2", "f -5 1 2", "f 1 2 5", "f 1 2"] {
+            match parse_obj(&format!("{}{}\n", SQUARE, face)) {
+                Err(ImportError::Parse(_)) => {}
+                _ => panic!("Expected a parse error for {}", face),
+            }
+        }
+    }
+
+    #[test]
+    fn corners_can_have_a_normal_or_a_tex_coord() {
+        let normals = format!("{}vn 0 0 -1\nf 1//1 2//1 3//1\n", SQUARE);
+        let obj = import(&normals);
+        let primitive = first_primitive(&obj);
+        assert!(!primitive.get_layout().has(Attribute::TexCoord0));
+        for vertex in primitive.get_vertices() {
+            assert_eq!(vertex.normal, -Vector3::z());
+        }
+
+        let tex_coords =
+            format!("{}vt 0 0\nvt 1 0\nvt 1 0.25\nf 1/1 2/2 3/3\n", SQUARE);
+        let obj = import(&tex_coords);
+        let primitive = first_primitive(&obj);
+        assert!(primitive.get_layout().has(Attribute::TexCoord0));
+        let vertices = primitive.get_vertices();
+        // The origin of the texture moves to the top
+        assert_eq!(vertices[2].tex_coord_0, Vector2::new(1., 0.75));
+        // Normals are generated
+        assert!((vertices[0].normal - Vector3::z()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn groups_and_materials_split_the_meshes() {
+        let source = format!(
+            "{}g first part\n\
+             usemtl red\nf 1 2 3\n\
+             usemtl blue\nf 1 3 4\n\
+             usemtl red\nf 2 3 4\n\
+             o\nf 1 2 3\n\
+             g empty\n",
+            SQUARE
+        );
+        let obj = import(&source);
+        assert_eq!(obj.meshes.len(), 2);
+        assert_eq!(obj.nodes[0].name, Some("first part".to_owned()));
+        assert_eq!(obj.nodes[1].name, None);
+
+        let primitives = obj.meshes[0].get_primitives();
+        assert_eq!(primitives.len(), 2);
+        assert_eq!(primitives[0].get_vertices().len(), 6);
+        assert_eq!(primitives[1].get_vertices().len(), 3);
+        // Without MTL file, the materials are the default one
+        assert_eq!(primitives[0].material, default_material());
+        assert_eq!(obj.meshes[1].get_primitives().len(), 1);
+    }
+
+    #[test]
+    fn library_names_can_contain_spaces() {
+        assert_eq!(split_libraries("a.mtl"), vec!["a.mtl"]);
+        assert_eq!(
+            split_libraries("my materials.mtl  other.MTL"),
+            vec!["my materials.mtl", "other.MTL"]
+        );
+        assert_eq!(split_libraries("no extension"), vec!["no extension"]);
+
+        let source = "mtllib my materials.mtl\nv 0 0 0\n";
+        let mut read = vec![];
+        {
+            let resolver = |path: &str| {
+                read.push(path.to_owned());
+                Ok(b"newmtl a\n".to_vec())
+            };
+            let options = ImportOptions::default();
+            ObjFormater::from_slice(source.as_bytes(), options, resolver)
+                .unwrap();
+        }
+        assert_eq!(read, vec!["my materials.mtl"]);
+    }
+}
@@ -0,0 +1,473 @@
+use fuel_render::{AlphaMode, Material, MaterialTexture, Texture};
+use image::{self, Rgba, RgbaImage};
+use na::Vector3;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use ImportError;
+
+/// Textures read for the materials, each file once.
+#[derive(Default)]
+pub struct Textures {
+    pub textures: Vec<Texture>,
+    indices: HashMap<String, usize>,
+}
+
+impl Textures {
+    /// Index of the texture of a file, read by `resolver` the
+    /// first time.
+    pub fn get<F>(
+        &mut self,
+        path: &str,
+        resolver: &mut F,
+    ) -> Result<usize, ImportError>
+    where
+        F: FnMut(&str) -> io::Result<Vec<u8>>,
+    {
+        self.get_converted(path, path, resolver, |image| image)
+    }
+
+    /// Index of a texture made by `convert` from the image of a
+    /// file, converted once for each `key`.
+    fn get_converted<F, C>(
+        &mut self,
+        key: &str,
+        path: &str,
+        resolver: &mut F,
+        convert: C,
+    ) -> Result<usize, ImportError>
+    where
+        F: FnMut(&str) -> io::Result<Vec<u8>>,
+        C: FnOnce(RgbaImage) -> RgbaImage,
+    {
+        if let Some(index) = self.indices.get(key) {
+            return Ok(*index);
+        }
+
+        let data = resolver(path)
+            .map_err(|error| ImportError::Resolve(path.to_owned(), error))?;
+        let image = image::load_from_memory(&data)
+            .map_err(|error| ImportError::Image(path.to_owned(), error))?
+            .to_rgba();
+        let image = convert(image);
+        let name = Path::new(path)
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned());
+        let (width, height) = image.dimensions();
+        self.textures
+            .push(Texture::new(name, width, height, image.into_raw()));
+
+        let index = self.textures.len() - 1;
+        self.indices.insert(key.to_owned(), index);
+        Ok(index)
+    }
+}
+
+/// Material of OBJ files without MTL, matte white.
+pub fn default_material() -> Material {
+    Material {
+        metallic_factor: 0.,
+        ..Default::default()
+    }
+}
+
+/// Materials of a MTL file, converted to PBR ones without metal.
+/// The specular exponent gives the roughness, and a black
+/// specular color the roughest material. Specular maps give the
+/// roughness of each texel the same way, bump maps are heights
+/// converted to normal maps.
+///
+/// Textures are read relative to `path`, the path of the file.
+pub fn parse_mtl<F>(
+    source: &str,
+    path: &str,
+    textures: &mut Textures,
+    resolver: &mut F,
+) -> Result<Vec<Material>, ImportError>
+where
+    F: FnMut(&str) -> io::Result<Vec<u8>>,
+{
+    let directory = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let mut materials: Vec<Material> = vec![];
+    let mut speculars: Vec<Specular> = vec![];
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments: Vec<&str> = words.collect();
+        let error = |message: &str| {
+            ImportError::Parse(format!("{}:{}: {}", path, number + 1, message))
+        };
+
+        if keyword == "newmtl" {
+            materials.push(Material {
+                name: Some(arguments.join(" ")),
+                ..default_material()
+            });
+            speculars.push(Specular::default());
+            continue;
+        }
+        // Properties are ignored until the first material
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => continue,
+        };
+        let specular = speculars.last_mut().expect("A specular by material");
+
+        match keyword {
+            "Kd" => {
+                let color = parse_color(&arguments)
+                    .ok_or_else(|| error("Invalid diffuse color"))?;
+                material.base_color_factor.x = color.x;
+                material.base_color_factor.y = color.y;
+                material.base_color_factor.z = color.z;
+            }
+            "Ks" => {
+                specular.color = parse_color(&arguments)
+                    .ok_or_else(|| error("Invalid specular color"))?;
+            }
+            "Ns" => {
+                specular.exponent = parse_number(&arguments)
+                    .ok_or_else(|| error("Invalid specular exponent"))?;
+            }
+            "Ke" => {
+                material.emissive_factor = parse_color(&arguments)
+                    .ok_or_else(|| error("Invalid emissive color"))?;
+            }
+            "d" | "Tr" => {
+                let value = parse_number(&arguments)
+                    .ok_or_else(|| error("Invalid transparency"))?;
+                material.base_color_factor.w =
+                    if keyword == "d" { value } else { 1. - value };
+            }
+            "map_Kd" | "map_Ke" | "map_Ks" | "map_Bump" | "map_bump"
+            | "bump" | "norm" => {
+                // Options come before the file name
+                let file = arguments
+                    .last()
+                    .ok_or_else(|| error("Missing texture file"))?;
+                let file = directory.join(file).to_string_lossy().into_owned();
+                let texture = |index| {
+                    Some(MaterialTexture {
+                        index,
+                        tex_coord: 0,
+                    })
+                };
+                match keyword {
+                    "map_Kd" => {
+                        let index = textures.get(&file, resolver)?;
+                        material.base_color_texture = texture(index);
+                    }
+                    "map_Ke" => {
+                        let index = textures.get(&file, resolver)?;
+                        material.emissive_texture = texture(index);
+                    }
+                    // Converted with the exponent, once it is known
+                    "map_Ks" => specular.map = Some(file),
+                    "norm" => {
+                        let index = textures.get(&file, resolver)?;
+                        material.normal_texture = texture(index);
+                        material.normal_scale = get_bump_scale(&arguments);
+                    }
+                    _ => {
+                        let key = format!("{}#bump", file);
+                        let index = textures.get_converted(
+                            &key,
+                            &file,
+                            resolver,
+                            |heights| get_normal_map(&heights),
+                        )?;
+                        material.normal_texture = texture(index);
+                        material.normal_scale = get_bump_scale(&arguments);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (material, specular) in materials.iter_mut().zip(speculars) {
+        let roughness = specular.get_roughness();
+        material.roughness_factor = roughness;
+        if let Some(file) = specular.map {
+            let color = specular.color;
+            let key = format!("{}#roughness {:?} {}", file, color, roughness);
+            let index =
+                textures.get_converted(&key, &file, resolver, |map| {
+                    get_roughness_map(&map, &color, roughness)
+                })?;
+            // The roughness is in the texture
+            material.roughness_factor = 1.;
+            material.metallic_roughness_texture = Some(MaterialTexture {
+                index,
+                tex_coord: 0,
+            });
+        }
+        if material.base_color_factor.w < 1. {
+            material.alpha_mode = AlphaMode::Blend;
+        }
+    }
+
+    Ok(materials)
+}
+
+// Specular color, exponent and map of a material
+struct Specular {
+    color: Vector3<f32>,
+    exponent: f32,
+    map: Option<String>,
+}
+
+impl Default for Specular {
+    fn default() -> Self {
+        Specular {
+            color: Vector3::repeat(1.),
+            exponent: 0.,
+            map: None,
+        }
+    }
+}
+
+impl Specular {
+    fn get_roughness(&self) -> f32 {
+        if self.color == Vector3::zeros() {
+            1.
+        } else {
+            // Blinn-Phong exponent to the roughness of GGX
+            (2. / (self.exponent.max(0.) + 2.)).sqrt()
+        }
+    }
+}
+
+// Roughness in green, from 1 for black to `roughness` for white
+fn get_roughness_map(
+    map: &RgbaImage,
+    color: &Vector3<f32>,
+    roughness: f32,
+) -> RgbaImage {
+    let luminance = Vector3::new(0.2126, 0.7152, 0.0722);
+    let mut roughness_map = map.clone();
+    for pixel in roughness_map.pixels_mut() {
+        let texel = Vector3::new(pixel.data[0], pixel.data[1], pixel.data[2])
+            .map(|channel| f32::from(channel) / 255.);
+        let specular = texel.component_mul(color).dot(&luminance).min(1.);
+        let texel_roughness = 1. - (1. - roughness) * specular;
+        // Metals are in blue, none here
+        *pixel = Rgba([0, (texel_roughness * 255.).round() as u8, 0, 255]);
+    }
+    roughness_map
+}
+
+// Normal map of a height map, a height of 1 rises by a texel
+fn get_normal_map(heights: &RgbaImage) -> RgbaImage {
+    let (width, height) = heights.dimensions();
+    let get_height = |x: i64, y: i64| {
+        let x = x.max(0).min(i64::from(width) - 1) as u32;
+        let y = y.max(0).min(i64::from(height) - 1) as u32;
+        let pixel = heights.get_pixel(x, y).data;
+        let sum =
+            u32::from(pixel[0]) + u32::from(pixel[1]) + u32::from(pixel[2]);
+        sum as f32 / (3. * 255.)
+    };
+    RgbaImage::from_fn(width, height, |x, y| {
+        let (x, y) = (i64::from(x), i64::from(y));
+        // Rows go down, and the Y of normal maps up
+        let slope_x = (get_height(x + 1, y) - get_height(x - 1, y)) / 2.;
+        let slope_y = (get_height(x, y - 1) - get_height(x, y + 1)) / 2.;
+        let normal = Vector3::new(-slope_x, -slope_y, 1.).normalize();
+        let color =
+            normal.map(|value| ((value + 1.) / 2. * 255.).round() as u8);
+        Rgba([color.x, color.y, color.z, 255])
+    })
+}
+
+fn parse_number(arguments: &[&str]) -> Option<f32> {
+    arguments.first()?.parse().ok()
+}
+
+// A single value is grey
+fn parse_color(arguments: &[&str]) -> Option<Vector3<f32>> {
+    let values = arguments
+        .iter()
+        .map(|argument| argument.parse().ok())
+        .collect::<Option<Vec<f32>>>()?;
+    match values.len() {
+        1 => Some(Vector3::repeat(values[0])),
+        3 => Some(Vector3::new(values[0], values[1], values[2])),
+        _ => None,
+    }
+}
+
+fn get_bump_scale(arguments: &[&str]) -> f32 {
+    arguments
+        .iter()
+        .position(|argument| *argument == "-bm")
+        .and_then(|index| arguments.get(index + 1))
+        .and_then(|scale| scale.parse().ok())
+        .unwrap_or(1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::png::PNGEncoder;
+    use image::ColorType;
+    use na::Vector4;
+
+    fn png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+        let mut data = vec![];
+        PNGEncoder::new(&mut data)
+            .encode(pixels, width, height, ColorType::RGBA(8))
+            .unwrap();
+        data
+    }
+
+    // Files read by the resolver, in order
+    fn parse(
+        source: &str,
+        files: &[(&str, Vec<u8>)],
+    ) -> (Vec<Material>, Textures, Vec<String>) {
+        let mut textures = Textures::default();
+        let mut read = vec![];
+        let materials = {
+            let mut resolver = |path: &str| {
+                read.push(path.to_owned());
+                files
+                    .iter()
+                    .find(|(name, _)| *name == path)
+                    .map(|(_, data)| data.clone())
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+            };
+            parse_mtl(source, "models/a.mtl", &mut textures, &mut resolver)
+                .unwrap()
+        };
+        (materials, textures, read)
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn colors_give_the_factors() {
+        let source = "\
+                      Ka 1 1 1 # ignored\n\
+                      newmtl red paint\n\
+                      Kd 1 0 0\n\
+                      Ks 0\n\
+                      Ke 0.5\n\
+                      d 0.5\n\
+                      newmtl shiny\n\
+                      Kd 0.5\n\
+                      Ns 98\n\
+                      Tr 0\n";
+        let (materials, _, _) = parse(source, &[]);
+        assert_eq!(materials.len(), 2);
+
+        let red = &materials[0];
+        assert_eq!(red.name, Some("red paint".to_owned()));
+        assert_eq!(red.base_color_factor, Vector4::new(1., 0., 0., 0.5));
+        assert_eq!(red.emissive_factor, Vector3::repeat(0.5));
+        assert_eq!(red.alpha_mode, AlphaMode::Blend);
+        // A black specular color is the roughest
+        assert!(close(red.roughness_factor, 1.));
+        assert!(close(red.metallic_factor, 0.));
+
+        let shiny = &materials[1];
+        assert_eq!(shiny.base_color_factor, Vector4::new(0.5, 0.5, 0.5, 1.));
+        assert!(close(shiny.roughness_factor, (2f32 / 100.).sqrt()));
+        assert_eq!(shiny.alpha_mode, AlphaMode::Opaque);
+    }
+
+    #[test]
+    fn invalid_values_are_refused() {
+        let mut textures = Textures::default();
+        for source in &["newmtl a\nKd 1 0", "newmtl a\nNs high"] {
+            let result =
+                parse_mtl(source, "a.mtl", &mut textures, &mut |_: &str| {
+                    Ok(vec![])
+                });
+            match result {
+                Err(ImportError::Parse(_)) => {}
+                other => panic!("Expected a parse error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn textures_are_read_once_relative_to_the_file() {
+        let source = "\
+                      newmtl a\n\
+                      map_Kd -s 1 1 1 wood.png\n\
+                      map_Ke wood.png\n\
+                      newmtl b\n\
+                      map_Kd wood.png\n\
+                      norm -bm 0.5 normal.png\n";
+        let files = [
+            ("models/wood.png", png(1, 1, &[10, 20, 30, 255])),
+            ("models/normal.png", png(1, 1, &[128, 128, 255, 255])),
+        ];
+        let (materials, textures, read) = parse(source, &files);
+        assert_eq!(read, vec!["models/wood.png", "models/normal.png"]);
+        assert_eq!(textures.textures.len(), 2);
+        assert_eq!(textures.textures[0].pixels, vec![10, 20, 30, 255]);
+
+        let index = |texture: Option<MaterialTexture>| texture.unwrap().index;
+        assert_eq!(index(materials[0].base_color_texture), 0);
+        assert_eq!(index(materials[0].emissive_texture), 0);
+        assert_eq!(index(materials[1].base_color_texture), 0);
+        // Normal maps are kept as they are
+        assert_eq!(index(materials[1].normal_texture), 1);
+        assert!(close(materials[1].normal_scale, 0.5));
+    }
+
+    #[test]
+    fn bump_maps_are_converted_to_normal_maps() {
+        // Heights rising to the right
+        let heights = [0, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255, 255];
+        let files = [("models/bump.png", png(3, 1, &heights))];
+        let (materials, textures, _) =
+            parse("newmtl a\nbump -bm 2 bump.png\n", &files);
+        assert!(close(materials[0].normal_scale, 2.));
+
+        let normal_map = &textures.textures[0];
+        assert_eq!((normal_map.width, normal_map.height), (3, 1));
+        let normal = |x: usize| {
+            let pixel = &normal_map.pixels[x * 4..x * 4 + 4];
+            Vector3::new(pixel[0], pixel[1], pixel[2])
+                .map(|value| f32::from(value) / 255. * 2. - 1.)
+        };
+        // The slope faces the left, and is flat on the right
+        assert!(normal(0).x < -0.3 && normal(1).x < -0.3);
+        assert!(normal(2).x.abs() < 0.01);
+        for x in 0..3 {
+            assert!(normal(x).y.abs() < 0.01 && normal(x).z > 0.8);
+        }
+    }
+
+    #[test]
+    fn specular_maps_give_the_roughness() {
+        let source = "\
+                      newmtl a\n\
+                      Ns 98\n\
+                      map_Ks specular.png\n";
+        let pixels = [255, 255, 255, 255, 0, 0, 0, 255];
+        let files = [("models/specular.png", png(2, 1, &pixels))];
+        let (materials, textures, _) = parse(source, &files);
+
+        let material = &materials[0];
+        let texture = material.metallic_roughness_texture.unwrap();
+        assert!(close(material.roughness_factor, 1.));
+        let pixels = &textures.textures[texture.index].pixels;
+        // White has the roughness of the exponent, black is rough
+        let roughness = (2f32 / 100.).sqrt();
+        assert_eq!(pixels[1], (roughness * 255.).round() as u8);
+        assert_eq!(pixels[5], 255);
+        // Never metallic
+        assert_eq!((pixels[2], pixels[6]), (0, 0));
+    }
+}
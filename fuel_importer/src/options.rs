@@ -0,0 +1,10 @@
+use fuel_render::Normals;
+
+/// Choices made while importing a file.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImportOptions {
+    /// Scene of the file imported, the default one when `None`.
+    pub scene: Option<usize>,
+    /// Normals generated for primitives without them.
+    pub normals: Normals,
+}
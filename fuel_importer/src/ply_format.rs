@@ -0,0 +1,546 @@
+use fuel_render::backend::DrawMode;
+use fuel_render::{
+    flat_normals, smooth_normals, triangulate, Attribute, Mesh, Meshes, Model,
+    Normals, Primitive, Vertex,
+};
+use fuel_types::bytes::{read_f32, read_f64, read_uint};
+use fuel_types::Transform;
+use na::{Vector2, Vector3, Vector4};
+use obj_material::default_material;
+use std::borrow::Cow;
+use std::fs;
+use std::str::SplitWhitespace;
+use {Formater, ImportError, ImportOptions};
+
+/// Format PLY files, ASCII or binary, to fit with the render
+/// engine.
+///
+/// Vertices keep their normals, colors and texture coordinates,
+/// integer colors are converted from sRGB to linear.
+/// Faces are split in triangles, files without faces are point
+/// clouds drawn as points.
+pub struct PlyFormater {
+    pub meshes: Meshes,
+    pub transform: Transform,
+}
+
+impl PlyFormater {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(ply_file_path: &str) -> Result<Self, ImportError> {
+        Self::with_options(ply_file_path, ImportOptions::default())
+    }
+
+    /// Scenes of the options are ignored.
+    pub fn with_options(
+        ply_file_path: &str,
+        options: ImportOptions,
+    ) -> Result<Self, ImportError> {
+        Self::from_slice(&fs::read(ply_file_path)?, options)
+    }
+
+    /// Import the content of a PLY file.
+    pub fn from_slice(
+        data: &[u8],
+        options: ImportOptions,
+    ) -> Result<Self, ImportError> {
+        let (header, data) = split_header(data)?;
+        let (encoding, elements) = parse_header(&header)?;
+        let text = match encoding {
+            Encoding::Ascii => String::from_utf8_lossy(data),
+            Encoding::Binary { .. } => Cow::Borrowed(""),
+        };
+        let mut body = match encoding {
+            Encoding::Ascii => Body::Ascii(text.split_whitespace()),
+            Encoding::Binary { big_endian } => Body::Binary {
+                data,
+                offset: 0,
+                big_endian,
+            },
+        };
+
+        let mut ply = Ply::default();
+        for element in &elements {
+            match &element.name[..] {
+                "vertex" => read_vertices(element, &mut body, &mut ply)?,
+                "face" => read_faces(element, &mut body, &mut ply)?,
+                _ => read_rows(element, &mut body, |_| Ok(()))?,
+            }
+        }
+
+        Ok(Self {
+            meshes: vec![Mesh::new(vec![get_primitive(ply, &options)?])],
+            transform: Transform::default(),
+        })
+    }
+}
+
+impl Formater for PlyFormater {
+    fn to_model(self) -> Model {
+        Model::new(self.transform, self.meshes, vec![])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scalar {
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(Scalar::Int8),
+            "uchar" | "uint8" => Some(Scalar::Uint8),
+            "short" | "int16" => Some(Scalar::Int16),
+            "ushort" | "uint16" => Some(Scalar::Uint16),
+            "int" | "int32" => Some(Scalar::Int32),
+            "uint" | "uint32" => Some(Scalar::Uint32),
+            "float" | "float32" => Some(Scalar::Float32),
+            "double" | "float64" => Some(Scalar::Float64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::Int8 | Scalar::Uint8 => 1,
+            Scalar::Int16 | Scalar::Uint16 => 2,
+            Scalar::Int32 | Scalar::Uint32 | Scalar::Float32 => 4,
+            Scalar::Float64 => 8,
+        }
+    }
+
+    fn is_integer(self) -> bool {
+        match self {
+            Scalar::Float32 | Scalar::Float64 => false,
+            _ => true,
+        }
+    }
+
+    // Integer colors go up to the largest value of their type
+    fn max(self) -> f32 {
+        match self {
+            Scalar::Int8 => 127.,
+            Scalar::Uint8 => 255.,
+            Scalar::Int16 => 32_767.,
+            Scalar::Uint16 => 65_535.,
+            Scalar::Int32 => 2_147_483_647.,
+            Scalar::Uint32 => 4_294_967_295.,
+            Scalar::Float32 | Scalar::Float64 => 1.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Scalar(Scalar),
+    /// Type of the length, then of the values.
+    List(Scalar, Scalar),
+}
+
+struct Property {
+    name: String,
+    kind: Kind,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    // Index of the first property with one of the names
+    fn find(&self, names: &[&str]) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|property| names.contains(&&property.name[..]))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Ascii,
+    Binary { big_endian: bool },
+}
+
+enum Body<'a> {
+    Ascii(SplitWhitespace<'a>),
+    Binary {
+        data: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl<'a> Body<'a> {
+    // Whether the rest of the data can hold the rows of an
+    // element, each one with at least a value by property
+    fn can_hold(&self, element: &Element) -> bool {
+        let (remaining, min_size) = match self {
+            Body::Ascii(words) => {
+                (words.clone().count(), element.properties.len())
+            }
+            Body::Binary { data, offset, .. } => {
+                let min_size = element
+                    .properties
+                    .iter()
+                    .map(|property| match property.kind {
+                        Kind::Scalar(scalar) | Kind::List(scalar, _) => {
+                            scalar.size()
+                        }
+                    })
+                    .sum();
+                (data.len() - *offset, min_size)
+            }
+        };
+        match min_size {
+            0 => element.count == 0,
+            _ => element.count <= remaining / min_size,
+        }
+    }
+
+    fn read(&mut self, scalar: Scalar) -> Result<f64, ImportError> {
+        let truncated = || ImportError::Parse("Truncated PLY data".to_owned());
+        match self {
+            Body::Ascii(words) => words
+                .next()
+                .and_then(|word| word.parse().ok())
+                .ok_or_else(truncated),
+            Body::Binary {
+                data,
+                offset,
+                big_endian,
+            } => {
+                let end = *offset + scalar.size();
+                let bytes = data.get(*offset..end).ok_or_else(truncated)?;
+                *offset = end;
+                let value = read_uint(bytes, *big_endian);
+                Ok(match scalar {
+                    Scalar::Int8 => f64::from(value as u8 as i8),
+                    Scalar::Int16 => f64::from(value as u16 as i16),
+                    Scalar::Int32 => f64::from(value as u32 as i32),
+                    Scalar::Uint8 | Scalar::Uint16 | Scalar::Uint32 => {
+                        value as f64
+                    }
+                    Scalar::Float32 => f64::from(read_f32(bytes, *big_endian)),
+                    Scalar::Float64 => read_f64(bytes, *big_endian),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Ply {
+    vertices: Vec<Vertex>,
+    faces: Vec<Vec<u32>>,
+    has_normals: bool,
+    has_colors: bool,
+    has_tex_coords: bool,
+}
+
+// The header is text, up to the end of its `end_header` line
+fn split_header(data: &[u8]) -> Result<(String, &[u8]), ImportError> {
+    let error = |message: &str| ImportError::Parse(message.to_owned());
+    if !data.starts_with(b"ply") {
+        return Err(error("Not a PLY file"));
+    }
+    let marker = b"end_header";
+    let end = data
+        .windows(marker.len())
+        .position(|window| window == marker)
+        .ok_or_else(|| error("PLY header without end_header"))?;
+    let body = data[end..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(data.len(), |line_end| end + line_end + 1);
+
+    Ok((
+        String::from_utf8_lossy(&data[..end]).into_owned(),
+        &data[body..],
+    ))
+}
+
+fn parse_header(header: &str) -> Result<(Encoding, Vec<Element>), ImportError> {
+    let mut encoding = None;
+    let mut elements: Vec<Element> = vec![];
+
+    for (number, line) in header.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let error = |message: &str| {
+            ImportError::Parse(format!("line {}: {}", number + 1, message))
+        };
+        let scalar = |name: &str| {
+            Scalar::parse(name).ok_or_else(|| error("Unknown property type"))
+        };
+
+        match words.get(0).cloned() {
+            Some("format") => {
+                encoding = match words.get(1).cloned() {
+                    Some("ascii") => Some(Encoding::Ascii),
+                    Some("binary_little_endian") => {
+                        Some(Encoding::Binary { big_endian: false })
+                    }
+                    Some("binary_big_endian") => {
+                        Some(Encoding::Binary { big_endian: true })
+                    }
+                    _ => return Err(error("Unknown format")),
+                };
+            }
+            Some("element") => {
+                if words.len() != 3 {
+                    return Err(error("Invalid element"));
+                }
+                elements.push(Element {
+                    name: words[1].to_owned(),
+                    count: words[2]
+                        .parse()
+                        .map_err(|_| error("Invalid element count"))?,
+                    properties: vec![],
+                });
+            }
+            Some("property") => {
+                let kind = match words.len() {
+                    3 => Kind::Scalar(scalar(words[1])?),
+                    5 if words[1] == "list" => {
+                        Kind::List(scalar(words[2])?, scalar(words[3])?)
+                    }
+                    _ => return Err(error("Invalid property")),
+                };
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("Property outside of an element"))?;
+                element.properties.push(Property {
+                    name: words[words.len() - 1].to_owned(),
+                    kind,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let encoding = encoding
+        .ok_or_else(|| ImportError::Parse("PLY without format".to_owned()))?;
+    Ok((encoding, elements))
+}
+
+// Rows are given with the values of each property, lists can
+// have any number of them
+fn read_rows<F>(
+    element: &Element,
+    body: &mut Body,
+    mut read_row: F,
+) -> Result<(), ImportError>
+where
+    F: FnMut(&[Vec<f64>]) -> Result<(), ImportError>,
+{
+    // Counts of the header are not trusted
+    if !body.can_hold(element) {
+        return Err(ImportError::Parse(format!(
+            "PLY data too short for {} {}",
+            element.count, element.name
+        )));
+    }
+
+    let mut row = vec![vec![]; element.properties.len()];
+    for _ in 0..element.count {
+        for (property, values) in element.properties.iter().zip(&mut row) {
+            values.clear();
+            match property.kind {
+                Kind::Scalar(scalar) => values.push(body.read(scalar)?),
+                Kind::List(length, scalar) => {
+                    let length =
+                        to_index(body.read(length)?).ok_or_else(|| {
+                            ImportError::Parse("Invalid list length".to_owned())
+                        })?;
+                    for _ in 0..length {
+                        values.push(body.read(scalar)?);
+                    }
+                }
+            }
+        }
+        read_row(&row)?;
+    }
+
+    Ok(())
+}
+
+fn to_index(value: f64) -> Option<usize> {
+    if value >= 0. && value <= f64::from(u32::max_value()) {
+        Some(value as usize)
+    } else {
+        None
+    }
+}
+
+fn read_vertices(
+    element: &Element,
+    body: &mut Body,
+    ply: &mut Ply,
+) -> Result<(), ImportError> {
+    let find_all = |names: &[&[&str]]| {
+        names
+            .iter()
+            .map(|names| element.find(names))
+            .collect::<Option<Vec<usize>>>()
+    };
+    let positions = find_all(&[&["x"], &["y"], &["z"]]).ok_or_else(|| {
+        ImportError::Parse("PLY vertices without position".to_owned())
+    })?;
+    let normals = find_all(&[&["nx"], &["ny"], &["nz"]]);
+    let colors = find_all(&[
+        &["red", "diffuse_red", "r"],
+        &["green", "diffuse_green", "g"],
+        &["blue", "diffuse_blue", "b"],
+    ]);
+    let alpha = element.find(&["alpha", "a"]);
+    let tex_coords = find_all(&[
+        &["s", "u", "texture_u", "texture_s"],
+        &["t", "v", "texture_v", "texture_t"],
+    ]);
+    ply.has_normals = normals.is_some();
+    ply.has_colors = colors.is_some();
+    ply.has_tex_coords = tex_coords.is_some();
+
+    let scalar = |index: usize| match element.properties[index].kind {
+        Kind::Scalar(scalar) | Kind::List(_, scalar) => scalar,
+    };
+    let vertices = &mut ply.vertices;
+    read_rows(element, body, |row| {
+        let value = |index: usize| row[index].get(0).cloned().unwrap_or(0.);
+        let vector = |indices: &[usize]| {
+            Vector3::new(
+                value(indices[0]) as f32,
+                value(indices[1]) as f32,
+                value(indices[2]) as f32,
+            )
+        };
+        let mut vertex = Vertex {
+            position: vector(&positions),
+            ..Default::default()
+        };
+        if let Some(normals) = &normals {
+            vertex.normal = vector(normals);
+        }
+        if let Some(colors) = &colors {
+            let channel =
+                |index: usize| value(index) as f32 / scalar(index).max();
+            // Integer colors are sRGB, alpha is always linear
+            let color = |index: usize| {
+                if scalar(index).is_integer() {
+                    to_linear(channel(index))
+                } else {
+                    channel(index)
+                }
+            };
+            let alpha = alpha.map_or(1., channel);
+            vertex.color_0 = Vector4::new(
+                color(colors[0]),
+                color(colors[1]),
+                color(colors[2]),
+                alpha,
+            );
+        }
+        if let Some(tex_coords) = &tex_coords {
+            // Same origin as OpenGL, at the bottom
+            let (u, v) = (value(tex_coords[0]), value(tex_coords[1]));
+            vertex.tex_coord_0 = Vector2::new(u as f32, 1. - v as f32);
+        }
+        vertices.push(vertex);
+        Ok(())
+    })
+}
+
+fn to_linear(channel: f32) -> f32 {
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn read_faces(
+    element: &Element,
+    body: &mut Body,
+    ply: &mut Ply,
+) -> Result<(), ImportError> {
+    let indices = element
+        .find(&["vertex_indices", "vertex_index"])
+        .ok_or_else(|| {
+            ImportError::Parse("PLY faces without vertices".to_owned())
+        })?;
+    let faces = &mut ply.faces;
+    read_rows(element, body, |row| {
+        let face = row[indices]
+            .iter()
+            .map(|index| to_index(*index).map(|index| index as u32))
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(|| {
+                ImportError::Parse("Invalid PLY vertex index".to_owned())
+            })?;
+        faces.push(face);
+        Ok(())
+    })
+}
+
+fn get_primitive(
+    ply: Ply,
+    options: &ImportOptions,
+) -> Result<Primitive, ImportError> {
+    let mut vertices = ply.vertices;
+    let mut attributes = vec![Attribute::Position];
+    if ply.has_colors {
+        attributes.push(Attribute::Color0);
+    }
+    if ply.has_tex_coords {
+        attributes.push(Attribute::TexCoord0);
+    }
+
+    if ply.faces.is_empty() {
+        if ply.has_normals {
+            attributes.push(Attribute::Normal);
+        }
+        return Ok(Primitive::new(vertices, None, &attributes)
+            .with_material(default_material())
+            .with_draw_mode(DrawMode::Points));
+    }
+
+    let mut indices = vec![];
+    for face in &ply.faces {
+        let polygon = face
+            .iter()
+            .map(|index| vertices.get(*index as usize).map(|v| v.position))
+            .collect::<Option<Vec<Vector3<f32>>>>()
+            .ok_or_else(|| {
+                ImportError::Parse("PLY face with a missing vertex".to_owned())
+            })?;
+        for triangle in triangulate(&polygon) {
+            indices.extend(triangle.iter().map(|corner| face[*corner]));
+        }
+    }
+
+    attributes.push(Attribute::Normal);
+    let mut indices = Some(indices);
+    if !ply.has_normals {
+        match options.normals {
+            Normals::Flat => {
+                let triangles = indices.take();
+                vertices =
+                    flat_normals(&vertices, triangles.as_ref().map(|i| &i[..]));
+            }
+            Normals::Smooth => {
+                smooth_normals(&mut vertices, indices.as_ref().map(|i| &i[..]))
+            }
+        }
+    }
+
+    Ok(Primitive::new(vertices, indices, &attributes)
+        .with_material(default_material()))
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::str;
+
+/// Resolver reading relative URIs from `base`, and file URIs.
+/// URIs are percent-decoded, relative ones can't leave `base`.
+pub fn file_resolver(base: &Path) -> impl FnMut(&str) -> io::Result<Vec<u8>> {
+    let base = base.to_path_buf();
+    move |uri| {
+        if uri.starts_with("file://") {
+            fs::read(percent_decode(&uri["file://".len()..])?)
+        } else if uri.starts_with("file:") {
+            fs::read(percent_decode(&uri["file:".len()..])?)
+        } else {
+            fs::read(join(&base, &percent_decode(uri)?)?)
+        }
+    }
+}
+
+/// Resolver reading paths relative to `base`, as they are
+/// written in OBJ and MTL files. They can't leave `base`.
+pub fn path_resolver(base: &Path) -> impl FnMut(&str) -> io::Result<Vec<u8>> {
+    let base = base.to_path_buf();
+    move |path| fs::read(join(&base, path)?)
+}
+
+/// Resolver of assets without external files.
+pub fn no_resolver(uri: &str) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No resolver given for {}", uri),
+    ))
+}
+
+// Paths going up more than they went down, or absolute ones,
+// would read files outside of the base
+fn join(base: &Path, path: &str) -> io::Result<PathBuf> {
+    let outside = || {
+        io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is outside of {}", path, base.display()),
+        )
+    };
+
+    let mut depth = 0usize;
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => (),
+            Component::ParentDir => {
+                depth = depth.checked_sub(1).ok_or_else(outside)?
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(outside()),
+        }
+    }
+    Ok(base.join(path))
+}
+
+// Escapes are `%` followed by two hexadecimal digits
+fn percent_decode(uri: &str) -> io::Result<String> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid escape in {}", uri),
+        )
+    };
+
+    let mut bytes = Vec::with_capacity(uri.len());
+    let mut rest = uri.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+        let digits = rest.get(..2).ok_or_else(invalid)?;
+        let digits = str::from_utf8(digits).map_err(|_| invalid())?;
+        bytes.push(u8::from_str_radix(digits, 16).map_err(|_| invalid())?);
+        rest = &rest[2..];
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn escapes_are_decoded() {
+        assert_eq!(
+            percent_decode("my%20texture.png").unwrap(),
+            "my texture.png"
+        );
+        assert_eq!(percent_decode("%C3%A9t%c3%a9.bin").unwrap(), "été.bin");
+        assert_eq!(percent_decode("plain.png").unwrap(), "plain.png");
+        assert!(percent_decode("bad%2").is_err());
+        assert!(percent_decode("bad%zz").is_err());
+        assert!(percent_decode("bad%FF").is_err());
+    }
+
+    #[test]
+    fn paths_stay_in_the_base() {
+        let base = Path::new("models");
+        assert_eq!(join(base, "a/../b.png").unwrap(), base.join("a/../b.png"));
+        assert_eq!(join(base, "./b.png").unwrap(), base.join("./b.png"));
+        assert!(join(base, "../b.png").is_err());
+        assert!(join(base, "a/../../b.png").is_err());
+        assert!(join(base, "/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn file_resolver_reads_encoded_uris() {
+        let base = env::temp_dir().join(format!("fuel-{}", process::id()));
+        fs::create_dir_all(base.join("dir")).unwrap();
+        fs::write(base.join("dir").join("my texture.png"), b"data").unwrap();
+
+        let mut resolver = file_resolver(&base.join("dir"));
+        assert_eq!(resolver("my%20texture.png").unwrap(), b"data");
+        let error = resolver("../dir/my%20texture.png").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        // Escaped dots are still dots
+        assert!(resolver("%2E%2E/dir/my%20texture.png").is_err());
+
+        let mut resolver = path_resolver(&base.join("dir"));
+        assert_eq!(resolver("my texture.png").unwrap(), b"data");
+        assert!(resolver("../dir/my texture.png").is_err());
+
+        fs::remove_dir_all(base).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+use fuel_render::{Attribute, Mesh, Meshes, Model, Node, Primitive, Vertex};
+use fuel_types::bytes::{read_f32, read_uint};
+use fuel_types::Transform;
+use na::Vector3;
+use obj_material::default_material;
+use std::fs;
+use {Formater, ImportError};
+
+const HEADER_SIZE: usize = 84;
+const TRIANGLE_SIZE: usize = 50;
+
+/// Format STL files, ASCII or binary, to fit with the render
+/// engine.
+///
+/// Each solid is a mesh, its triangles keep the normal of their
+/// facet. Facets without normal get the one of their triangle.
+pub struct StlFormater {
+    pub meshes: Meshes,
+    pub nodes: Vec<Node>,
+    pub transform: Transform,
+}
+
+impl StlFormater {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(stl_file_path: &str) -> Result<Self, ImportError> {
+        Self::from_slice(&fs::read(stl_file_path)?)
+    }
+
+    /// Import the content of an STL file.
+    pub fn from_slice(data: &[u8]) -> Result<Self, ImportError> {
+        // Binary files can start with `solid` too
+        let solids = if is_binary(data) {
+            vec![parse_binary(data)?]
+        } else if String::from_utf8_lossy(&data[..data.len().min(80)])
+            .trim_start()
+            .starts_with("solid")
+        {
+            parse_ascii(&String::from_utf8_lossy(data))?
+        } else {
+            vec![parse_binary(data)?]
+        };
+
+        let nodes = solids
+            .iter()
+            .enumerate()
+            .map(|(index, (name, _))| Node {
+                name: name.clone(),
+                mesh: Some(index),
+                ..Default::default()
+            })
+            .collect();
+        let meshes = solids
+            .into_iter()
+            .map(|(_, vertices)| {
+                let attributes = [Attribute::Position, Attribute::Normal];
+                let primitive = Primitive::new(vertices, None, &attributes)
+                    .with_material(default_material());
+                Mesh::new(vec![primitive])
+            })
+            .collect();
+
+        Ok(Self {
+            meshes,
+            nodes,
+            transform: Transform::default(),
+        })
+    }
+}
+
+impl Formater for StlFormater {
+    fn to_model(self) -> Model {
+        let roots = (0..self.nodes.len()).collect();
+        Model::new(self.transform, self.meshes, vec![])
+            .with_nodes(self.nodes, roots)
+    }
+}
+
+// Name of a solid, with the vertices of its triangles
+type Solid = (Option<String>, Vec<Vertex>);
+
+fn is_binary(data: &[u8]) -> bool {
+    data.len() >= HEADER_SIZE
+        && read_uint(&data[80..84], false) as usize * TRIANGLE_SIZE
+            == data.len() - HEADER_SIZE
+}
+
+fn parse_binary(data: &[u8]) -> Result<Solid, ImportError> {
+    if data.len() < HEADER_SIZE {
+        return Err(ImportError::Parse("Truncated STL header".to_owned()));
+    }
+    let count = read_uint(&data[80..84], false) as usize;
+    let triangles = &data[HEADER_SIZE..];
+    if triangles.len() < count * TRIANGLE_SIZE {
+        return Err(ImportError::Parse("Truncated STL triangles".to_owned()));
+    }
+
+    let vector = |bytes: &[u8]| {
+        Vector3::new(
+            read_f32(&bytes[0..4], false),
+            read_f32(&bytes[4..8], false),
+            read_f32(&bytes[8..12], false),
+        )
+    };
+    let mut vertices = Vec::with_capacity(count * 3);
+    for triangle in triangles.chunks(TRIANGLE_SIZE).take(count) {
+        let normal = vector(&triangle[0..12]);
+        let positions = [
+            vector(&triangle[12..24]),
+            vector(&triangle[24..36]),
+            vector(&triangle[36..48]),
+        ];
+        push_triangle(&mut vertices, normal, &positions);
+    }
+
+    Ok((None, vertices))
+}
+
+fn parse_ascii(source: &str) -> Result<Vec<Solid>, ImportError> {
+    let mut solids = vec![];
+    let mut solid: Option<Solid> = None;
+    let mut normal = Vector3::zeros();
+    let mut positions = vec![];
+
+    for (number, line) in source.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments: Vec<&str> = words.collect();
+        let error = |message: &str| {
+            ImportError::Parse(format!("line {}: {}", number + 1, message))
+        };
+        let vector = |values: &[&str]| -> Result<_, ImportError> {
+            let values = values
+                .iter()
+                .map(|value| value.parse().ok())
+                .collect::<Option<Vec<f32>>>()
+                .filter(|values| values.len() == 3)
+                .ok_or_else(|| error("Invalid vector"))?;
+            Ok(Vector3::new(values[0], values[1], values[2]))
+        };
+
+        match keyword {
+            "solid" => {
+                solids.extend(solid.take());
+                let name =
+                    Some(arguments.join(" ")).filter(|name| !name.is_empty());
+                solid = Some((name, vec![]));
+            }
+            "facet" => {
+                normal = vector(arguments.get(1..).unwrap_or(&[]))?;
+                positions.clear();
+            }
+            "vertex" => positions.push(vector(&arguments)?),
+            "endfacet" => {
+                if positions.len() != 3 {
+                    return Err(error("Facet without 3 vertices"));
+                }
+                let solid = solid
+                    .as_mut()
+                    .ok_or_else(|| error("Facet outside of a solid"))?;
+                push_triangle(&mut solid.1, normal, &positions);
+            }
+            "endsolid" => solids.extend(solid.take()),
+            _ => {}
+        }
+    }
+    solids.extend(solid);
+
+    Ok(solids)
+}
+
+fn push_triangle(
+    vertices: &mut Vec<Vertex>,
+    normal: Vector3<f32>,
+    positions: &[Vector3<f32>],
+) {
+    let normal = if normal.norm() > 1e-6 {
+        normal.normalize()
+    } else {
+        let edge_1 = positions[1] - positions[0];
+        let edge_2 = positions[2] - positions[0];
+        let normal = edge_1.cross(&edge_2);
+        if normal.norm() > 1e-12 {
+            normal.normalize()
+        } else {
+            Vector3::z()
+        }
+    };
+
+    vertices.extend(positions.iter().map(|position| Vertex {
+        position: *position,
+        normal,
+        ..Default::default()
+    }));
+}
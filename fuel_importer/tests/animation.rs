@@ -0,0 +1,72 @@
+extern crate fuel_importer;
+extern crate fuel_render;
+
+use fuel_importer::{ImportError, Importer};
+use fuel_render::KeyframesError;
+
+// Translation of a node, the buffer holds two times followed by
+// two translations
+fn animated(buffer: &str) -> String {
+    format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "nodes": [{{}}],
+            "buffers": [{{
+                "byteLength": 32,
+                "uri": "data:application/octet-stream;base64,{}"
+            }}],
+            "bufferViews": [
+                {{ "buffer": 0, "byteLength": 8 }},
+                {{ "buffer": 0, "byteOffset": 8, "byteLength": 24 }}
+            ],
+            "accessors": [
+                {{
+                    "bufferView": 0,
+                    "componentType": 5126,
+                    "count": 2,
+                    "type": "SCALAR"
+                }},
+                {{
+                    "bufferView": 1,
+                    "componentType": 5126,
+                    "count": 2,
+                    "type": "VEC3"
+                }}
+            ],
+            "animations": [{{
+                "channels": [{{
+                    "sampler": 0,
+                    "target": {{ "node": 0, "path": "translation" }}
+                }}],
+                "samplers": [{{ "input": 0, "output": 1 }}]
+            }}]
+        }}"#,
+        buffer
+    )
+}
+
+// Times 0 and 1
+const SORTED: &str = "AAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAQAAAQEA=";
+// Times 1 and 0
+const UNSORTED: &str = "AACAPwAAAAAAAAAAAAAAAAAAAAAAAIA/AAAAQAAAQEA=";
+// Times 0 and NaN
+const NAN: &str = "AAAAAAAAwH8AAAAAAAAAAAAAAAAAAIA/AAAAQAAAQEA=";
+
+#[test]
+fn animations_are_imported() {
+    let model = Importer::from_gltf_slice(animated(SORTED).as_bytes()).unwrap();
+    let keyframes = &model.get_animations()[0].get_channels()[0].keyframes;
+    assert_eq!(keyframes.get_times(), &[0., 1.]);
+}
+
+#[test]
+fn keyframes_out_of_order_are_refused() {
+    match Importer::from_gltf_slice(animated(UNSORTED).as_bytes()) {
+        Err(ImportError::Animation(0, KeyframesError::NotSorted(1))) => {}
+        other => panic!("Expected an animation error, got {:?}", other.err()),
+    }
+    match Importer::from_gltf_slice(animated(NAN).as_bytes()) {
+        Err(ImportError::Animation(0, KeyframesError::NotFinite(1))) => {}
+        other => panic!("Expected an animation error, got {:?}", other.err()),
+    }
+}
@@ -0,0 +1,27 @@
+extern crate fuel_importer;
+
+use fuel_importer::{ImportError, Importer};
+
+// Image in a view of `length` bytes, in a buffer of four
+fn image_in_view(length: usize) -> String {
+    format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "buffers": [{{
+                "byteLength": 4,
+                "uri": "data:application/octet-stream;base64,AAAAAA=="
+            }}],
+            "bufferViews": [{{ "buffer": 0, "byteLength": {} }}],
+            "images": [{{ "bufferView": 0, "mimeType": "image/png" }}]
+        }}"#,
+        length
+    )
+}
+
+#[test]
+fn images_outside_of_their_buffer_are_refused() {
+    match Importer::from_gltf_slice(image_in_view(100).as_bytes()) {
+        Err(ImportError::ImageOutsideBuffer(0)) => {}
+        other => panic!("Expected an image error, got {:?}", other.err()),
+    }
+}
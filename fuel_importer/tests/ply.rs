@@ -0,0 +1,124 @@
+extern crate fuel_importer;
+extern crate fuel_render;
+extern crate nalgebra as na;
+
+use fuel_importer::{ImportError, ImportOptions, PlyFormater};
+use fuel_render::backend::DrawMode;
+use fuel_render::{Attribute, Normals};
+use na::{Vector3, Vector4};
+
+// Counts of the header are checked against the body, not trusted
+#[test]
+fn huge_counts_with_a_short_body_are_refused() {
+    for element in &["vertex", "face"] {
+        let ply = format!(
+            "ply\nformat binary_little_endian 1.0\n\
+             element {} 1000000000000000\n\
+             property float x\nproperty float y\nproperty float z\n\
+             property list uchar int vertex_indices\n\
+             end_header\n",
+            element
+        );
+        let result =
+            PlyFormater::from_slice(ply.as_bytes(), ImportOptions::default());
+        match result {
+            Err(ImportError::Parse(_)) => {}
+            other => panic!("Expected a parse error, got {:?}", other.err()),
+        }
+    }
+}
+
+#[test]
+fn elements_without_properties_are_refused() {
+    let ply = "ply\nformat ascii 1.0\n\
+               element nothing 1000000000000000\n\
+               end_header\n";
+    let result =
+        PlyFormater::from_slice(ply.as_bytes(), ImportOptions::default());
+    match result {
+        Err(ImportError::Parse(_)) => {}
+        other => panic!("Expected a parse error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn ascii_faces_are_split_in_triangles() {
+    let ply = "ply\nformat ascii 1.0\ncomment a square\n\
+               element vertex 4\n\
+               property float x\nproperty float y\nproperty float z\n\
+               element face 1\n\
+               property list uchar int vertex_indices\n\
+               end_header\n\
+               0 0 0\n1 0 0\n1 1 0\n0 1 0\n\
+               4 0 1 2 3\n";
+    let options = ImportOptions {
+        normals: Normals::Smooth,
+        ..Default::default()
+    };
+    let ply = PlyFormater::from_slice(ply.as_bytes(), options).unwrap();
+    let primitive = &ply.meshes[0].get_primitives()[0];
+    assert_eq!(primitive.get_draw_mode(), DrawMode::Triangles);
+    assert_eq!(primitive.get_vertices().len(), 4);
+    assert_eq!(primitive.get_indices().unwrap().len(), 6);
+    for vertex in primitive.get_vertices() {
+        assert!((vertex.normal - Vector3::z()).norm() < 1e-5);
+    }
+}
+
+// Point of a binary little-endian file, with an sRGB color
+fn binary_point(position: [f32; 3], color: [u8; 3]) -> Vec<u8> {
+    let mut data = vec![];
+    for value in &position {
+        let bits = value.to_bits();
+        data.extend((0..4).map(|byte| (bits >> (byte * 8)) as u8));
+    }
+    data.extend(&color);
+    data
+}
+
+#[test]
+fn binary_point_clouds_keep_their_colors() {
+    let mut ply = b"ply\nformat binary_little_endian 1.0\n\
+                    element vertex 2\n\
+                    property float x\nproperty float y\nproperty float z\n\
+                    property uchar red\nproperty uchar green\n\
+                    property uchar blue\n\
+                    end_header\n"
+        .to_vec();
+    ply.extend(binary_point([1., 2., 3.], [255, 0, 188]));
+    ply.extend(binary_point([-1., -2., -3.5], [0, 255, 10]));
+
+    let ply = PlyFormater::from_slice(&ply, ImportOptions::default()).unwrap();
+    let primitive = &ply.meshes[0].get_primitives()[0];
+    // Without faces, the vertices are drawn as points
+    assert_eq!(primitive.get_draw_mode(), DrawMode::Points);
+    assert!(primitive.get_indices().is_none());
+    assert!(primitive.get_layout().has(Attribute::Color0));
+
+    let vertices = primitive.get_vertices();
+    assert_eq!(vertices.len(), 2);
+    assert_eq!(vertices[0].position, Vector3::new(1., 2., 3.));
+    assert_eq!(vertices[1].position, Vector3::new(-1., -2., -3.5));
+    // sRGB colors are converted to linear ones
+    let color = vertices[0].color_0;
+    assert!((color - Vector4::new(1., 0., 0.5, 1.)).norm() < 1e-2);
+    let color = vertices[1].color_0;
+    assert!((color.z - 10. / 255. / 12.92).abs() < 1e-6);
+}
+
+#[test]
+fn truncated_binary_rows_are_refused() {
+    let mut ply = b"ply\nformat binary_little_endian 1.0\n\
+                    element vertex 2\n\
+                    property float x\nproperty float y\nproperty float z\n\
+                    property uchar red\nproperty uchar green\n\
+                    property uchar blue\n\
+                    end_header\n"
+        .to_vec();
+    ply.extend(binary_point([1., 2., 3.], [255, 0, 188]));
+    ply.extend(&[0; 14]);
+    match PlyFormater::from_slice(&ply, ImportOptions::default()) {
+        Err(ImportError::Parse(_)) => {}
+        other => panic!("Expected a parse error, got {:?}", other.err()),
+    }
+}
@@ -0,0 +1,26 @@
+extern crate fuel_importer;
+
+use fuel_importer::Importer;
+
+// Nodes all used as joints of a single skin
+fn skinned(joints: usize) -> String {
+    let nodes = vec!["{}"; joints].join(",");
+    let indices = (0..joints)
+        .map(|index| index.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "nodes": [{}],
+            "skins": [{{ "joints": [{}] }}]
+        }}"#,
+        nodes, indices
+    )
+}
+
+#[test]
+fn skins_with_many_joints_are_imported() {
+    let model = Importer::from_gltf_slice(skinned(200).as_bytes()).unwrap();
+    assert_eq!(model.get_skins()[0].joints.len(), 200);
+}
@@ -0,0 +1,105 @@
+extern crate fuel_importer;
+extern crate nalgebra as na;
+
+use fuel_importer::{ImportError, StlFormater};
+use na::Vector3;
+
+fn push_vector(data: &mut Vec<u8>, vector: [f32; 3]) {
+    for value in &vector {
+        let bits = value.to_bits();
+        data.extend((0..4).map(|byte| (bits >> (byte * 8)) as u8));
+    }
+}
+
+// Binary file of triangles given by their normal then corners
+fn binary(header: &[u8], triangles: &[[[f32; 3]; 4]]) -> Vec<u8> {
+    let mut data = header.to_vec();
+    data.resize(80, 0);
+    let count = triangles.len() as u32;
+    data.extend((0..4).map(|byte| (count >> (byte * 8)) as u8));
+    for triangle in triangles {
+        for vector in triangle {
+            push_vector(&mut data, *vector);
+        }
+        // Attribute byte count
+        data.extend(&[0, 0]);
+    }
+    data
+}
+
+const TRIANGLE: [[f32; 3]; 4] =
+    [[0., 0., 0.], [0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+
+#[test]
+fn ascii_solids_are_meshes() {
+    let stl = "solid first part\n\
+               facet normal 0 0 2\n\
+               outer loop\n\
+               vertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\n\
+               endloop\n\
+               endfacet\n\
+               endsolid first part\n\
+               solid\n\
+               facet normal 0 0 0\n\
+               outer loop\n\
+               vertex 0 0 0\nvertex 0 1 0\nvertex 1 0 0\n\
+               endloop\n\
+               endfacet\n\
+               endsolid\n";
+    let stl = StlFormater::from_slice(stl.as_bytes()).unwrap();
+    assert_eq!(stl.meshes.len(), 2);
+    assert_eq!(stl.nodes[0].name, Some("first part".to_owned()));
+    assert_eq!(stl.nodes[1].name, None);
+
+    let first = stl.meshes[0].get_primitives()[0].get_vertices();
+    assert_eq!(first.len(), 3);
+    assert_eq!(first[1].position, Vector3::new(1., 0., 0.));
+    // Normals are normalized, or given by the triangle
+    assert_eq!(first[0].normal, Vector3::z());
+    let second = stl.meshes[1].get_primitives()[0].get_vertices();
+    assert_eq!(second[0].normal, -Vector3::z());
+}
+
+#[test]
+fn invalid_ascii_facets_are_refused() {
+    let stl = "solid\nfacet normal 0 0 1\nvertex 0 0 0\nendfacet\n";
+    match StlFormater::from_slice(stl.as_bytes()) {
+        Err(ImportError::Parse(_)) => {}
+        other => panic!("Expected a parse error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn binary_triangles_keep_their_normal() {
+    let mut triangle = TRIANGLE;
+    triangle[0] = [0., 0., -1.];
+    let stl = binary(b"exported", &[TRIANGLE, triangle]);
+    let stl = StlFormater::from_slice(&stl).unwrap();
+    assert_eq!(stl.meshes.len(), 1);
+
+    let vertices = stl.meshes[0].get_primitives()[0].get_vertices();
+    assert_eq!(vertices.len(), 6);
+    assert_eq!(vertices[2].position, Vector3::new(0., 1., 0.));
+    assert_eq!(vertices[0].normal, Vector3::z());
+    assert_eq!(vertices[3].normal, -Vector3::z());
+}
+
+#[test]
+fn binary_files_can_start_with_solid() {
+    let stl = binary(b"solid exported as binary", &[TRIANGLE]);
+    let stl = StlFormater::from_slice(&stl).unwrap();
+    let vertices = stl.meshes[0].get_primitives()[0].get_vertices();
+    assert_eq!(vertices.len(), 3);
+    assert_eq!(vertices[1].position, Vector3::new(1., 0., 0.));
+}
+
+#[test]
+fn truncated_binary_files_are_refused() {
+    let mut stl = binary(b"exported", &[TRIANGLE, TRIANGLE]);
+    let length = stl.len() - 10;
+    stl.truncate(length);
+    match StlFormater::from_slice(&stl) {
+        Err(ImportError::Parse(_)) => {}
+        other => panic!("Expected a parse error, got {:?}", other.err()),
+    }
+}
@@ -0,0 +1,762 @@
+use fuel_types::Transform;
+use na::{Quaternion, UnitQuaternion};
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
+
+/// How values are computed between two keyframes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    Linear,
+    /// The value of a keyframe is kept until the next one.
+    Step,
+    /// Hermite spline, each keyframe has an in and an out tangent.
+    CubicSpline,
+}
+
+/// Why keyframes were refused, by the index of the keyframe
+/// at fault.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyframesError {
+    /// Its time is infinite or NaN.
+    NotFinite(usize),
+    /// Its time is before the one of the previous keyframe.
+    NotSorted(usize),
+}
+
+impl fmt::Display for KeyframesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyframesError::NotFinite(index) => {
+                write!(f, "Time of keyframe {} isn't finite", index)
+            }
+            KeyframesError::NotSorted(index) => {
+                write!(f, "Keyframe {} is before the previous one", index)
+            }
+        }
+    }
+}
+
+impl error::Error for KeyframesError {}
+
+/// Values of a property over time, as in a glTF sampler.
+///
+/// Each keyframe has `components` values. With cubic splines,
+/// they come after the in tangent and before the out tangent of
+/// the keyframe, so a keyframe has three times as many values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframes {
+    pub interpolation: Interpolation,
+    times: Vec<f32>,
+    values: Vec<f32>,
+    components: usize,
+}
+
+impl Keyframes {
+    /// Times are in seconds, finite and sorted, as keyframes are
+    /// searched by time. Keyframes without all their values are
+    /// dropped.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        interpolation: Interpolation,
+        mut times: Vec<f32>,
+        values: Vec<f32>,
+        components: usize,
+    ) -> Result<Self, KeyframesError> {
+        let stride = match interpolation {
+            Interpolation::CubicSpline => components * 3,
+            _ => components,
+        };
+        let count = if stride > 0 { values.len() / stride } else { 0 };
+        times.truncate(count);
+
+        for (index, time) in times.iter().enumerate() {
+            if !time.is_finite() {
+                return Err(KeyframesError::NotFinite(index));
+            }
+            if index > 0 && *time < times[index - 1] {
+                return Err(KeyframesError::NotSorted(index));
+            }
+        }
+
+        Ok(Keyframes {
+            interpolation,
+            times,
+            values,
+            components,
+        })
+    }
+
+    pub fn get_times(&self) -> &[f32] {
+        &self.times
+    }
+
+    pub fn get_components(&self) -> usize {
+        self.components
+    }
+
+    /// Time of the last keyframe.
+    pub fn get_duration(&self) -> f32 {
+        self.times.last().cloned().unwrap_or(0.)
+    }
+
+    /// Values at `time`, each component is interpolated on its
+    /// own. Before the first keyframe and after the last one,
+    /// their values are kept. Empty without keyframes.
+    pub fn sample(&self, time: f32) -> Vec<f32> {
+        self.interpolate(time, |from, to, factor| {
+            from.iter()
+                .zip(to)
+                .map(|(from, to)| from + (to - from) * factor)
+                .collect()
+        })
+    }
+
+    /// Rotation at `time`, values being quaternions in the
+    /// (x, y, z, w) order of glTF. Linear keyframes follow the
+    /// shortest arc between two rotations.
+    pub fn sample_rotation(&self, time: f32) -> Option<UnitQuaternion<f32>> {
+        let value = self.interpolate(time, |from, to, factor| {
+            let from = to_quaternion(from);
+            let to = to_quaternion(to);
+            let to = if from.coords.dot(&to.coords) < 0. {
+                UnitQuaternion::new_unchecked(-to.unwrap())
+            } else {
+                to
+            };
+            let rotation = from
+                .try_slerp(&to, factor, 1e-6)
+                .unwrap_or_else(|| from.nlerp(&to, factor));
+            let coords = rotation.coords;
+            vec![coords.x, coords.y, coords.z, coords.w]
+        });
+
+        if value.len() == 4 {
+            Some(to_quaternion(&value))
+        } else {
+            None
+        }
+    }
+
+    fn value(&self, keyframe: usize) -> &[f32] {
+        self.part(keyframe, 1)
+    }
+
+    // Part 0 is the in tangent, 1 the value and 2 the out tangent
+    // of a cubic spline keyframe
+    fn part(&self, keyframe: usize, part: usize) -> &[f32] {
+        let components = self.components;
+        let start = match self.interpolation {
+            Interpolation::CubicSpline => (keyframe * 3 + part) * components,
+            _ => keyframe * components,
+        };
+        &self.values[start..start + components]
+    }
+
+    fn interpolate<F>(&self, time: f32, linear: F) -> Vec<f32>
+    where
+        F: Fn(&[f32], &[f32], f32) -> Vec<f32>,
+    {
+        let last = match self.times.len() {
+            0 => return vec![],
+            count => count - 1,
+        };
+        let next = match self.times.binary_search_by(|probe| {
+            probe.partial_cmp(&time).unwrap_or(Ordering::Less)
+        }) {
+            Ok(keyframe) => return self.value(keyframe).to_vec(),
+            Err(0) => return self.value(0).to_vec(),
+            Err(next) if next > last => return self.value(last).to_vec(),
+            Err(next) => next,
+        };
+        let previous = next - 1;
+        let delta = self.times[next] - self.times[previous];
+        let factor = if delta > 0. {
+            (time - self.times[previous]) / delta
+        } else {
+            0.
+        };
+
+        match self.interpolation {
+            Interpolation::Step => self.value(previous).to_vec(),
+            Interpolation::Linear => {
+                linear(self.value(previous), self.value(next), factor)
+            }
+            Interpolation::CubicSpline => {
+                let (t, t2, t3) = (factor, factor * factor, factor.powi(3));
+                let from = self.value(previous);
+                let out_tangent = self.part(previous, 2);
+                let in_tangent = self.part(next, 0);
+                let to = self.value(next);
+
+                (0..self.components)
+                    .map(|index| {
+                        (2. * t3 - 3. * t2 + 1.) * from[index]
+                            + (t3 - 2. * t2 + t) * delta * out_tangent[index]
+                            + (-2. * t3 + 3. * t2) * to[index]
+                            + (t3 - t2) * delta * in_tangent[index]
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+// Normalized, splines don't keep the length of quaternions
+fn to_quaternion(value: &[f32]) -> UnitQuaternion<f32> {
+    UnitQuaternion::from_quaternion(Quaternion::new(
+        value[3], value[0], value[1], value[2],
+    ))
+}
+
+/// Property of a node changed by a channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Property {
+    Translation,
+    Rotation,
+    Scale,
+    /// Weights of the morph targets of the mesh of the node.
+    Weights,
+}
+
+impl Property {
+    /// Values of each keyframe, weights have one for each
+    /// morph target.
+    pub fn components(self) -> Option<usize> {
+        match self {
+            Property::Translation | Property::Scale => Some(3),
+            Property::Rotation => Some(4),
+            Property::Weights => None,
+        }
+    }
+}
+
+/// Transforms and morph weights of the nodes of a Model, by
+/// index. Empty weights are the ones of the mesh of the node.
+#[derive(Clone, Debug, Default)]
+pub struct Pose {
+    pub transforms: Vec<Transform>,
+    pub weights: Vec<Vec<f32>>,
+}
+
+impl Pose {
+    /// Pose between this one, for a `factor` of 0, and `other`,
+    /// for 1. Weights of different targets are not mixed, the
+    /// closest ones are kept.
+    pub fn interpolate(&self, other: &Pose, factor: f32) -> Pose {
+        let transforms = self
+            .transforms
+            .iter()
+            .zip(&other.transforms)
+            .map(|(from, to)| from.interpolate(to, factor))
+            .collect();
+        let weights = self
+            .weights
+            .iter()
+            .zip(&other.weights)
+            .map(|(from, to)| {
+                if from.len() == to.len() {
+                    from.iter()
+                        .zip(to)
+                        .map(|(from, to)| from + (to - from) * factor)
+                        .collect()
+                } else if factor < 0.5 {
+                    from.clone()
+                } else {
+                    to.clone()
+                }
+            })
+            .collect();
+
+        Pose {
+            transforms,
+            weights,
+        }
+    }
+}
+
+/// Animates one property of a node, `node` is an index in the
+/// nodes of the Model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+    pub node: usize,
+    pub property: Property,
+    pub keyframes: Keyframes,
+}
+
+impl Channel {
+    /// Set the property of the node to its value at `time`.
+    pub fn apply(&self, time: f32, pose: &mut Pose) {
+        if self.property == Property::Weights {
+            let value = self.keyframes.sample(time);
+            if let Some(weights) = pose.weights.get_mut(self.node) {
+                if !value.is_empty() {
+                    *weights = value;
+                }
+            }
+            return;
+        }
+
+        let transform = match pose.transforms.get_mut(self.node) {
+            Some(transform) => transform,
+            None => return,
+        };
+        match self.property {
+            Property::Rotation => {
+                if let Some(rotation) = self.keyframes.sample_rotation(time) {
+                    transform.rotation.set(rotation);
+                }
+            }
+            _ => {
+                let value = self.keyframes.sample(time);
+                if value.len() != 3 {
+                    return;
+                }
+                let (x, y, z) = (value[0], value[1], value[2]);
+                match self.property {
+                    Property::Translation => transform.position.set(x, y, z),
+                    _ => transform.scale.set(x, y, z),
+                }
+            }
+        }
+    }
+}
+
+/// Channels played together, like the walk of a character.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    pub name: Option<String>,
+    channels: Vec<Channel>,
+    duration: f32,
+}
+
+impl Animation {
+    pub fn new(name: Option<String>, channels: Vec<Channel>) -> Self {
+        let duration = channels
+            .iter()
+            .map(|channel| channel.keyframes.get_duration())
+            .fold(0., f32::max);
+
+        Animation {
+            name,
+            channels,
+            duration,
+        }
+    }
+
+    pub fn get_channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    /// Time of the last keyframe of the channels.
+    pub fn get_duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Move the nodes of the pose to their place at `time`,
+    /// properties without channel are kept.
+    pub fn apply(&self, time: f32, pose: &mut Pose) {
+        for channel in &self.channels {
+            channel.apply(time, pose);
+        }
+    }
+}
+
+/// Second animation mixed with the one played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Blend {
+    pub animation: usize,
+    pub time: f32,
+    /// From 0, only the animation played, to 1, only this one.
+    pub weight: f32,
+    /// Weight added each second, to fade between both.
+    pub fade_speed: f32,
+}
+
+/// Plays the animations of a Model, one at a time or two
+/// blended together.
+///
+/// Time is in seconds from the start of the animation, it goes
+/// backward with a negative speed. Blended animations share
+/// the speed and the looping of the player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationPlayer {
+    pub animation: Option<usize>,
+    pub time: f32,
+    pub speed: f32,
+    pub playing: bool,
+    pub looping: bool,
+    pub blend: Option<Blend>,
+}
+
+impl Default for AnimationPlayer {
+    fn default() -> Self {
+        AnimationPlayer {
+            animation: None,
+            time: 0.,
+            speed: 1.,
+            playing: false,
+            looping: true,
+            blend: None,
+        }
+    }
+}
+
+impl AnimationPlayer {
+    /// Play an animation alone, from its start.
+    pub fn play(&mut self, animation: usize) {
+        self.animation = Some(animation);
+        self.time = 0.;
+        self.playing = true;
+        self.blend = None;
+    }
+
+    /// Mix another animation, from its start, with the one
+    /// played.
+    pub fn blend(&mut self, animation: usize, weight: f32) {
+        self.blend = Some(Blend {
+            animation,
+            time: 0.,
+            weight: clamp_weight(weight),
+            fade_speed: 0.,
+        });
+    }
+
+    /// Fade from the animation played to another one in
+    /// `duration` seconds, the other one is then played alone.
+    pub fn cross_fade(&mut self, animation: usize, duration: f32) {
+        if self.animation.is_none() || duration <= 0. {
+            self.play(animation);
+            return;
+        }
+
+        self.blend = Some(Blend {
+            animation,
+            time: 0.,
+            weight: 0.,
+            fade_speed: 1. / duration,
+        });
+        self.playing = true;
+    }
+
+    pub fn set_blend_weight(&mut self, weight: f32) {
+        if let Some(blend) = &mut self.blend {
+            blend.weight = clamp_weight(weight);
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        self.playing = self.animation.is_some();
+    }
+
+    /// Stop the animations, their pose is kept.
+    pub fn stop(&mut self) {
+        self.animation = None;
+        self.time = 0.;
+        self.playing = false;
+        self.blend = None;
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Move the time forward by `dt` seconds. Looping animations
+    /// wrap around, the others pause at their end. Indices of
+    /// the player are in `animations`.
+    pub fn advance(&mut self, dt: f32, animations: &[Animation]) {
+        if !self.playing {
+            return;
+        }
+        let duration = |animation: usize| {
+            animations
+                .get(animation)
+                .map_or(0., |animation| animation.get_duration())
+        };
+
+        let mut finished = true;
+        if let Some(animation) = self.animation {
+            let (time, ended) = self.step(self.time, dt, duration(animation));
+            self.time = time;
+            finished = ended;
+        }
+        if let Some(mut blend) = self.blend {
+            let (time, ended) =
+                self.step(blend.time, dt, duration(blend.animation));
+            blend.time = time;
+            blend.weight = clamp_weight(blend.weight + blend.fade_speed * dt);
+
+            if blend.fade_speed > 0. && blend.weight >= 1. {
+                // The fade is over, only the new animation is left
+                self.animation = Some(blend.animation);
+                self.time = blend.time;
+                self.blend = None;
+                finished = ended;
+            } else {
+                self.blend = Some(blend);
+                finished = finished && ended;
+            }
+        }
+
+        if finished {
+            self.playing = false;
+        }
+    }
+
+    // New time of an animation, and whether it ended
+    fn step(&self, time: f32, dt: f32, duration: f32) -> (f32, bool) {
+        let time = time + dt * self.speed;
+        if duration <= 0. {
+            (0., !self.looping)
+        } else if self.looping {
+            let time = time % duration;
+            (if time < 0. { time + duration } else { time }, false)
+        } else {
+            let ended = if self.speed < 0. {
+                time <= 0.
+            } else {
+                time >= duration
+            };
+            (time.max(0.).min(duration), ended)
+        }
+    }
+
+    /// Nodes posed by the animations at their current time.
+    /// `rest` is the pose of the nodes before any animation, it
+    /// is kept by the properties without channel.
+    pub fn get_pose(&self, animations: &[Animation], rest: &Pose) -> Pose {
+        let pose = |animation: Option<usize>, time: f32| {
+            let mut pose = rest.clone();
+            if let Some(animation) = animation.and_then(|a| animations.get(a)) {
+                animation.apply(time, &mut pose);
+            }
+            pose
+        };
+
+        let played = pose(self.animation, self.time);
+        match self.blend {
+            Some(blend) => played.interpolate(
+                &pose(Some(blend.animation), blend.time),
+                blend.weight,
+            ),
+            None => played,
+        }
+    }
+}
+
+fn clamp_weight(weight: f32) -> f32 {
+    weight.max(0.).min(1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use na::Vector3;
+    use std::f32;
+    use std::f32::consts::PI;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-5, "{:?}", actual);
+        }
+    }
+
+    // Clip keeping a node at (x, 0, 0) for a second
+    fn translation(x: f32) -> Animation {
+        let keyframes = Keyframes::new(
+            Interpolation::Linear,
+            vec![0., 1.],
+            vec![x, 0., 0., x, 0., 0.],
+            3,
+        )
+        .unwrap();
+        let channel = Channel {
+            node: 0,
+            property: Property::Translation,
+            keyframes,
+        };
+        Animation::new(None, vec![channel])
+    }
+
+    fn rest() -> Pose {
+        Pose {
+            transforms: vec![Transform::default()],
+            weights: vec![vec![]],
+        }
+    }
+
+    fn position(player: &AnimationPlayer, animations: &[Animation]) -> f32 {
+        player.get_pose(animations, &rest()).transforms[0]
+            .position
+            .get()
+            .x
+    }
+
+    #[test]
+    fn incomplete_keyframes_are_dropped() {
+        let keyframes = Keyframes::new(
+            Interpolation::Linear,
+            vec![0., 1., 2.],
+            vec![0.; 5],
+            2,
+        )
+        .unwrap();
+        assert_eq!(keyframes.get_times(), &[0., 1.]);
+        assert_close(&[keyframes.get_duration()], &[1.]);
+    }
+
+    #[test]
+    fn times_are_finite_and_sorted() {
+        let keyframes = |times: Vec<f32>| {
+            let values = vec![0.; times.len()];
+            Keyframes::new(Interpolation::Linear, times, values, 1)
+        };
+        assert!(keyframes(vec![0., 1., 1., 2.]).is_ok());
+        assert_eq!(
+            keyframes(vec![0., 2., 1.]),
+            Err(KeyframesError::NotSorted(2))
+        );
+        assert_eq!(
+            keyframes(vec![0., f32::NAN]),
+            Err(KeyframesError::NotFinite(1))
+        );
+        assert_eq!(
+            keyframes(vec![f32::NEG_INFINITY, 0.]),
+            Err(KeyframesError::NotFinite(0))
+        );
+        // Dropped keyframes aren't checked
+        let keyframes =
+            Keyframes::new(Interpolation::Step, vec![0., -1.], vec![0.], 1);
+        assert_eq!(keyframes.unwrap().get_times(), &[0.]);
+    }
+
+    #[test]
+    fn linear() {
+        let keyframes = Keyframes::new(
+            Interpolation::Linear,
+            vec![0., 1., 3.],
+            vec![0., 10., 30.],
+            1,
+        )
+        .unwrap();
+        assert_close(&keyframes.sample(0.5), &[5.]);
+        assert_close(&keyframes.sample(1.), &[10.]);
+        assert_close(&keyframes.sample(2.), &[20.]);
+        // Values are kept outside of the keyframes
+        assert_close(&keyframes.sample(-1.), &[0.]);
+        assert_close(&keyframes.sample(5.), &[30.]);
+        assert!(Keyframes::new(Interpolation::Linear, vec![], vec![], 1)
+            .unwrap()
+            .sample(0.)
+            .is_empty());
+    }
+
+    #[test]
+    fn step() {
+        let keyframes =
+            Keyframes::new(Interpolation::Step, vec![0., 1.], vec![0., 10.], 1)
+                .unwrap();
+        assert_close(&keyframes.sample(0.99), &[0.]);
+        assert_close(&keyframes.sample(1.), &[10.]);
+    }
+
+    #[test]
+    fn cubic_spline() {
+        // In tangent, value and out tangent of each keyframe
+        let keyframes = Keyframes::new(
+            Interpolation::CubicSpline,
+            vec![0., 2.],
+            vec![0., 0., 1., 0., 1., 0.],
+            1,
+        )
+        .unwrap();
+        assert_close(&keyframes.sample(0.), &[0.]);
+        // Tangents are scaled by the time between the keyframes
+        assert_close(&keyframes.sample(1.), &[0.75]);
+        assert_close(&keyframes.sample(2.), &[1.]);
+
+        let flat = Keyframes::new(
+            Interpolation::CubicSpline,
+            vec![0., 1.],
+            vec![0., 0., 0., 0., 1., 0.],
+            1,
+        )
+        .unwrap();
+        assert_close(&flat.sample(0.25), &[0.15625]);
+        assert_close(&flat.sample(0.5), &[0.5]);
+    }
+
+    #[test]
+    fn rotations_follow_the_shortest_arc() {
+        let quarter =
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), PI / 2.);
+        // Same rotation as `quarter`, on the other side of the sphere
+        let opposite = -quarter.coords;
+        let keyframes = Keyframes::new(
+            Interpolation::Linear,
+            vec![0., 1.],
+            vec![
+                0., 0., 0., 1., opposite.x, opposite.y, opposite.z, opposite.w,
+            ],
+            4,
+        )
+        .unwrap();
+
+        let halfway = keyframes.sample_rotation(0.5).unwrap();
+        let expected =
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), PI / 4.);
+        assert!(halfway.angle_to(&expected) < 1e-4, "{:?}", halfway);
+        let end = keyframes.sample_rotation(1.).unwrap();
+        assert!(end.angle_to(&quarter) < 1e-4);
+    }
+
+    #[test]
+    fn looping() {
+        let animations = [translation(0.)];
+        let mut player = AnimationPlayer::default();
+        player.play(0);
+        player.advance(2.5, &animations);
+        assert_close(&[player.time], &[0.5]);
+        assert!(player.playing);
+
+        player.set_speed(-1.);
+        player.advance(1., &animations);
+        assert_close(&[player.time], &[0.5]);
+
+        player.set_speed(1.);
+        player.set_looping(false);
+        player.advance(2., &animations);
+        assert_close(&[player.time], &[1.]);
+        assert!(!player.playing);
+    }
+
+    #[test]
+    fn blending() {
+        let animations = [translation(0.), translation(10.)];
+        let mut player = AnimationPlayer::default();
+        player.play(0);
+        player.blend(1, 0.25);
+        assert_close(&[position(&player, &animations)], &[2.5]);
+        player.set_blend_weight(2.);
+        assert_close(&[position(&player, &animations)], &[10.]);
+
+        player.play(0);
+        player.cross_fade(1, 1.);
+        player.advance(0.5, &animations);
+        assert_close(&[position(&player, &animations)], &[5.]);
+        // Only the new animation is left once the fade is over
+        player.advance(0.5, &animations);
+        assert_eq!(player.animation, Some(1));
+        assert_eq!(player.blend, None);
+        assert_close(&[position(&player, &animations)], &[10.]);
+    }
+}
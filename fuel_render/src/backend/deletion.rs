@@ -0,0 +1,102 @@
+use super::{ProgramId, TextureId};
+use fuel_types::{VAO, VBO};
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+/// Resource of the graphic card, with the id given by the
+/// backend that created it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resource {
+    VertexArray(VAO),
+    /// Vertex or index buffer.
+    Buffer(VBO),
+    Program(ProgramId),
+    Texture(TextureId),
+}
+
+/// Resources created by a backend and not deleted yet.
+/// They are only counted in debug builds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LiveResources {
+    pub vertex_arrays: usize,
+    pub buffers: usize,
+    pub programs: usize,
+    pub textures: usize,
+}
+
+impl LiveResources {
+    pub fn total(&self) -> usize {
+        self.vertex_arrays + self.buffers + self.programs + self.textures
+    }
+
+    fn get_mut(&mut self, resource: Resource) -> &mut usize {
+        match resource {
+            Resource::VertexArray(_) => &mut self.vertex_arrays,
+            Resource::Buffer(_) => &mut self.buffers,
+            Resource::Program(_) => &mut self.programs,
+            Resource::Texture(_) => &mut self.textures,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    pending: Vec<Resource>,
+    live: LiveResources,
+}
+
+/// Resources dropped by their owner, waiting for the backend that
+/// created them. Owners don't have any backend when they are
+/// dropped, and its context may not be current, so the deletion
+/// happens with the next `RenderBackend::collect_garbage`.
+///
+/// Clones share the same queue.
+#[derive(Clone, Debug, Default)]
+pub struct DeletionQueue {
+    queue: Rc<RefCell<Queue>>,
+}
+
+impl DeletionQueue {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn push(&self, resource: Resource) {
+        self.queue.borrow_mut().pending.push(resource);
+    }
+
+    /// Return the pending resources and empty the queue.
+    pub fn take(&self) -> Vec<Resource> {
+        mem::replace(&mut self.queue.borrow_mut().pending, vec![])
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.borrow().pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.borrow().pending.is_empty()
+    }
+
+    /// Called by the backend for each resource it creates.
+    pub fn created(&self, resource: Resource) {
+        if cfg!(debug_assertions) {
+            *self.queue.borrow_mut().live.get_mut(resource) += 1;
+        }
+    }
+
+    /// Called by the backend for each resource it deletes.
+    pub fn deleted(&self, resource: Resource) {
+        if cfg!(debug_assertions) {
+            let mut queue = self.queue.borrow_mut();
+            let count = queue.live.get_mut(resource);
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// Always empty in release builds.
+    pub fn live(&self) -> LiveResources {
+        self.queue.borrow().live
+    }
+}
@@ -0,0 +1,373 @@
+use super::{
+    DeletionQueue, DrawMode, PolygonMode, ProgramId, RenderBackend, Resource,
+    Sampler, ShaderError, TextureId, Uniform, VertexAttribute,
+};
+use fuel_types::{EBO, VAO, VBO};
+use na::Matrix4;
+use std::collections::HashMap;
+
+/// A command received by the `HeadlessBackend`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Clear([f32; 4]),
+    SetViewport {
+        width: u32,
+        height: u32,
+    },
+    SetPolygonMode(PolygonMode),
+    SetDepthTest(bool),
+    SetFaceCulling(bool),
+    SetBlending(bool),
+    CreateVertexArray(VAO),
+    CreateVertexBuffer {
+        vao: VAO,
+        vbo: VBO,
+        data: Vec<f32>,
+    },
+    UpdateVertexBuffer {
+        vbo: VBO,
+        data: Vec<f32>,
+    },
+    CreateIndexBuffer {
+        vao: VAO,
+        ebo: EBO,
+        data: Vec<u32>,
+    },
+    SetVertexAttribute {
+        vao: VAO,
+        vbo: VBO,
+        attribute: VertexAttribute,
+    },
+    CreateProgram {
+        program: ProgramId,
+        vertex: String,
+        fragment: String,
+    },
+    UseProgram(ProgramId),
+    SetUniform {
+        program: ProgramId,
+        name: String,
+        value: Uniform,
+    },
+    CreateTexture {
+        texture: TextureId,
+        width: u32,
+        height: u32,
+        sampler: Sampler,
+    },
+    CreateDataTexture {
+        texture: TextureId,
+        width: u32,
+        height: u32,
+        data: Vec<f32>,
+    },
+    UpdateDataTexture {
+        texture: TextureId,
+        width: u32,
+        height: u32,
+        data: Vec<f32>,
+    },
+    BindTexture {
+        unit: u32,
+        texture: TextureId,
+    },
+    DrawArrays {
+        vao: VAO,
+        mode: DrawMode,
+        count: u32,
+    },
+    DrawElements {
+        vao: VAO,
+        mode: DrawMode,
+        count: u32,
+    },
+    DeleteVertexArray(VAO),
+    DeleteBuffer(VBO),
+    DeleteProgram(ProgramId),
+    DeleteTexture(TextureId),
+}
+
+/// A draw command with the state it was issued with.
+#[derive(Clone, Debug)]
+pub struct DrawCall {
+    pub command: Command,
+    pub program: Option<ProgramId>,
+    pub uniforms: HashMap<String, Uniform>,
+}
+
+impl DrawCall {
+    pub fn is_indexed(&self) -> bool {
+        match self.command {
+            Command::DrawElements { .. } => true,
+            _ => false,
+        }
+    }
+
+    pub fn uniform(&self, name: &str) -> Option<&Uniform> {
+        self.uniforms.get(name)
+    }
+
+    pub fn mvp(&self) -> Option<Matrix4<f32>> {
+        match self.uniform("mvp") {
+            Some(Uniform::Mat4(mvp)) => Some(*mvp),
+            _ => None,
+        }
+    }
+}
+
+/// Backend without any graphic context. Each command is
+/// recorded into a log that can be inspected afterward,
+/// which lets us run the render path on CI.
+#[derive(Debug, Default)]
+pub struct HeadlessBackend {
+    commands: Vec<Command>,
+    next_id: u32,
+    deletion_queue: DeletionQueue,
+}
+
+impl HeadlessBackend {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Return the log and start a new one.
+    pub fn take_commands(&mut self) -> Vec<Command> {
+        self.commands.drain(..).collect()
+    }
+
+    /// Replay the log and return every draw call with the program
+    /// and the uniforms that were bound when it was issued.
+    pub fn draw_calls(&self) -> Vec<DrawCall> {
+        let mut program = None;
+        let mut uniforms: HashMap<ProgramId, HashMap<String, Uniform>> =
+            HashMap::new();
+        let mut draw_calls = vec![];
+
+        for command in &self.commands {
+            match command {
+                Command::UseProgram(id) => program = Some(*id),
+                Command::SetUniform {
+                    program: id,
+                    name,
+                    value,
+                } => {
+                    uniforms
+                        .entry(*id)
+                        .or_insert_with(HashMap::new)
+                        .insert(name.clone(), value.clone());
+                }
+                Command::DrawArrays { .. } | Command::DrawElements { .. } => {
+                    draw_calls.push(DrawCall {
+                        command: command.clone(),
+                        program,
+                        uniforms: program
+                            .and_then(|id| uniforms.get(&id).cloned())
+                            .unwrap_or_default(),
+                    })
+                }
+                _ => (),
+            }
+        }
+
+        draw_calls
+    }
+
+    fn gen_id(&mut self) -> u32 {
+        // Zero is never a valid object name for OpenGL
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+impl RenderBackend for HeadlessBackend {
+    fn clear(&mut self, color: [f32; 4]) {
+        self.commands.push(Command::Clear(color));
+    }
+
+    fn set_viewport(&mut self, width: u32, height: u32) {
+        self.commands.push(Command::SetViewport { width, height });
+    }
+
+    fn set_polygon_mode(&mut self, mode: PolygonMode) {
+        self.commands.push(Command::SetPolygonMode(mode));
+    }
+
+    fn set_depth_test(&mut self, enabled: bool) {
+        self.commands.push(Command::SetDepthTest(enabled));
+    }
+
+    fn set_face_culling(&mut self, enabled: bool) {
+        self.commands.push(Command::SetFaceCulling(enabled));
+    }
+
+    fn set_blending(&mut self, enabled: bool) {
+        self.commands.push(Command::SetBlending(enabled));
+    }
+
+    fn create_vertex_array(&mut self) -> VAO {
+        let vao = self.gen_id();
+        self.commands.push(Command::CreateVertexArray(vao));
+        self.deletion_queue.created(Resource::VertexArray(vao));
+        vao
+    }
+
+    fn create_vertex_buffer(&mut self, vao: VAO, data: &[f32]) -> VBO {
+        let vbo = self.gen_id();
+        self.commands.push(Command::CreateVertexBuffer {
+            vao,
+            vbo,
+            data: data.to_vec(),
+        });
+        self.deletion_queue.created(Resource::Buffer(vbo));
+        vbo
+    }
+
+    fn update_vertex_buffer(&mut self, vbo: VBO, data: &[f32]) {
+        self.commands.push(Command::UpdateVertexBuffer {
+            vbo,
+            data: data.to_vec(),
+        });
+    }
+
+    fn create_index_buffer(&mut self, vao: VAO, data: &[u32]) -> EBO {
+        let ebo = self.gen_id();
+        self.commands.push(Command::CreateIndexBuffer {
+            vao,
+            ebo,
+            data: data.to_vec(),
+        });
+        self.deletion_queue.created(Resource::Buffer(ebo));
+        ebo
+    }
+
+    fn set_vertex_attribute(
+        &mut self,
+        vao: VAO,
+        vbo: VBO,
+        attribute: VertexAttribute,
+    ) {
+        self.commands.push(Command::SetVertexAttribute {
+            vao,
+            vbo,
+            attribute,
+        });
+    }
+
+    fn create_program(
+        &mut self,
+        vertex: &str,
+        fragment: &str,
+    ) -> Result<ProgramId, ShaderError> {
+        let program = self.gen_id();
+        self.commands.push(Command::CreateProgram {
+            program,
+            vertex: vertex.to_owned(),
+            fragment: fragment.to_owned(),
+        });
+        self.deletion_queue.created(Resource::Program(program));
+        Ok(program)
+    }
+
+    fn use_program(&mut self, program: ProgramId) {
+        self.commands.push(Command::UseProgram(program));
+    }
+
+    fn set_uniform(&mut self, program: ProgramId, name: &str, value: &Uniform) {
+        self.commands.push(Command::SetUniform {
+            program,
+            name: name.to_owned(),
+            value: value.clone(),
+        });
+    }
+
+    fn create_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        _pixels: &[u8],
+        sampler: &Sampler,
+    ) -> TextureId {
+        let texture = self.gen_id();
+        self.commands.push(Command::CreateTexture {
+            texture,
+            width,
+            height,
+            sampler: *sampler,
+        });
+        self.deletion_queue.created(Resource::Texture(texture));
+        texture
+    }
+
+    fn create_data_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        data: &[f32],
+    ) -> TextureId {
+        let texture = self.gen_id();
+        self.commands.push(Command::CreateDataTexture {
+            texture,
+            width,
+            height,
+            data: data.to_vec(),
+        });
+        self.deletion_queue.created(Resource::Texture(texture));
+        texture
+    }
+
+    fn update_data_texture(
+        &mut self,
+        texture: TextureId,
+        width: u32,
+        height: u32,
+        data: &[f32],
+    ) {
+        self.commands.push(Command::UpdateDataTexture {
+            texture,
+            width,
+            height,
+            data: data.to_vec(),
+        });
+    }
+
+    fn bind_texture(&mut self, unit: u32, texture: TextureId) {
+        self.commands.push(Command::BindTexture { unit, texture });
+    }
+
+    fn draw_arrays(&mut self, vao: VAO, mode: DrawMode, count: u32) {
+        self.commands.push(Command::DrawArrays { vao, mode, count });
+    }
+
+    fn draw_elements(&mut self, vao: VAO, mode: DrawMode, count: u32) {
+        self.commands
+            .push(Command::DrawElements { vao, mode, count });
+    }
+
+    fn delete_vertex_array(&mut self, vao: VAO) {
+        self.commands.push(Command::DeleteVertexArray(vao));
+        self.deletion_queue.deleted(Resource::VertexArray(vao));
+    }
+
+    fn delete_buffer(&mut self, buffer: VBO) {
+        self.commands.push(Command::DeleteBuffer(buffer));
+        self.deletion_queue.deleted(Resource::Buffer(buffer));
+    }
+
+    fn delete_program(&mut self, program: ProgramId) {
+        self.commands.push(Command::DeleteProgram(program));
+        self.deletion_queue.deleted(Resource::Program(program));
+    }
+
+    fn delete_texture(&mut self, texture: TextureId) {
+        self.commands.push(Command::DeleteTexture(texture));
+        self.deletion_queue.deleted(Resource::Texture(texture));
+    }
+
+    fn get_deletion_queue(&self) -> DeletionQueue {
+        self.deletion_queue.clone()
+    }
+}
@@ -0,0 +1,194 @@
+use fuel_types::{EBO, VAO, VBO};
+use na::{Matrix4, Vector3, Vector4};
+
+mod deletion;
+mod headless;
+mod opengl;
+mod software;
+
+pub use self::deletion::{DeletionQueue, LiveResources, Resource};
+pub use self::headless::{Command, DrawCall, HeadlessBackend};
+pub use self::opengl::OpenGlBackend;
+pub use self::software::SoftwareBackend;
+pub use shader::ShaderError;
+pub use vertex::VertexAttribute;
+
+pub type ProgramId = u32;
+pub type TextureId = u32;
+
+/// How polygons are rasterized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolygonMode {
+    Fill,
+    Line,
+    Point,
+}
+
+impl Default for PolygonMode {
+    fn default() -> Self {
+        PolygonMode::Fill
+    }
+}
+
+/// Topology of the vertices sent by a draw call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawMode {
+    Points,
+    Lines,
+    LineLoop,
+    LineStrip,
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
+
+/// How texture coordinates outside of [0, 1] are handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+}
+
+impl Default for Wrap {
+    fn default() -> Self {
+        Wrap::Repeat
+    }
+}
+
+/// How texels are filtered when a texture is sampled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+/// Filters and wrap modes of a texture. Mipmaps are generated
+/// only when there is a mipmap filter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sampler {
+    pub mag_filter: Filter,
+    pub min_filter: Filter,
+    pub mipmap_filter: Option<Filter>,
+    pub wrap_s: Wrap,
+    pub wrap_t: Wrap,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap_filter: Some(Filter::Linear),
+            wrap_s: Wrap::Repeat,
+            wrap_t: Wrap::Repeat,
+        }
+    }
+}
+
+/// Value given to a shader uniform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Uniform {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    Vec3(Vector3<f32>),
+    Vec4(Vector4<f32>),
+    Mat4(Matrix4<f32>),
+}
+
+/// Every command the engine sends to the graphic card goes
+/// through a backend. Resources are identified by the ids
+/// returned by the backend that created them.
+///
+/// Resources dropped by their owner are deleted by the next
+/// `collect_garbage`, `Scene::render` calls it every frame.
+pub trait RenderBackend {
+    fn clear(&mut self, color: [f32; 4]);
+    /// Area of the frame buffer drawn into, in pixels.
+    fn set_viewport(&mut self, width: u32, height: u32);
+    fn set_polygon_mode(&mut self, mode: PolygonMode);
+    fn set_depth_test(&mut self, enabled: bool);
+    /// Back faces are culled, front faces are counter-clockwise.
+    fn set_face_culling(&mut self, enabled: bool);
+    /// Blend with the source alpha, `src * a + dst * (1 - a)`.
+    fn set_blending(&mut self, enabled: bool);
+
+    fn create_vertex_array(&mut self) -> VAO;
+    fn create_vertex_buffer(&mut self, vao: VAO, data: &[f32]) -> VBO;
+    fn create_index_buffer(&mut self, vao: VAO, data: &[u32]) -> EBO;
+    /// Replace the data of a vertex buffer, for vertices
+    /// changed on the CPU.
+    fn update_vertex_buffer(&mut self, vbo: VBO, data: &[f32]);
+    fn set_vertex_attribute(
+        &mut self,
+        vao: VAO,
+        vbo: VBO,
+        attribute: VertexAttribute,
+    );
+
+    fn create_program(
+        &mut self,
+        vertex: &str,
+        fragment: &str,
+    ) -> Result<ProgramId, ShaderError>;
+    fn use_program(&mut self, program: ProgramId);
+    fn set_uniform(&mut self, program: ProgramId, name: &str, value: &Uniform);
+
+    /// Pixels are tightly packed RGBA, 8 bits per channel.
+    fn create_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        sampler: &Sampler,
+    ) -> TextureId;
+    /// Texture of floats read by shaders with `texelFetch`, each
+    /// texel has four of them. It is neither filtered nor wrapped.
+    fn create_data_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        data: &[f32],
+    ) -> TextureId;
+    /// Replace the data of a data texture, its size can change.
+    fn update_data_texture(
+        &mut self,
+        texture: TextureId,
+        width: u32,
+        height: u32,
+        data: &[f32],
+    );
+    fn bind_texture(&mut self, unit: u32, texture: TextureId);
+
+    fn draw_arrays(&mut self, vao: VAO, mode: DrawMode, count: u32);
+    fn draw_elements(&mut self, vao: VAO, mode: DrawMode, count: u32);
+
+    fn delete_vertex_array(&mut self, vao: VAO);
+    /// Vertex or index buffer.
+    fn delete_buffer(&mut self, buffer: VBO);
+    fn delete_program(&mut self, program: ProgramId);
+    fn delete_texture(&mut self, texture: TextureId);
+
+    /// Queue shared with the owners of the resources created
+    /// by this backend.
+    fn get_deletion_queue(&self) -> DeletionQueue;
+
+    /// Delete the resources dropped since the last call, the
+    /// context of the backend has to be current.
+    fn collect_garbage(&mut self) {
+        for resource in self.get_deletion_queue().take() {
+            match resource {
+                Resource::VertexArray(vao) => self.delete_vertex_array(vao),
+                Resource::Buffer(buffer) => self.delete_buffer(buffer),
+                Resource::Program(program) => self.delete_program(program),
+                Resource::Texture(texture) => self.delete_texture(texture),
+            }
+        }
+    }
+
+    /// Resources created and not deleted yet, debug builds only.
+    fn live_resources(&self) -> LiveResources {
+        self.get_deletion_queue().live()
+    }
+}
@@ -0,0 +1,448 @@
+use super::{
+    DeletionQueue, DrawMode, Filter, PolygonMode, ProgramId, RenderBackend,
+    Resource, Sampler, ShaderError, TextureId, Uniform, VertexAttribute, Wrap,
+};
+use fuel_types::{EBO, VAO, VBO};
+use gl;
+use gl::types::*;
+use shader::ShaderStage;
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+use vertex::AttributeType;
+
+/// Backend talking to the OpenGL context of the current window.
+/// OpenGL functions have to be loaded before using it.
+#[derive(Debug, Default)]
+pub struct OpenGlBackend {
+    deletion_queue: DeletionQueue,
+}
+
+impl OpenGlBackend {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+fn gl_draw_mode(mode: DrawMode) -> GLenum {
+    match mode {
+        DrawMode::Points => gl::POINTS,
+        DrawMode::Lines => gl::LINES,
+        DrawMode::LineLoop => gl::LINE_LOOP,
+        DrawMode::LineStrip => gl::LINE_STRIP,
+        DrawMode::Triangles => gl::TRIANGLES,
+        DrawMode::TriangleStrip => gl::TRIANGLE_STRIP,
+        DrawMode::TriangleFan => gl::TRIANGLE_FAN,
+    }
+}
+
+fn gl_wrap(wrap: Wrap) -> GLenum {
+    match wrap {
+        Wrap::Repeat => gl::REPEAT,
+        Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+        Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+    }
+}
+
+fn gl_min_filter(filter: Filter, mipmap_filter: Option<Filter>) -> GLenum {
+    match (filter, mipmap_filter) {
+        (Filter::Nearest, None) => gl::NEAREST,
+        (Filter::Linear, None) => gl::LINEAR,
+        (Filter::Nearest, Some(Filter::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+        (Filter::Linear, Some(Filter::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+        (Filter::Nearest, Some(Filter::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+        (Filter::Linear, Some(Filter::Linear)) => gl::LINEAR_MIPMAP_LINEAR,
+    }
+}
+
+fn set_capability(capability: GLenum, enabled: bool) {
+    unsafe {
+        if enabled {
+            gl::Enable(capability);
+        } else {
+            gl::Disable(capability);
+        }
+    }
+}
+
+// Info log of a shader or a program, `get_iv` and `get_info_log`
+// are the GL functions matching the kind of object.
+unsafe fn info_log(
+    object: GLuint,
+    get_iv: unsafe fn(GLuint, GLenum, *mut GLint),
+    get_info_log: unsafe fn(GLuint, GLsizei, *mut GLsizei, *mut GLchar),
+) -> String {
+    let mut length = 0;
+    get_iv(object, gl::INFO_LOG_LENGTH, &mut length);
+    let mut info_log = vec![0u8; length.max(1) as usize];
+    let mut written = 0;
+    get_info_log(
+        object,
+        info_log.len() as i32,
+        &mut written,
+        info_log.as_mut_ptr() as *mut GLchar,
+    );
+    info_log.truncate(written.max(0) as usize);
+
+    String::from_utf8_lossy(&info_log).into_owned()
+}
+
+unsafe fn compile_shader(
+    stage: ShaderStage,
+    source: &str,
+) -> Result<GLuint, ShaderError> {
+    // convert to C compatible string
+    let source =
+        CString::new(source.as_bytes()).map_err(|_| ShaderError::Compile {
+            stage,
+            log: "Shader source contains a nul byte.".to_owned(),
+        })?;
+    let shader = gl::CreateShader(match stage {
+        ShaderStage::Vertex => gl::VERTEX_SHADER,
+        ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+    });
+
+    gl::ShaderSource(shader, 1, &source.as_ptr(), ptr::null());
+    gl::CompileShader(shader);
+
+    let mut success = i32::from(gl::FALSE);
+    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+    if success != i32::from(gl::TRUE) {
+        let log = info_log(shader, gl::GetShaderiv, gl::GetShaderInfoLog);
+        gl::DeleteShader(shader);
+        return Err(ShaderError::Compile { stage, log });
+    }
+
+    Ok(shader)
+}
+
+impl RenderBackend for OpenGlBackend {
+    fn clear(&mut self, color: [f32; 4]) {
+        unsafe {
+            gl::ClearColor(color[0], color[1], color[2], color[3]);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    fn set_viewport(&mut self, width: u32, height: u32) {
+        unsafe { gl::Viewport(0, 0, width as i32, height as i32) }
+    }
+
+    fn set_polygon_mode(&mut self, mode: PolygonMode) {
+        let mode = match mode {
+            PolygonMode::Fill => gl::FILL,
+            PolygonMode::Line => gl::LINE,
+            PolygonMode::Point => gl::POINT,
+        };
+        unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, mode) }
+    }
+
+    fn set_depth_test(&mut self, enabled: bool) {
+        set_capability(gl::DEPTH_TEST, enabled);
+        unsafe { gl::DepthFunc(gl::LESS) }
+    }
+
+    fn set_face_culling(&mut self, enabled: bool) {
+        set_capability(gl::CULL_FACE, enabled);
+        unsafe {
+            gl::CullFace(gl::BACK);
+            gl::FrontFace(gl::CCW);
+        }
+    }
+
+    fn set_blending(&mut self, enabled: bool) {
+        set_capability(gl::BLEND, enabled);
+        unsafe { gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA) }
+    }
+
+    fn create_vertex_array(&mut self) -> VAO {
+        let mut vao = 0;
+        unsafe { gl::GenVertexArrays(1, &mut vao) }
+        self.deletion_queue.created(Resource::VertexArray(vao));
+        vao
+    }
+
+    fn create_vertex_buffer(&mut self, vao: VAO, data: &[f32]) -> VBO {
+        let mut vbo = 0;
+        unsafe {
+            gl::BindVertexArray(vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (data.len() * mem::size_of::<f32>()) as isize,
+                data.as_ptr() as *const c_void,
+                // Tell the GPU if our data are likely to change frequently
+                gl::STATIC_DRAW,
+            );
+        }
+        self.deletion_queue.created(Resource::Buffer(vbo));
+        vbo
+    }
+
+    fn update_vertex_buffer(&mut self, vbo: VBO, data: &[f32]) {
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (data.len() * mem::size_of::<f32>()) as isize,
+                data.as_ptr() as *const c_void,
+                gl::DYNAMIC_DRAW,
+            );
+        }
+    }
+
+    fn create_index_buffer(&mut self, vao: VAO, data: &[u32]) -> EBO {
+        let mut ebo = 0;
+        unsafe {
+            // The element buffer binding is stored in the vertex array
+            gl::BindVertexArray(vao);
+            gl::GenBuffers(1, &mut ebo);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                (data.len() * mem::size_of::<u32>()) as isize,
+                data.as_ptr() as *const c_void,
+                gl::STATIC_DRAW,
+            );
+        }
+        self.deletion_queue.created(Resource::Buffer(ebo));
+        ebo
+    }
+
+    fn set_vertex_attribute(
+        &mut self,
+        vao: VAO,
+        vbo: VBO,
+        attribute: VertexAttribute,
+    ) {
+        unsafe {
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            let kind = match attribute.kind {
+                AttributeType::Float => gl::FLOAT,
+            };
+            gl::VertexAttribPointer(
+                attribute.location,
+                attribute.components as i32,
+                kind,
+                gl::FALSE,
+                attribute.stride as i32,
+                attribute.offset as *const c_void,
+            );
+            gl::EnableVertexAttribArray(attribute.location);
+            gl::BindVertexArray(0);
+        }
+    }
+
+    fn create_program(
+        &mut self,
+        vertex: &str,
+        fragment: &str,
+    ) -> Result<ProgramId, ShaderError> {
+        unsafe {
+            let vertex_shader = compile_shader(ShaderStage::Vertex, vertex)?;
+            let fragment_shader =
+                match compile_shader(ShaderStage::Fragment, fragment) {
+                    Ok(fragment_shader) => fragment_shader,
+                    Err(error) => {
+                        gl::DeleteShader(vertex_shader);
+                        return Err(error);
+                    }
+                };
+
+            let program = gl::CreateProgram();
+            gl::AttachShader(program, vertex_shader);
+            gl::AttachShader(program, fragment_shader);
+            gl::LinkProgram(program);
+
+            gl::DeleteShader(vertex_shader);
+            gl::DeleteShader(fragment_shader);
+
+            let mut success = i32::from(gl::FALSE);
+            gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
+            if success != i32::from(gl::TRUE) {
+                let log =
+                    info_log(program, gl::GetProgramiv, gl::GetProgramInfoLog);
+                gl::DeleteProgram(program);
+                return Err(ShaderError::Link(log));
+            }
+
+            self.deletion_queue.created(Resource::Program(program));
+            Ok(program)
+        }
+    }
+
+    fn use_program(&mut self, program: ProgramId) {
+        unsafe { gl::UseProgram(program) }
+    }
+
+    fn set_uniform(&mut self, program: ProgramId, name: &str, value: &Uniform) {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            let location = gl::GetUniformLocation(program, name.as_ptr());
+            match value {
+                Uniform::Bool(value) => gl::Uniform1i(location, *value as i32),
+                Uniform::Int(value) => gl::Uniform1i(location, *value),
+                Uniform::Float(value) => gl::Uniform1f(location, *value),
+                Uniform::Vec3(value) => {
+                    gl::Uniform3f(location, value.x, value.y, value.z)
+                }
+                Uniform::Vec4(value) => {
+                    gl::Uniform4f(location, value.x, value.y, value.z, value.w)
+                }
+                Uniform::Mat4(value) => gl::UniformMatrix4fv(
+                    location,
+                    1,
+                    gl::FALSE,
+                    value.as_slice().as_ptr(),
+                ),
+            }
+        }
+    }
+
+    fn create_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        sampler: &Sampler,
+    ) -> TextureId {
+        let mut texture = 0;
+        let mag_filter = match sampler.mag_filter {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        };
+        let min_filter =
+            gl_min_filter(sampler.min_filter, sampler.mipmap_filter);
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            let parameters = [
+                (gl::TEXTURE_WRAP_S, gl_wrap(sampler.wrap_s)),
+                (gl::TEXTURE_WRAP_T, gl_wrap(sampler.wrap_t)),
+                (gl::TEXTURE_MAG_FILTER, mag_filter),
+                (gl::TEXTURE_MIN_FILTER, min_filter),
+            ];
+            for (parameter, value) in &parameters {
+                gl::TexParameteri(gl::TEXTURE_2D, *parameter, *value as i32);
+            }
+
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as i32,
+                width as i32,
+                height as i32,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const c_void,
+            );
+            if sampler.mipmap_filter.is_some() {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+        }
+        self.deletion_queue.created(Resource::Texture(texture));
+        texture
+    }
+
+    fn create_data_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        data: &[f32],
+    ) -> TextureId {
+        let mut texture = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            let parameters = [
+                (gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE),
+                (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE),
+                (gl::TEXTURE_MAG_FILTER, gl::NEAREST),
+                (gl::TEXTURE_MIN_FILTER, gl::NEAREST),
+            ];
+            for (parameter, value) in &parameters {
+                gl::TexParameteri(gl::TEXTURE_2D, *parameter, *value as i32);
+            }
+        }
+        self.update_data_texture(texture, width, height, data);
+        self.deletion_queue.created(Resource::Texture(texture));
+        texture
+    }
+
+    fn update_data_texture(
+        &mut self,
+        texture: TextureId,
+        width: u32,
+        height: u32,
+        data: &[f32],
+    ) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA32F as i32,
+                width as i32,
+                height as i32,
+                0,
+                gl::RGBA,
+                gl::FLOAT,
+                data.as_ptr() as *const c_void,
+            );
+        }
+    }
+
+    fn bind_texture(&mut self, unit: u32, texture: TextureId) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+        }
+    }
+
+    fn draw_arrays(&mut self, vao: VAO, mode: DrawMode, count: u32) {
+        unsafe {
+            gl::BindVertexArray(vao);
+            gl::DrawArrays(gl_draw_mode(mode), 0, count as i32);
+        }
+    }
+
+    fn draw_elements(&mut self, vao: VAO, mode: DrawMode, count: u32) {
+        unsafe {
+            gl::BindVertexArray(vao);
+            gl::DrawElements(
+                gl_draw_mode(mode),
+                count as i32,
+                gl::UNSIGNED_INT,
+                ptr::null(),
+            );
+        }
+    }
+
+    fn delete_vertex_array(&mut self, vao: VAO) {
+        unsafe { gl::DeleteVertexArrays(1, &vao) }
+        self.deletion_queue.deleted(Resource::VertexArray(vao));
+    }
+
+    fn delete_buffer(&mut self, buffer: VBO) {
+        unsafe { gl::DeleteBuffers(1, &buffer) }
+        self.deletion_queue.deleted(Resource::Buffer(buffer));
+    }
+
+    fn delete_program(&mut self, program: ProgramId) {
+        unsafe { gl::DeleteProgram(program) }
+        self.deletion_queue.deleted(Resource::Program(program));
+    }
+
+    fn delete_texture(&mut self, texture: TextureId) {
+        unsafe { gl::DeleteTextures(1, &texture) }
+        self.deletion_queue.deleted(Resource::Texture(texture));
+    }
+
+    fn get_deletion_queue(&self) -> DeletionQueue {
+        self.deletion_queue.clone()
+    }
+}
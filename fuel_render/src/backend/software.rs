@@ -0,0 +1,1139 @@
+use super::{
+    DeletionQueue, DrawMode, Filter, PolygonMode, ProgramId, RenderBackend,
+    Resource, Sampler, ShaderError, TextureId, Uniform, VertexAttribute, Wrap,
+};
+use fuel_types::{EBO, VAO, VBO};
+use image::{ImageBuffer, RgbaImage};
+use na::{Matrix4, Vector2, Vector4};
+use std::collections::HashMap;
+use std::mem;
+use vertex::Attribute;
+
+#[derive(Debug, Default)]
+struct VertexArray {
+    attributes: HashMap<u32, (VBO, VertexAttribute)>,
+    ebo: Option<EBO>,
+}
+
+#[derive(Debug)]
+struct SoftwareTexture {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    sampler: Sampler,
+}
+
+// Index of a texel on one axis, following the wrap mode
+fn wrap_texel(texel: i64, size: u32, wrap: Wrap) -> u32 {
+    let size = i64::from(size);
+    let modulo = |texel: i64, size: i64| ((texel % size) + size) % size;
+    let texel = match wrap {
+        Wrap::Repeat => modulo(texel, size),
+        Wrap::MirroredRepeat => {
+            let texel = modulo(texel, size * 2);
+            if texel < size {
+                texel
+            } else {
+                size * 2 - 1 - texel
+            }
+        }
+        Wrap::ClampToEdge => texel.max(0).min(size - 1),
+    };
+    texel as u32
+}
+
+impl SoftwareTexture {
+    // Textures without texels sample as opaque black, like
+    // incomplete textures of OpenGL
+    fn texel(&self, x: i64, y: i64) -> Vector4<f32> {
+        if self.width == 0 || self.height == 0 {
+            return Vector4::new(0., 0., 0., 1.);
+        }
+        let x = wrap_texel(x, self.width, self.sampler.wrap_s);
+        let y = wrap_texel(y, self.height, self.sampler.wrap_t);
+        let index = ((y * self.width + x) * 4) as usize;
+        self.pixels
+            .get(index..index + 4)
+            .map(from_rgba)
+            .unwrap_or_else(|| Vector4::new(0., 0., 0., 1.))
+    }
+
+    // Sampled from the base level only, with the mag filter
+    fn sample(&self, uv: Vector2<f32>) -> Vector4<f32> {
+        let x = uv.x * self.width as f32;
+        let y = uv.y * self.height as f32;
+
+        match self.sampler.mag_filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Linear => {
+                // Texel centers are at half coordinates
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let top = self.texel(x0, y0) * (1. - tx)
+                    + self.texel(x0 + 1, y0) * tx;
+                let bottom = self.texel(x0, y0 + 1) * (1. - tx)
+                    + self.texel(x0 + 1, y0 + 1) * tx;
+                top * (1. - ty) + bottom * ty
+            }
+        }
+    }
+}
+
+/// Vertex coming out of the vertex stage, in clip space.
+#[derive(Clone, Copy, Debug)]
+struct ClipVertex {
+    position: Vector4<f32>,
+    tex_coord: Vector2<f32>,
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &ClipVertex, t: f32) -> ClipVertex {
+        ClipVertex {
+            position: self.position + (other.position - self.position) * t,
+            tex_coord: self.tex_coord + (other.tex_coord - self.tex_coord) * t,
+        }
+    }
+
+    // Signed distance to the near plane, positive when visible
+    fn near_distance(&self) -> f32 {
+        self.position.z + self.position.w
+    }
+}
+
+/// Vertex in window coordinates. Attributes are divided by w
+/// so they can be interpolated linearly on screen.
+#[derive(Clone, Copy, Debug)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    tex_coord: Vector2<f32>,
+}
+
+impl ScreenVertex {
+    fn lerp(&self, other: &ScreenVertex, t: f32) -> ScreenVertex {
+        ScreenVertex {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+            z: self.z + (other.z - self.z) * t,
+            inv_w: self.inv_w + (other.inv_w - self.inv_w) * t,
+            tex_coord: self.tex_coord + (other.tex_coord - self.tex_coord) * t,
+        }
+    }
+}
+
+/// Pipeline state and fragment stage of a draw call.
+struct Shading<'a> {
+    color: Vector4<f32>,
+    texture: Option<&'a SoftwareTexture>,
+    /// Fragments with a lower alpha are discarded.
+    alpha_cutoff: Option<f32>,
+    depth_test: bool,
+    face_culling: bool,
+    blending: bool,
+}
+
+impl<'a> Shading<'a> {
+    fn shade(&self, tex_coord: Vector2<f32>) -> Option<Vector4<f32>> {
+        let mut color = self.color;
+        if let Some(texture) = self.texture {
+            color = color.component_mul(&texture.sample(tex_coord));
+        }
+
+        match self.alpha_cutoff {
+            Some(cutoff) if color.w < cutoff => None,
+            _ => Some(color),
+        }
+    }
+}
+
+fn to_rgba(color: &Vector4<f32>) -> [u8; 4] {
+    let channel = |c: f32| (c.max(0.).min(1.) * 255.).round() as u8;
+    [
+        channel(color.x),
+        channel(color.y),
+        channel(color.z),
+        channel(color.w),
+    ]
+}
+
+fn from_rgba(color: &[u8]) -> Vector4<f32> {
+    Vector4::new(
+        f32::from(color[0]),
+        f32::from(color[1]),
+        f32::from(color[2]),
+        f32::from(color[3]),
+    ) / 255.
+}
+
+#[derive(Debug)]
+struct FrameBuffer {
+    width: u32,
+    height: u32,
+    color: Vec<u8>,
+    depth: Vec<f32>,
+}
+
+impl FrameBuffer {
+    fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        FrameBuffer {
+            width,
+            height,
+            color: vec![0; size * 4],
+            depth: vec![1.; size],
+        }
+    }
+
+    fn to_screen(&self, vertex: &ClipVertex) -> ScreenVertex {
+        let p = vertex.position;
+        let inv_w = 1. / p.w;
+        ScreenVertex {
+            x: (p.x * inv_w + 1.) * 0.5 * self.width as f32,
+            // Rows are stored from the top of the image
+            y: (1. - p.y * inv_w) * 0.5 * self.height as f32,
+            z: p.z * inv_w,
+            inv_w,
+            tex_coord: vertex.tex_coord * inv_w,
+        }
+    }
+
+    // Depth test then write or blend the shaded fragment
+    fn fragment(
+        &mut self,
+        x: i64,
+        y: i64,
+        v: &ScreenVertex,
+        shading: &Shading,
+    ) {
+        let (width, height) = (i64::from(self.width), i64::from(self.height));
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return;
+        }
+        // Far plane
+        if v.z > 1. {
+            return;
+        }
+
+        let index = (y as u32 * self.width + x as u32) as usize;
+        let depth = v.z * 0.5 + 0.5;
+        if shading.depth_test && depth >= self.depth[index] {
+            return;
+        }
+
+        let mut color = match shading.shade(v.tex_coord / v.inv_w) {
+            Some(color) => color,
+            None => return,
+        };
+        let pixel = &mut self.color[index * 4..index * 4 + 4];
+        if shading.blending {
+            let alpha = color.w;
+            color = color * alpha + from_rgba(pixel) * (1. - alpha);
+        }
+        pixel.copy_from_slice(&to_rgba(&color));
+        if shading.depth_test {
+            self.depth[index] = depth;
+        }
+    }
+
+    fn point(&mut self, v: &ScreenVertex, shading: &Shading) {
+        self.fragment(v.x.floor() as i64, v.y.floor() as i64, v, shading);
+    }
+
+    fn line(&mut self, a: &ScreenVertex, b: &ScreenVertex, shading: &Shading) {
+        let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.);
+        for step in 0..=steps as i64 {
+            let v = a.lerp(b, step as f32 / steps);
+            self.point(&v, shading);
+        }
+    }
+
+    fn triangle(&mut self, v: &[ScreenVertex; 3], shading: &Shading) {
+        let edge = |a: &ScreenVertex, b: &ScreenVertex, x: f32, y: f32| {
+            (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+        };
+
+        // Rows go down, so front faces have a negative area
+        let area = edge(&v[0], &v[1], v[2].x, v[2].y);
+        if area == 0. || (shading.face_culling && area > 0.) {
+            return;
+        }
+        // Top-left rule: pixels centered on an edge are only drawn
+        // by the triangle on its right or below it, so triangles
+        // sharing the edge don't draw them twice
+        let is_top_left = |a: &ScreenVertex, b: &ScreenVertex| {
+            let (a, b) = if area > 0. { (a, b) } else { (b, a) };
+            let rise = b.y - a.y;
+            rise < 0. || (rise == 0. && b.x > a.x)
+        };
+        let top_left = [
+            is_top_left(&v[1], &v[2]),
+            is_top_left(&v[2], &v[0]),
+            is_top_left(&v[0], &v[1]),
+        ];
+        let inside = |weight: f32, top_left: bool| {
+            weight > 0. || (weight == 0. && top_left)
+        };
+
+        let min_x = v.iter().fold(v[0].x, |m, v| m.min(v.x)).floor().max(0.);
+        let min_y = v.iter().fold(v[0].y, |m, v| m.min(v.y)).floor().max(0.);
+        let max_x = v
+            .iter()
+            .fold(v[0].x, |m, v| m.max(v.x))
+            .ceil()
+            .min(self.width as f32 - 1.);
+        let max_y = v
+            .iter()
+            .fold(v[0].y, |m, v| m.max(v.y))
+            .ceil()
+            .min(self.height as f32 - 1.);
+
+        for y in min_y as i64..=max_y as i64 {
+            for x in min_x as i64..=max_x as i64 {
+                // Sample at the center of the pixel
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let b0 = edge(&v[1], &v[2], px, py) / area;
+                let b1 = edge(&v[2], &v[0], px, py) / area;
+                let b2 = edge(&v[0], &v[1], px, py) / area;
+                if !inside(b0, top_left[0])
+                    || !inside(b1, top_left[1])
+                    || !inside(b2, top_left[2])
+                {
+                    continue;
+                }
+
+                let fragment = ScreenVertex {
+                    x: px,
+                    y: py,
+                    z: b0 * v[0].z + b1 * v[1].z + b2 * v[2].z,
+                    inv_w: b0 * v[0].inv_w + b1 * v[1].inv_w + b2 * v[2].inv_w,
+                    tex_coord: v[0].tex_coord * b0
+                        + v[1].tex_coord * b1
+                        + v[2].tex_coord * b2,
+                };
+                self.fragment(x, y, &fragment, shading);
+            }
+        }
+    }
+}
+
+// Clip a polygon against the near plane
+fn clip_polygon(polygon: &[ClipVertex]) -> Vec<ClipVertex> {
+    let mut clipped = vec![];
+    for (i, current) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        let (d_current, d_next) =
+            (current.near_distance(), next.near_distance());
+
+        if d_current >= 0. {
+            clipped.push(*current);
+        }
+        if (d_current >= 0.) != (d_next >= 0.) {
+            clipped.push(current.lerp(next, d_current / (d_current - d_next)));
+        }
+    }
+    clipped
+}
+
+// Clip a segment against the near plane
+fn clip_line(
+    a: &ClipVertex,
+    b: &ClipVertex,
+) -> Option<(ClipVertex, ClipVertex)> {
+    let (d_a, d_b) = (a.near_distance(), b.near_distance());
+    match (d_a >= 0., d_b >= 0.) {
+        (true, true) => Some((*a, *b)),
+        (false, false) => None,
+        (true, false) => Some((*a, a.lerp(b, d_a / (d_a - d_b)))),
+        (false, true) => Some((a.lerp(b, d_a / (d_a - d_b)), *b)),
+    }
+}
+
+/// Backend rasterizing every draw call on the CPU into an
+/// in-memory RGBA image, so we can get real pictures of a
+/// scene on machines without any GPU.
+///
+/// Shader programs can't be run, the fragment color is the
+/// `base_color_factor` uniform multiplied by the texture
+/// bound to the unit 0, sampled with the texture coordinates
+/// of the location 1. Positions are read from the location 0
+/// and transformed by the `mvp` uniform. There is no lighting.
+#[derive(Debug)]
+pub struct SoftwareBackend {
+    target: FrameBuffer,
+    polygon_mode: PolygonMode,
+    depth_test: bool,
+    face_culling: bool,
+    blending: bool,
+    vertex_arrays: HashMap<VAO, VertexArray>,
+    vertex_buffers: HashMap<VBO, Vec<f32>>,
+    index_buffers: HashMap<EBO, Vec<u32>>,
+    uniforms: HashMap<ProgramId, HashMap<String, Uniform>>,
+    program: Option<ProgramId>,
+    textures: HashMap<TextureId, SoftwareTexture>,
+    texture_units: HashMap<u32, TextureId>,
+    next_id: u32,
+    deletion_queue: DeletionQueue,
+}
+
+impl SoftwareBackend {
+    pub fn new(width: u32, height: u32) -> Self {
+        SoftwareBackend {
+            target: FrameBuffer::new(width, height),
+            polygon_mode: PolygonMode::default(),
+            depth_test: false,
+            face_culling: false,
+            blending: false,
+            vertex_arrays: HashMap::new(),
+            vertex_buffers: HashMap::new(),
+            index_buffers: HashMap::new(),
+            uniforms: HashMap::new(),
+            program: None,
+            textures: HashMap::new(),
+            texture_units: HashMap::new(),
+            next_id: 0,
+            deletion_queue: DeletionQueue::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.target.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.target.height
+    }
+
+    /// RGBA pixels, row by row from the top left corner.
+    pub fn pixels(&self) -> &[u8] {
+        &self.target.color
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.target.width + x) * 4) as usize;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.target.color[i..i + 4]);
+        pixel
+    }
+
+    /// Depth of a pixel in [0, 1], 1 is the far plane.
+    pub fn depth(&self, x: u32, y: u32) -> f32 {
+        self.target.depth[(y * self.target.width + x) as usize]
+    }
+
+    pub fn to_image(&self) -> RgbaImage {
+        ImageBuffer::from_raw(
+            self.target.width,
+            self.target.height,
+            self.target.color.clone(),
+        )
+        .expect("Frame buffer doesn't match its dimensions.")
+    }
+
+    fn gen_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn uniform(&self, name: &str) -> Option<&Uniform> {
+        self.program
+            .and_then(|program| self.uniforms.get(&program))
+            .and_then(|uniforms| uniforms.get(name))
+    }
+
+    // Read the components of an attribute, missing ones are 0
+    fn fetch(&self, vao: &VertexArray, location: u32, index: u32) -> [f32; 4] {
+        let mut value = [0.; 4];
+        if let Some((vbo, attribute)) = vao.attributes.get(&location) {
+            let data = &self.vertex_buffers[vbo];
+            let float_size = mem::size_of::<f32>();
+            let stride = match attribute.stride {
+                0 => attribute.components * float_size,
+                stride => stride,
+            };
+            let start =
+                (index as usize * stride + attribute.offset) / float_size;
+            let components = attribute.components.min(4);
+            for (c, component) in value.iter_mut().take(components).enumerate()
+            {
+                if let Some(data) = data.get(start + c) {
+                    *component = *data;
+                }
+            }
+        }
+        value
+    }
+
+    // Vertex stage
+    fn vertex(
+        &self,
+        vao: &VertexArray,
+        mvp: &Matrix4<f32>,
+        index: u32,
+    ) -> ClipVertex {
+        let p = self.fetch(vao, Attribute::Position.location(), index);
+        let uv = self.fetch(vao, Attribute::TexCoord0.location(), index);
+        ClipVertex {
+            position: mvp * Vector4::new(p[0], p[1], p[2], 1.),
+            tex_coord: Vector2::new(uv[0], uv[1]),
+        }
+    }
+
+    fn draw(&mut self, vao: VAO, mode: DrawMode, indices: &[u32]) {
+        let vertices: Vec<ClipVertex> = {
+            let vertex_array = match self.vertex_arrays.get(&vao) {
+                Some(vertex_array) => vertex_array,
+                None => return,
+            };
+            let mvp = match self.uniform("mvp") {
+                Some(Uniform::Mat4(mvp)) => *mvp,
+                _ => Matrix4::identity(),
+            };
+            indices
+                .iter()
+                .map(|index| self.vertex(vertex_array, &mvp, *index))
+                .collect()
+        };
+
+        let has_tex_coord = self.vertex_arrays[&vao]
+            .attributes
+            .contains_key(&Attribute::TexCoord0.location());
+        let has_texture = match self.uniform("has_base_color_texture") {
+            Some(Uniform::Bool(has_texture)) => *has_texture,
+            _ => true,
+        };
+        let color = match self.uniform("base_color_factor") {
+            Some(Uniform::Vec4(color)) => *color,
+            _ => Vector4::new(1., 1., 1., 1.),
+        };
+        let alpha_cutoff =
+            match (self.uniform("alpha_mode"), self.uniform("alpha_cutoff")) {
+                (Some(Uniform::Int(1)), Some(Uniform::Float(cutoff))) => {
+                    Some(*cutoff)
+                }
+                _ => None,
+            };
+
+        let textures = &self.textures;
+        let texture = self
+            .texture_units
+            .get(&0)
+            .and_then(|id| textures.get(id))
+            .filter(|_| has_tex_coord && has_texture);
+        let shading = Shading {
+            color,
+            texture,
+            alpha_cutoff,
+            depth_test: self.depth_test,
+            face_culling: self.face_culling,
+            blending: self.blending,
+        };
+        let target = &mut self.target;
+
+        match mode {
+            DrawMode::Points => {
+                for vertex in &vertices {
+                    rasterize_point(target, vertex, &shading);
+                }
+            }
+            DrawMode::Lines => {
+                for line in vertices.chunks(2).filter(|l| l.len() == 2) {
+                    rasterize_line(target, &line[0], &line[1], &shading);
+                }
+            }
+            DrawMode::LineStrip | DrawMode::LineLoop => {
+                for line in vertices.windows(2) {
+                    rasterize_line(target, &line[0], &line[1], &shading);
+                }
+                if mode == DrawMode::LineLoop && vertices.len() > 2 {
+                    let (first, last) =
+                        (&vertices[0], &vertices[vertices.len() - 1]);
+                    rasterize_line(target, last, first, &shading);
+                }
+            }
+            DrawMode::Triangles => {
+                for triangle in vertices.chunks(3).filter(|t| t.len() == 3) {
+                    let triangle = [triangle[0], triangle[1], triangle[2]];
+                    rasterize_triangle(
+                        target,
+                        self.polygon_mode,
+                        &triangle,
+                        &shading,
+                    );
+                }
+            }
+            DrawMode::TriangleStrip => {
+                for (i, triangle) in vertices.windows(3).enumerate() {
+                    // Keep the same winding for every triangle
+                    let triangle = if i % 2 == 0 {
+                        [triangle[0], triangle[1], triangle[2]]
+                    } else {
+                        [triangle[1], triangle[0], triangle[2]]
+                    };
+                    rasterize_triangle(
+                        target,
+                        self.polygon_mode,
+                        &triangle,
+                        &shading,
+                    );
+                }
+            }
+            DrawMode::TriangleFan => {
+                for i in 2..vertices.len() {
+                    let triangle = [vertices[0], vertices[i - 1], vertices[i]];
+                    rasterize_triangle(
+                        target,
+                        self.polygon_mode,
+                        &triangle,
+                        &shading,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn rasterize_point(
+    target: &mut FrameBuffer,
+    vertex: &ClipVertex,
+    shading: &Shading,
+) {
+    if vertex.near_distance() >= 0. {
+        let vertex = target.to_screen(vertex);
+        target.point(&vertex, shading);
+    }
+}
+
+fn rasterize_line(
+    target: &mut FrameBuffer,
+    a: &ClipVertex,
+    b: &ClipVertex,
+    shading: &Shading,
+) {
+    if let Some((a, b)) = clip_line(a, b) {
+        let (a, b) = (target.to_screen(&a), target.to_screen(&b));
+        target.line(&a, &b, shading);
+    }
+}
+
+fn rasterize_triangle(
+    target: &mut FrameBuffer,
+    mode: PolygonMode,
+    triangle: &[ClipVertex; 3],
+    shading: &Shading,
+) {
+    match mode {
+        PolygonMode::Point => {
+            for vertex in triangle {
+                rasterize_point(target, vertex, shading);
+            }
+        }
+        PolygonMode::Line => {
+            for i in 0..3 {
+                rasterize_line(
+                    target,
+                    &triangle[i],
+                    &triangle[(i + 1) % 3],
+                    shading,
+                );
+            }
+        }
+        PolygonMode::Fill => {
+            let polygon: Vec<ScreenVertex> = clip_polygon(triangle)
+                .iter()
+                .map(|vertex| target.to_screen(vertex))
+                .collect();
+            for i in 2..polygon.len() {
+                target.triangle(
+                    &[polygon[0], polygon[i - 1], polygon[i]],
+                    shading,
+                );
+            }
+        }
+    }
+}
+
+impl RenderBackend for SoftwareBackend {
+    fn clear(&mut self, color: [f32; 4]) {
+        let channel = |c: f32| (c.max(0.).min(1.) * 255.).round() as u8;
+        let color = [
+            channel(color[0]),
+            channel(color[1]),
+            channel(color[2]),
+            channel(color[3]),
+        ];
+        for pixel in self.target.color.chunks_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+        for depth in &mut self.target.depth {
+            *depth = 1.;
+        }
+    }
+
+    // The frame buffer is the whole viewport, it is
+    // reallocated when the size changes
+    fn set_viewport(&mut self, width: u32, height: u32) {
+        if (width, height) != (self.target.width, self.target.height) {
+            self.target = FrameBuffer::new(width, height);
+        }
+    }
+
+    fn set_polygon_mode(&mut self, mode: PolygonMode) {
+        self.polygon_mode = mode;
+    }
+
+    fn set_depth_test(&mut self, enabled: bool) {
+        self.depth_test = enabled;
+    }
+
+    fn set_face_culling(&mut self, enabled: bool) {
+        self.face_culling = enabled;
+    }
+
+    fn set_blending(&mut self, enabled: bool) {
+        self.blending = enabled;
+    }
+
+    fn create_vertex_array(&mut self) -> VAO {
+        let vao = self.gen_id();
+        self.vertex_arrays.insert(vao, VertexArray::default());
+        self.deletion_queue.created(Resource::VertexArray(vao));
+        vao
+    }
+
+    fn create_vertex_buffer(&mut self, _vao: VAO, data: &[f32]) -> VBO {
+        let vbo = self.gen_id();
+        self.vertex_buffers.insert(vbo, data.to_vec());
+        self.deletion_queue.created(Resource::Buffer(vbo));
+        vbo
+    }
+
+    fn update_vertex_buffer(&mut self, vbo: VBO, data: &[f32]) {
+        if let Some(buffer) = self.vertex_buffers.get_mut(&vbo) {
+            *buffer = data.to_vec();
+        }
+    }
+
+    fn create_index_buffer(&mut self, vao: VAO, data: &[u32]) -> EBO {
+        let ebo = self.gen_id();
+        self.index_buffers.insert(ebo, data.to_vec());
+        if let Some(vertex_array) = self.vertex_arrays.get_mut(&vao) {
+            vertex_array.ebo = Some(ebo);
+        }
+        self.deletion_queue.created(Resource::Buffer(ebo));
+        ebo
+    }
+
+    fn set_vertex_attribute(
+        &mut self,
+        vao: VAO,
+        vbo: VBO,
+        attribute: VertexAttribute,
+    ) {
+        if let Some(vertex_array) = self.vertex_arrays.get_mut(&vao) {
+            vertex_array
+                .attributes
+                .insert(attribute.location, (vbo, attribute));
+        }
+    }
+
+    fn create_program(
+        &mut self,
+        _vertex: &str,
+        _fragment: &str,
+    ) -> Result<ProgramId, ShaderError> {
+        let program = self.gen_id();
+        self.uniforms.insert(program, HashMap::new());
+        self.deletion_queue.created(Resource::Program(program));
+        Ok(program)
+    }
+
+    fn use_program(&mut self, program: ProgramId) {
+        self.program = Some(program);
+    }
+
+    fn set_uniform(&mut self, program: ProgramId, name: &str, value: &Uniform) {
+        self.uniforms
+            .entry(program)
+            .or_insert_with(HashMap::new)
+            .insert(name.to_owned(), value.clone());
+    }
+
+    fn create_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        sampler: &Sampler,
+    ) -> TextureId {
+        let texture = self.gen_id();
+        self.textures.insert(
+            texture,
+            SoftwareTexture {
+                width,
+                height,
+                pixels: pixels.to_vec(),
+                sampler: *sampler,
+            },
+        );
+        self.deletion_queue.created(Resource::Texture(texture));
+        texture
+    }
+
+    // Vertices are not skinned, so data textures are never read
+    fn create_data_texture(
+        &mut self,
+        _width: u32,
+        _height: u32,
+        _data: &[f32],
+    ) -> TextureId {
+        let texture = self.gen_id();
+        self.deletion_queue.created(Resource::Texture(texture));
+        texture
+    }
+
+    fn update_data_texture(
+        &mut self,
+        _texture: TextureId,
+        _width: u32,
+        _height: u32,
+        _data: &[f32],
+    ) {
+    }
+
+    fn bind_texture(&mut self, unit: u32, texture: TextureId) {
+        self.texture_units.insert(unit, texture);
+    }
+
+    fn draw_arrays(&mut self, vao: VAO, mode: DrawMode, count: u32) {
+        let indices: Vec<u32> = (0..count).collect();
+        self.draw(vao, mode, &indices);
+    }
+
+    fn draw_elements(&mut self, vao: VAO, mode: DrawMode, count: u32) {
+        let indices = self
+            .vertex_arrays
+            .get(&vao)
+            .and_then(|vertex_array| vertex_array.ebo)
+            .and_then(|ebo| self.index_buffers.get(&ebo))
+            .map(|indices| {
+                indices
+                    .iter()
+                    .take(count as usize)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            });
+
+        if let Some(indices) = indices {
+            self.draw(vao, mode, &indices);
+        }
+    }
+
+    fn delete_vertex_array(&mut self, vao: VAO) {
+        self.vertex_arrays.remove(&vao);
+        self.deletion_queue.deleted(Resource::VertexArray(vao));
+    }
+
+    fn delete_buffer(&mut self, buffer: VBO) {
+        self.vertex_buffers.remove(&buffer);
+        self.index_buffers.remove(&buffer);
+        self.deletion_queue.deleted(Resource::Buffer(buffer));
+    }
+
+    fn delete_program(&mut self, program: ProgramId) {
+        self.uniforms.remove(&program);
+        if self.program == Some(program) {
+            self.program = None;
+        }
+        self.deletion_queue.deleted(Resource::Program(program));
+    }
+
+    fn delete_texture(&mut self, texture: TextureId) {
+        self.textures.remove(&texture);
+        self.deletion_queue.deleted(Resource::Texture(texture));
+    }
+
+    fn get_deletion_queue(&self) -> DeletionQueue {
+        self.deletion_queue.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vertex::AttributeType;
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 0];
+
+    fn attribute(location: u32, components: usize) -> VertexAttribute {
+        VertexAttribute {
+            name: "",
+            location,
+            components,
+            kind: AttributeType::Float,
+            stride: 0,
+            offset: 0,
+        }
+    }
+
+    // Program drawing a color, without texture
+    fn use_color(backend: &mut SoftwareBackend, color: Vector4<f32>) {
+        let program = backend.create_program("", "").unwrap();
+        backend.use_program(program);
+        backend.set_uniform(
+            program,
+            "base_color_factor",
+            &Uniform::Vec4(color),
+        );
+        backend.set_uniform(
+            program,
+            "has_base_color_texture",
+            &Uniform::Bool(false),
+        );
+    }
+
+    // Positions are in window coordinates, with the depth in
+    // normalized device coordinates
+    fn draw(
+        backend: &mut SoftwareBackend,
+        mode: DrawMode,
+        positions: &[(f32, f32, f32)],
+    ) {
+        let (width, height) = (backend.width() as f32, backend.height() as f32);
+        let data: Vec<f32> = positions
+            .iter()
+            .flat_map(|(x, y, z)| {
+                vec![x / width * 2. - 1., 1. - y / height * 2., *z]
+            })
+            .collect();
+        let vao = backend.create_vertex_array();
+        let vbo = backend.create_vertex_buffer(vao, &data);
+        backend.set_vertex_attribute(vao, vbo, attribute(0, 3));
+        backend.draw_arrays(vao, mode, positions.len() as u32);
+    }
+
+    fn rectangle(
+        min: (f32, f32),
+        max: (f32, f32),
+        z: f32,
+    ) -> Vec<(f32, f32, f32)> {
+        vec![
+            (min.0, min.1, z),
+            (min.0, max.1, z),
+            (max.0, max.1, z),
+            (min.0, min.1, z),
+            (max.0, max.1, z),
+            (max.0, min.1, z),
+        ]
+    }
+
+    // Pixels of the backend drawn with a color
+    fn drawn(backend: &SoftwareBackend, color: [u8; 4]) -> Vec<(u32, u32)> {
+        let mut pixels = vec![];
+        for y in 0..backend.height() {
+            for x in 0..backend.width() {
+                if backend.pixel(x, y) == color {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn fill_covers_the_pixel_centers() {
+        let mut backend = SoftwareBackend::new(4, 4);
+        use_color(&mut backend, Vector4::repeat(1.));
+        // Edges go through the centers of the pixels
+        draw(
+            &mut backend,
+            DrawMode::Triangles,
+            &rectangle((0.5, 0.5), (3.5, 2.5), 0.),
+        );
+        let mut expected = vec![];
+        for y in 0..2 {
+            for x in 0..3 {
+                expected.push((x, y));
+            }
+        }
+        // Only the top and left edges are drawn
+        assert_eq!(drawn(&backend, WHITE), expected);
+        assert_eq!(backend.pixel(3, 3), BLACK);
+    }
+
+    #[test]
+    fn shared_edges_are_drawn_once() {
+        let mut backend = SoftwareBackend::new(4, 4);
+        backend.set_blending(true);
+        use_color(&mut backend, Vector4::new(1., 1., 1., 0.5));
+        // The diagonal goes through the centers of the pixels,
+        // blended twice they would be brighter
+        let quad = rectangle((0., 0.), (4., 4.), 0.);
+        draw(&mut backend, DrawMode::Triangles, &quad);
+        let half = backend.pixel(0, 0);
+        assert_eq!(half[0], 128);
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(backend.pixel(x, y), half, "{} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn depth_test_keeps_the_nearest_fragment() {
+        let mut backend = SoftwareBackend::new(2, 2);
+        backend.set_depth_test(true);
+        let quad = |z| rectangle((0., 0.), (2., 2.), z);
+
+        use_color(&mut backend, Vector4::new(1., 0., 0., 1.));
+        draw(&mut backend, DrawMode::Triangles, &quad(0.5));
+        assert!((backend.depth(1, 1) - 0.75).abs() < 1e-6);
+        use_color(&mut backend, Vector4::new(0., 1., 0., 1.));
+        draw(&mut backend, DrawMode::Triangles, &quad(0.8));
+        assert_eq!(backend.pixel(1, 1), [255, 0, 0, 255]);
+        assert!((backend.depth(1, 1) - 0.75).abs() < 1e-6);
+        use_color(&mut backend, Vector4::new(0., 0., 1., 1.));
+        draw(&mut backend, DrawMode::Triangles, &quad(0.2));
+        assert_eq!(backend.pixel(1, 1), [0, 0, 255, 255]);
+        assert!((backend.depth(1, 1) - 0.6).abs() < 1e-6);
+
+        // Without depth test, nothing is tested nor written
+        backend.set_depth_test(false);
+        use_color(&mut backend, Vector4::new(0., 1., 0., 1.));
+        draw(&mut backend, DrawMode::Triangles, &quad(0.9));
+        assert_eq!(backend.pixel(1, 1), [0, 255, 0, 255]);
+        assert!((backend.depth(1, 1) - 0.6).abs() < 1e-6);
+        backend.clear([0.; 4]);
+        assert!((backend.depth(1, 1) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lines_and_points_draw_the_crossed_pixels() {
+        let mut backend = SoftwareBackend::new(4, 4);
+        use_color(&mut backend, Vector4::repeat(1.));
+        let line = [(0.5, 1.5, 0.), (3.5, 1.5, 0.)];
+        draw(&mut backend, DrawMode::Lines, &line);
+        assert_eq!(
+            drawn(&backend, WHITE),
+            vec![(0, 1), (1, 1), (2, 1), (3, 1)]
+        );
+
+        backend.clear([0.; 4]);
+        draw(
+            &mut backend,
+            DrawMode::Points,
+            &[(2.5, 2.5, 0.), (0.5, 0.5, 2.)],
+        );
+        // Behind the far plane, the second point is clipped
+        assert_eq!(drawn(&backend, WHITE), vec![(2, 2)]);
+    }
+
+    #[test]
+    fn polygon_modes_draw_the_edges_or_the_corners() {
+        let triangle = [(0.5, 0.5, 0.), (0.5, 4.5, 0.), (4.5, 4.5, 0.)];
+        let mut backend = SoftwareBackend::new(6, 6);
+        use_color(&mut backend, Vector4::repeat(1.));
+
+        backend.set_polygon_mode(PolygonMode::Line);
+        draw(&mut backend, DrawMode::Triangles, &triangle);
+        let edges = drawn(&backend, WHITE);
+        assert!(edges.contains(&(0, 2)) && edges.contains(&(2, 4)));
+        assert!(edges.contains(&(2, 2)));
+        // The inside is empty
+        assert!(!edges.contains(&(1, 3)));
+
+        backend.clear([0.; 4]);
+        backend.set_polygon_mode(PolygonMode::Point);
+        draw(&mut backend, DrawMode::Triangles, &triangle);
+        assert_eq!(drawn(&backend, WHITE), vec![(0, 0), (0, 4), (4, 4)]);
+    }
+
+    #[test]
+    fn tex_coords_are_perspective_correct() {
+        let mut backend = SoftwareBackend::new(8, 2);
+        // Red of the texels is their column
+        let pixels: Vec<u8> =
+            (0..=255).flat_map(|x| vec![x as u8, 0, 0, 255]).collect();
+        let sampler = Sampler {
+            mag_filter: Filter::Nearest,
+            ..Default::default()
+        };
+        let texture = backend.create_texture(256, 1, &pixels, &sampler);
+        backend.bind_texture(0, texture);
+
+        let program = backend.create_program("", "").unwrap();
+        backend.use_program(program);
+        // The input w is the z of the position
+        let mut mvp = Matrix4::identity();
+        mvp[(2, 2)] = 0.;
+        mvp[(3, 2)] = 1.;
+        mvp[(3, 3)] = 0.;
+        backend.set_uniform(program, "mvp", &Uniform::Mat4(mvp));
+
+        // A quad going away, w is 1 on the left and 3 on the right
+        let positions = [
+            [-1., 1., 1.],
+            [-1., -1., 1.],
+            [3., -3., 3.],
+            [-1., 1., 1.],
+            [3., -3., 3.],
+            [3., 3., 3.],
+        ];
+        let tex_coords = [0., 0., 1., 0., 1., 1.];
+        let vao = backend.create_vertex_array();
+        let data: Vec<f32> =
+            positions.iter().flat_map(|p| p.to_vec()).collect();
+        let vbo = backend.create_vertex_buffer(vao, &data);
+        backend.set_vertex_attribute(vao, vbo, attribute(0, 3));
+        let data: Vec<f32> =
+            tex_coords.iter().flat_map(|u| vec![*u, 0.5]).collect();
+        let vbo = backend.create_vertex_buffer(vao, &data);
+        backend.set_vertex_attribute(vao, vbo, attribute(1, 2));
+        backend.draw_arrays(vao, DrawMode::Triangles, 6);
+
+        for x in 0..8 {
+            // U is linear in 1 / w, not on screen
+            let t = (x as f32 + 0.5) / 8.;
+            let u = (t / 3.) / (1. - t + t / 3.);
+            let texel = (u * 256.).floor();
+            let red = f32::from(backend.pixel(x, 0)[0]);
+            assert!((red - texel).abs() <= 1., "{}: {} {}", x, red, texel);
+        }
+        // Interpolated on screen, the middle would be at 144
+        assert_eq!(backend.pixel(4, 1)[0], 76);
+    }
+
+    fn texture(width: u32, height: u32, pixels: Vec<u8>) -> SoftwareTexture {
+        SoftwareTexture {
+            width,
+            height,
+            pixels,
+            sampler: Sampler::default(),
+        }
+    }
+
+    #[test]
+    fn wrap_modes() {
+        assert_eq!(wrap_texel(-1, 4, Wrap::Repeat), 3);
+        assert_eq!(wrap_texel(5, 4, Wrap::Repeat), 1);
+        assert_eq!(wrap_texel(4, 4, Wrap::MirroredRepeat), 3);
+        assert_eq!(wrap_texel(-1, 4, Wrap::MirroredRepeat), 0);
+        assert_eq!(wrap_texel(9, 4, Wrap::ClampToEdge), 3);
+        assert_eq!(wrap_texel(-9, 4, Wrap::ClampToEdge), 0);
+    }
+
+    #[test]
+    fn empty_textures_sample_black() {
+        let black = Vector4::new(0., 0., 0., 1.);
+        let uv = Vector2::new(0.5, 0.5);
+        assert_eq!(texture(0, 4, vec![]).sample(uv), black);
+        assert_eq!(texture(4, 0, vec![]).sample(uv), black);
+        // Fewer pixels than the size of the texture
+        assert_eq!(texture(2, 2, vec![255; 4]).texel(1, 1), black);
+    }
+}
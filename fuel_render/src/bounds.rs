@@ -0,0 +1,116 @@
+use na::{Matrix4, Vector3, Vector4};
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Self {
+        Aabb { min, max }
+    }
+
+    /// Smallest box containing the points, none without points.
+    pub fn from_points<'a>(
+        points: impl IntoIterator<Item = &'a Vector3<f32>>,
+    ) -> Option<Self> {
+        points.into_iter().fold(None, |aabb, point| match aabb {
+            Some(Aabb { min, max }) => Some(Aabb::new(
+                min.zip_map(point, f32::min),
+                max.zip_map(point, f32::max),
+            )),
+            None => Some(Aabb::new(*point, *point)),
+        })
+    }
+
+    pub fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) / 2.
+    }
+
+    /// Half of the size on each axis.
+    pub fn half_extents(&self) -> Vector3<f32> {
+        (self.max - self.min) / 2.
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb::new(
+            self.min.zip_map(&other.min, f32::min),
+            self.max.zip_map(&other.max, f32::max),
+        )
+    }
+
+    /// Box containing this one once transformed, it is larger
+    /// than the box itself when the matrix has a rotation.
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> Aabb {
+        let center = transform_point(matrix, &self.center());
+        let half_extents = self.half_extents();
+        let half_extents = Vector3::from_fn(|row, _| {
+            (0..3)
+                .map(|column| {
+                    matrix[(row, column)].abs() * half_extents[column]
+                })
+                .sum()
+        });
+
+        Aabb::new(center - half_extents, center + half_extents)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vector3<f32>, radius: f32) -> Self {
+        BoundingSphere { center, radius }
+    }
+
+    /// Sphere centered on the box of the points, none
+    /// without points.
+    pub fn from_points<'a>(
+        points: impl IntoIterator<Item = &'a Vector3<f32>> + Clone,
+    ) -> Option<Self> {
+        let center = Aabb::from_points(points.clone())?.center();
+        let radius = points
+            .into_iter()
+            .map(|point| (point - center).norm())
+            .fold(0., f32::max);
+
+        Some(BoundingSphere::new(center, radius))
+    }
+
+    pub fn from_aabb(aabb: &Aabb) -> Self {
+        BoundingSphere::new(aabb.center(), aabb.half_extents().norm())
+    }
+
+    /// Sphere containing this one once transformed, the radius
+    /// follows the largest scale of the matrix.
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> BoundingSphere {
+        let center = transform_point(matrix, &self.center);
+        let scale = (0..3)
+            .map(|column| {
+                Vector3::new(
+                    matrix[(0, column)],
+                    matrix[(1, column)],
+                    matrix[(2, column)],
+                )
+                .norm()
+            })
+            .fold(0., f32::max);
+
+        BoundingSphere::new(center, self.radius * scale)
+    }
+}
+
+// Matrices of the scene are affine
+fn transform_point(
+    matrix: &Matrix4<f32>,
+    point: &Vector3<f32>,
+) -> Vector3<f32> {
+    let point = matrix * Vector4::new(point.x, point.y, point.z, 1.);
+    Vector3::new(point.x, point.y, point.z)
+}
@@ -0,0 +1,214 @@
+use bounds::{Aabb, BoundingSphere};
+use na::{Matrix4, Vector3, Vector4};
+
+/// Volume seen by a camera, as six planes facing inward.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    // Normal in xyz and distance in w
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Planes of the clip space of `projection * view`, the
+    /// frustum is in world space.
+    pub fn from_matrix(matrix: &Matrix4<f32>) -> Self {
+        let row = |index: usize| {
+            Vector4::new(
+                matrix[(index, 0)],
+                matrix[(index, 1)],
+                matrix[(index, 2)],
+                matrix[(index, 3)],
+            )
+        };
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let normalize = |plane: Vector4<f32>| {
+            let length = Vector3::new(plane.x, plane.y, plane.z).norm();
+            if length > 0. {
+                plane / length
+            } else {
+                plane
+            }
+        };
+
+        Frustum {
+            planes: [
+                normalize(w + x),
+                normalize(w - x),
+                normalize(w + y),
+                normalize(w - y),
+                normalize(w + z),
+                normalize(w - z),
+            ],
+        }
+    }
+
+    fn distance(plane: &Vector4<f32>, point: &Vector3<f32>) -> f32 {
+        plane.x * point.x + plane.y * point.y + plane.z * point.z + plane.w
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|plane| {
+            Frustum::distance(plane, &sphere.center) >= -sphere.radius
+        })
+    }
+
+    /// Boxes near the corners of the frustum can be seen
+    /// as intersecting while they are outside.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // Corner of the box the furthest along the normal
+            let corner = Vector3::new(
+                if plane.x >= 0. {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.y >= 0. {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.z >= 0. {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            Frustum::distance(plane, &corner) >= 0.
+        })
+    }
+}
+
+/// Primitives drawn and skipped during a render.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CullingStats {
+    pub drawn: usize,
+    pub culled: usize,
+}
+
+/// Decides which primitives are drawn during a render, and
+/// counts them. Everything is drawn without frustum.
+#[derive(Clone, Debug, Default)]
+pub struct Culling {
+    pub frustum: Option<Frustum>,
+    pub stats: CullingStats,
+}
+
+impl Culling {
+    pub fn new(frustum: Option<Frustum>) -> Self {
+        Culling {
+            frustum,
+            stats: CullingStats::default(),
+        }
+    }
+
+    /// Test bounding volumes, in model space, once moved to
+    /// the world. The sphere is tested first, it is cheaper.
+    pub fn should_draw(
+        &mut self,
+        bounding_box: Option<&Aabb>,
+        bounding_sphere: Option<&BoundingSphere>,
+        model: &Matrix4<f32>,
+    ) -> bool {
+        let visible = match self.frustum {
+            Some(ref frustum) => {
+                bounding_sphere.map_or(true, |sphere| {
+                    frustum.intersects_sphere(&sphere.transform(model))
+                }) && bounding_box.map_or(true, |aabb| {
+                    frustum.intersects_aabb(&aabb.transform(model))
+                })
+            }
+            None => true,
+        };
+
+        if visible {
+            self.stats.drawn += 1;
+        } else {
+            self.stats.culled += 1;
+        }
+        visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Camera at the origin looking down -z
+    fn frustum() -> Frustum {
+        let projection = Matrix4::new_perspective(1., 1., 0.1, 100.);
+        Frustum::from_matrix(&projection)
+    }
+
+    fn cube(center: Vector3<f32>, half_size: f32) -> Aabb {
+        let half_extents = Vector3::repeat(half_size);
+        Aabb::new(center - half_extents, center + half_extents)
+    }
+
+    #[test]
+    fn planes_are_normalized() {
+        for plane in &frustum().planes {
+            let normal = Vector3::new(plane.x, plane.y, plane.z);
+            assert!((normal.norm() - 1.).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn spheres() {
+        let frustum = frustum();
+        let sphere = |z: f32, radius: f32| {
+            BoundingSphere::new(Vector3::new(0., 0., z), radius)
+        };
+        assert!(frustum.intersects_sphere(&sphere(-10., 1.)));
+        // Behind the camera and beyond the far plane
+        assert!(!frustum.intersects_sphere(&sphere(5., 1.)));
+        assert!(!frustum.intersects_sphere(&sphere(-150., 1.)));
+        // Across the near plane
+        assert!(frustum.intersects_sphere(&sphere(0.5, 1.)));
+        // Beside the frustum, then large enough to reach it
+        let beside = Vector3::new(50., 0., -10.);
+        assert!(!frustum.intersects_sphere(&BoundingSphere::new(beside, 1.)));
+        assert!(frustum.intersects_sphere(&BoundingSphere::new(beside, 50.)));
+    }
+
+    #[test]
+    fn boxes() {
+        let frustum = frustum();
+        assert!(frustum.intersects_aabb(&cube(Vector3::new(0., 0., -10.), 1.)));
+        assert!(!frustum.intersects_aabb(&cube(Vector3::new(0., 0., 5.), 1.)));
+        assert!(
+            !frustum.intersects_aabb(&cube(Vector3::new(0., 0., -150.), 1.))
+        );
+        assert!(
+            !frustum.intersects_aabb(&cube(Vector3::new(50., 0., -10.), 1.))
+        );
+        // Surrounding the camera
+        assert!(frustum.intersects_aabb(&cube(Vector3::zeros(), 200.)));
+    }
+
+    #[test]
+    fn stats() {
+        let aabb = cube(Vector3::zeros(), 1.);
+        let sphere = BoundingSphere::from_aabb(&aabb);
+        let in_front = Matrix4::new_translation(&Vector3::new(0., 0., -10.));
+        let behind = Matrix4::new_translation(&Vector3::new(0., 0., 10.));
+
+        let mut culling = Culling::new(Some(frustum()));
+        assert!(culling.should_draw(Some(&aabb), Some(&sphere), &in_front));
+        assert!(!culling.should_draw(Some(&aabb), Some(&sphere), &behind));
+        assert!(!culling.should_draw(Some(&aabb), None, &behind));
+        // Primitives without bounds are always drawn
+        assert!(culling.should_draw(None, None, &behind));
+        assert_eq!(
+            culling.stats,
+            CullingStats {
+                drawn: 2,
+                culled: 2,
+            }
+        );
+
+        let mut culling = Culling::new(None);
+        assert!(culling.should_draw(Some(&aabb), Some(&sphere), &behind));
+        assert_eq!(culling.stats.culled, 0);
+    }
+}
@@ -0,0 +1,535 @@
+use na::{Vector2, Vector3, Vector4};
+use std::collections::HashMap;
+use vertex::Vertex;
+
+/// Largest angle, in degrees, between the faces of split
+/// vertices welded by smooth normals.
+pub const SMOOTHING_ANGLE: f32 = 60.;
+
+/// How normals are generated for primitives without them.
+/// glTF asks for flat normals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normals {
+    Flat,
+    /// Vertices at the same position share their normal, unless
+    /// their faces make a hard edge.
+    Smooth,
+}
+
+impl Default for Normals {
+    fn default() -> Self {
+        Normals::Flat
+    }
+}
+
+// Vertices of each triangle, incomplete ones are ignored
+fn get_triangles(count: usize, indices: Option<&[u32]>) -> Vec<[usize; 3]> {
+    match indices {
+        Some(indices) => indices
+            .chunks(3)
+            .filter(|triangle| triangle.len() == 3)
+            .map(|triangle| {
+                [
+                    triangle[0] as usize,
+                    triangle[1] as usize,
+                    triangle[2] as usize,
+                ]
+            })
+            .filter(|triangle| triangle.iter().all(|index| *index < count))
+            .collect(),
+        None => (0..count / 3)
+            .map(|triangle| [triangle * 3, triangle * 3 + 1, triangle * 3 + 2])
+            .collect(),
+    }
+}
+
+// Not normalized, its length is twice the area of the triangle
+fn get_face_normal(vertices: &[Vertex], triangle: &[usize; 3]) -> Vector3<f32> {
+    let [a, b, c] = *triangle;
+    let edge_1 = vertices[b].position - vertices[a].position;
+    let edge_2 = vertices[c].position - vertices[a].position;
+    edge_1.cross(&edge_2)
+}
+
+fn normalize_or(vector: Vector3<f32>, default: Vector3<f32>) -> Vector3<f32> {
+    let norm = vector.norm();
+    if norm > 1e-12 {
+        vector / norm
+    } else {
+        default
+    }
+}
+
+/// Give each triangle its own vertices, with the normal of its
+/// face. The vertices returned are not indexed anymore.
+pub fn flat_normals(
+    vertices: &[Vertex],
+    indices: Option<&[u32]>,
+) -> Vec<Vertex> {
+    get_triangles(vertices.len(), indices)
+        .iter()
+        .flat_map(|triangle| {
+            let normal =
+                normalize_or(get_face_normal(vertices, triangle), Vector3::z());
+            triangle
+                .iter()
+                .map(|index| Vertex {
+                    normal,
+                    ..vertices[*index].clone()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Values of the vertices of each triangle, in the order of
+/// the vertices given by `flat_normals`.
+pub fn unindex<T: Clone>(values: &[T], indices: Option<&[u32]>) -> Vec<T> {
+    get_triangles(values.len(), indices)
+        .iter()
+        .flat_map(|triangle| {
+            triangle.iter().map(|index| values[*index].clone())
+        })
+        .collect()
+}
+
+/// Normals averaged from the faces around each vertex, weighted
+/// by their area.
+///
+/// Split vertices, like the ones of a UV seam, are welded with
+/// the faces at their position which are less than
+/// `SMOOTHING_ANGLE` away from their own, so hard edges are kept.
+pub fn smooth_normals(vertices: &mut [Vertex], indices: Option<&[u32]>) {
+    let key = |vertex: &Vertex| {
+        let position = vertex.position;
+        (
+            position.x.to_bits(),
+            position.y.to_bits(),
+            position.z.to_bits(),
+        )
+    };
+    let triangles = get_triangles(vertices.len(), indices);
+    let face_normals: Vec<Vector3<f32>> = triangles
+        .iter()
+        .map(|triangle| get_face_normal(vertices, triangle))
+        .collect();
+
+    // Faces of each vertex, and faces around each position
+    let mut own = vec![Vector3::zeros(); vertices.len()];
+    let mut around: HashMap<(u32, u32, u32), Vec<usize>> = HashMap::new();
+    for (face, triangle) in triangles.iter().enumerate() {
+        for index in triangle {
+            own[*index] += face_normals[face];
+            let faces = around.entry(key(&vertices[*index])).or_default();
+            if faces.last() != Some(&face) {
+                faces.push(face);
+            }
+        }
+    }
+
+    let threshold = SMOOTHING_ANGLE.to_radians().cos();
+    for (index, vertex) in vertices.iter_mut().enumerate() {
+        let direction = normalize_or(own[index], Vector3::zeros());
+        let faces = around.get(&key(vertex)).map_or(&[][..], |f| &f[..]);
+        let mut normal = own[index];
+        for face in faces {
+            if triangles[*face].contains(&index) {
+                continue;
+            }
+            let face_normal = face_normals[*face];
+            let cosine =
+                direction.dot(&normalize_or(face_normal, Vector3::zeros()));
+            // Vertices without faces take the ones at their position
+            if direction == Vector3::zeros() || cosine >= threshold {
+                normal += face_normal;
+            }
+        }
+        vertex.normal = normalize_or(normal, Vector3::z());
+    }
+}
+
+/// Tangents for normal maps, from the texture coordinates of the
+/// set given, which should be the one of the normal texture.
+///
+/// Tangents of the faces are averaged on each vertex, as done by
+/// Lengyel. They are orthogonal to the normal, along the u axis
+/// of the texture, and w is the sign of the bitangent, along the
+/// v axis. Vertices are not split like MikkTSpace does, so maps
+/// baked with it can show seams where the faces of a vertex
+/// disagree. Normals have to be set first.
+pub fn generate_tangents(
+    vertices: &mut [Vertex],
+    indices: Option<&[u32]>,
+    tex_coord: u32,
+) {
+    let uv = |vertex: &Vertex| -> Vector2<f32> {
+        if tex_coord == 1 {
+            vertex.tex_coord_1
+        } else {
+            vertex.tex_coord_0
+        }
+    };
+    let mut tangents = vec![Vector3::zeros(); vertices.len()];
+    let mut bitangents = vec![Vector3::zeros(); vertices.len()];
+
+    for triangle in get_triangles(vertices.len(), indices) {
+        let [a, b, c] = triangle;
+        let edge_1 = vertices[b].position - vertices[a].position;
+        let edge_2 = vertices[c].position - vertices[a].position;
+        let delta_1 = uv(&vertices[b]) - uv(&vertices[a]);
+        let delta_2 = uv(&vertices[c]) - uv(&vertices[a]);
+
+        let determinant = delta_1.x * delta_2.y - delta_2.x * delta_1.y;
+        if determinant.abs() < 1e-12 {
+            continue;
+        }
+        // Weighted by the area, like the face normals
+        let area = edge_1.cross(&edge_2).norm();
+        let tangent = (edge_1 * delta_2.y - edge_2 * delta_1.y) / determinant;
+        let bitangent = (edge_2 * delta_1.x - edge_1 * delta_2.x) / determinant;
+        let tangent = normalize_or(tangent, Vector3::zeros()) * area;
+        let bitangent = normalize_or(bitangent, Vector3::zeros()) * area;
+
+        for index in &triangle {
+            tangents[*index] += tangent;
+            bitangents[*index] += bitangent;
+        }
+    }
+
+    for ((vertex, tangent), bitangent) in
+        vertices.iter_mut().zip(tangents).zip(bitangents)
+    {
+        let normal = vertex.normal;
+        // Gram-Schmidt, then any axis orthogonal to the normal
+        let tangent = tangent - normal * normal.dot(&tangent);
+        let fallback = if normal.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        let fallback = normalize_or(
+            fallback - normal * normal.dot(&fallback),
+            Vector3::x(),
+        );
+        let tangent = normalize_or(tangent, fallback);
+        let sign = if normal.cross(&tangent).dot(&bitangent) < 0. {
+            -1.
+        } else {
+            1.
+        };
+
+        vertex.tangent = Vector4::new(tangent.x, tangent.y, tangent.z, sign);
+    }
+}
+
+/// Triangles covering a polygon, as indices of its corners. The
+/// polygon can be concave, its corners are expected to be on a
+/// plane and wound the same way as the triangles returned.
+pub fn triangulate(polygon: &[Vector3<f32>]) -> Vec<[usize; 3]> {
+    if polygon.len() < 3 {
+        return vec![];
+    }
+    // Newell's method, robust to concave polygons
+    let mut normal = Vector3::zeros();
+    for (index, current) in polygon.iter().enumerate() {
+        let next = polygon[(index + 1) % polygon.len()];
+        normal.x += (current.y - next.y) * (current.z + next.z);
+        normal.y += (current.z - next.z) * (current.x + next.x);
+        normal.z += (current.x - next.x) * (current.y + next.y);
+    }
+    let normal = normalize_or(normal, Vector3::zeros());
+    // Degenerate polygons are given as a fan
+    if polygon.len() == 3 || normal == Vector3::zeros() {
+        return (1..polygon.len() - 1)
+            .map(|index| [0, index, index + 1])
+            .collect();
+    }
+
+    // Counter clockwise in the plane, seen from the normal
+    let axis = if normal.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let u = normal.cross(&axis).normalize();
+    let v = normal.cross(&u);
+    let points: Vec<Vector2<f32>> = polygon
+        .iter()
+        .map(|point| Vector2::new(point.dot(&u), point.dot(&v)))
+        .collect();
+    let cross = |a: usize, b: usize, c: usize| {
+        let (ab, ac) = (points[b] - points[a], points[c] - points[a]);
+        ab.x * ac.y - ab.y * ac.x
+    };
+
+    let mut remaining: Vec<usize> = (0..polygon.len()).collect();
+    let mut triangles = vec![];
+    while remaining.len() > 3 {
+        let (position, triangle) = {
+            let count = remaining.len();
+            let corners = |position: usize| {
+                [
+                    remaining[(position + count - 1) % count],
+                    remaining[position],
+                    remaining[(position + 1) % count],
+                ]
+            };
+            let is_ear = |position: usize| {
+                let [previous, current, next] = corners(position);
+                cross(previous, current, next) > 0.
+                    && remaining.iter().all(|other| {
+                        [previous, current, next].contains(other)
+                            || cross(previous, current, *other) < 0.
+                            || cross(current, next, *other) < 0.
+                            || cross(next, previous, *other) < 0.
+                    })
+            };
+            // Self-intersecting polygons can be left without ears
+            let position =
+                (0..count).find(|position| is_ear(*position)).unwrap_or(0);
+            (position, corners(position))
+        };
+        triangles.push(triangle);
+        remaining.remove(position);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &Vector3<f32>, expected: &Vector3<f32>) {
+        assert!(
+            (actual - expected).norm() < 1e-5,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    fn xyz(vector: &Vector4<f32>) -> Vector3<f32> {
+        Vector3::new(vector.x, vector.y, vector.z)
+    }
+
+    fn vertex(position: Vector3<f32>, uv: (f32, f32)) -> Vertex {
+        Vertex {
+            position,
+            tex_coord_0: Vector2::new(uv.0, uv.1),
+            ..Default::default()
+        }
+    }
+
+    // Corners of a face facing `u × v`, counter clockwise, with
+    // the u and v axes of the texture along them
+    fn face(
+        center: Vector3<f32>,
+        u: Vector3<f32>,
+        v: Vector3<f32>,
+    ) -> Vec<Vertex> {
+        vec![
+            vertex(center - u - v, (0., 0.)),
+            vertex(center + u - v, (1., 0.)),
+            vertex(center + u + v, (1., 1.)),
+            vertex(center - u + v, (0., 1.)),
+        ]
+    }
+
+    fn quad_indices(faces: u32) -> Vec<u32> {
+        (0..faces)
+            .flat_map(|face| {
+                let first = face * 4;
+                vec![first, first + 1, first + 2, first, first + 2, first + 3]
+            })
+            .collect()
+    }
+
+    // Axes of the faces of a cube, the normal is `u × v`
+    fn cube_axes() -> Vec<(Vector3<f32>, Vector3<f32>)> {
+        let (x, y, z) = (Vector3::x(), Vector3::y(), Vector3::z());
+        vec![(y, z), (z, y), (z, x), (x, z), (x, y), (y, x)]
+    }
+
+    // Each face has its own vertices
+    fn cube() -> (Vec<Vertex>, Vec<u32>) {
+        let vertices = cube_axes()
+            .into_iter()
+            .flat_map(|(u, v)| face(u.cross(&v), u, v))
+            .collect();
+        (vertices, quad_indices(6))
+    }
+
+    #[test]
+    fn flat_normals_of_a_quad() {
+        let vertices = face(Vector3::zeros(), Vector3::x(), Vector3::y());
+        let flat = flat_normals(&vertices, Some(&quad_indices(1)));
+        assert_eq!(flat.len(), 6);
+        for vertex in &flat {
+            assert_close(&vertex.normal, &Vector3::z());
+        }
+        assert_eq!(flat[5].position, vertices[3].position);
+        // Without indices, vertices are taken three by three
+        assert_eq!(flat_normals(&vertices, None).len(), 3);
+        assert_eq!(unindex(&[1, 2, 3, 4], Some(&quad_indices(1))).len(), 6);
+    }
+
+    #[test]
+    fn flat_normals_of_a_cube() {
+        let (vertices, indices) = cube();
+        let flat = flat_normals(&vertices, Some(&indices));
+        for (face, (u, v)) in cube_axes().into_iter().enumerate() {
+            for vertex in &flat[face * 6..face * 6 + 6] {
+                assert_close(&vertex.normal, &u.cross(&v));
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_normals_keep_hard_edges() {
+        let (mut vertices, indices) = cube();
+        smooth_normals(&mut vertices, Some(&indices));
+        for (face, (u, v)) in cube_axes().into_iter().enumerate() {
+            for vertex in &vertices[face * 4..face * 4 + 4] {
+                assert_close(&vertex.normal, &u.cross(&v));
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_normals_of_shared_vertices() {
+        // Corners of the cube shared by its faces
+        let (cube, indices) = cube();
+        let mut corners: Vec<Vertex> = vec![];
+        let indices: Vec<u32> = indices
+            .iter()
+            .map(|index| {
+                let position = cube[*index as usize].position;
+                let corner = corners
+                    .iter()
+                    .position(|corner| corner.position == position)
+                    .unwrap_or_else(|| {
+                        corners.push(vertex(position, (0., 0.)));
+                        corners.len() - 1
+                    });
+                corner as u32
+            })
+            .collect();
+        assert_eq!(corners.len(), 8);
+
+        // Between the faces, weighted by the area of their triangles
+        smooth_normals(&mut corners, Some(&indices));
+        for corner in &corners {
+            let normal = corner.normal;
+            assert!(normal.dot(&corner.position.normalize()) > 0.9);
+            assert!(normal.iter().all(|value| value.abs() < 0.9));
+        }
+    }
+
+    #[test]
+    fn smooth_normals_weld_seams() {
+        // Two quads folded by 20 degrees, each with its own vertices
+        let angle = 10f32.to_radians();
+        let left = Vector3::new(-angle.cos(), 0., angle.sin());
+        let right = Vector3::new(angle.cos(), 0., angle.sin());
+        let mut vertices = face(left, -left, Vector3::y());
+        vertices.extend(face(right, right, Vector3::y()));
+        smooth_normals(&mut vertices, Some(&quad_indices(2)));
+
+        let folded: Vec<&Vertex> = vertices
+            .iter()
+            .filter(|vertex| vertex.position.x.abs() < 1e-5)
+            .collect();
+        assert_eq!(folded.len(), 4);
+        // Vertices at the same place share a normal between the faces
+        for vertex in &folded {
+            assert!(vertex.normal.z > angle.cos() + 1e-3);
+            for other in &folded {
+                if other.position == vertex.position {
+                    assert_close(&other.normal, &vertex.normal);
+                }
+            }
+        }
+        let outer = Vector3::new(angle.sin(), 0., angle.cos());
+        assert_close(&vertices[0].normal, &outer);
+    }
+
+    #[test]
+    fn tangents_of_a_cube() {
+        let (mut vertices, indices) = cube();
+        // Mirror the texture of the last face
+        for vertex in &mut vertices[20..24] {
+            vertex.tex_coord_0.x = 1. - vertex.tex_coord_0.x;
+        }
+        smooth_normals(&mut vertices, Some(&indices));
+        generate_tangents(&mut vertices, Some(&indices), 0);
+
+        for (face, (u, _)) in cube_axes().into_iter().enumerate() {
+            let (u, sign) = if face == 5 { (-u, -1.) } else { (u, 1.) };
+            for vertex in &vertices[face * 4..face * 4 + 4] {
+                // Handedness of the tangent space in w
+                let expected = Vector4::new(u.x, u.y, u.z, sign);
+                assert!((vertex.tangent - expected).norm() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn tangents_without_texture_coordinates() {
+        let mut vertices = face(Vector3::zeros(), Vector3::x(), Vector3::y());
+        for vertex in &mut vertices {
+            vertex.normal = Vector3::z();
+            vertex.tex_coord_0 = Vector2::zeros();
+        }
+        generate_tangents(&mut vertices, Some(&quad_indices(1)), 0);
+        for vertex in &vertices {
+            let tangent = xyz(&vertex.tangent);
+            assert!((tangent.norm() - 1.).abs() < 1e-5);
+            assert!(tangent.dot(&Vector3::z()).abs() < 1e-5);
+        }
+    }
+
+    // Twice the area of the triangles, positive when counter
+    // clockwise in the xy plane
+    fn areas(polygon: &[Vector3<f32>], triangles: &[[usize; 3]]) -> Vec<f32> {
+        triangles
+            .iter()
+            .map(|[a, b, c]| {
+                (polygon[*b] - polygon[*a])
+                    .cross(&(polygon[*c] - polygon[*a]))
+                    .z
+            })
+            .collect()
+    }
+
+    #[test]
+    fn triangulate_a_quad() {
+        let polygon: Vec<Vector3<f32>> =
+            face(Vector3::zeros(), Vector3::x(), Vector3::y())
+                .iter()
+                .map(|vertex| vertex.position)
+                .collect();
+        let triangles = triangulate(&polygon);
+        assert_eq!(triangles.len(), 2);
+        let areas = areas(&polygon, &triangles);
+        assert!(areas.iter().all(|area| *area > 0.));
+        assert!((areas.iter().sum::<f32>() - 8.).abs() < 1e-5);
+        assert!(triangulate(&polygon[..2]).is_empty());
+    }
+
+    #[test]
+    fn triangulate_a_concave_polygon() {
+        // L shape, its reflex corner can't start a fan
+        let polygon: Vec<Vector3<f32>> =
+            [(0., 0.), (2., 0.), (2., 1.), (1., 1.), (1., 2.), (0., 2.)]
+                .iter()
+                .map(|(x, y)| Vector3::new(*x, *y, 0.))
+                .collect();
+        let triangles = triangulate(&polygon);
+        assert_eq!(triangles.len(), 4);
+        let areas = areas(&polygon, &triangles);
+        assert!(areas.iter().all(|area| *area > 0.));
+        assert!((areas.iter().sum::<f32>() - 6.).abs() < 1e-5);
+    }
+}
@@ -1,19 +1,51 @@
 extern crate fuel_camera;
-extern crate fuel_core;
 extern crate fuel_types;
 extern crate gl;
 extern crate gltf;
 extern crate image;
 extern crate nalgebra as na;
 
+mod animation;
+pub mod backend;
+mod bounds;
+mod culling;
+mod geometry;
+mod light;
+mod material;
 mod mesh;
+mod morph;
+mod object;
 mod primitive;
+mod ray;
 mod shader;
+mod skin;
 mod texture;
 pub mod vertex;
 
-pub use self::texture::Texture;
-pub use self::mesh::{Meshes, Mesh};
+pub use self::animation::{
+    Animation, AnimationPlayer, Blend, Channel, Interpolation, Keyframes,
+    KeyframesError, Pose, Property,
+};
+pub use self::backend::{
+    HeadlessBackend, OpenGlBackend, RenderBackend, SoftwareBackend,
+};
+pub use self::bounds::{Aabb, BoundingSphere};
+pub use self::culling::{Culling, CullingStats, Frustum};
+pub use self::geometry::{
+    flat_normals, generate_tangents, smooth_normals, triangulate, unindex,
+    Normals, SMOOTHING_ANGLE,
+};
+pub use self::light::{bind_lights, Light, LightKind, LightSource, MAX_LIGHTS};
+pub use self::material::{AlphaMode, Material, MaterialTexture};
 pub use self::mesh::Model;
+pub use self::mesh::{Mesh, Meshes, Node};
+pub use self::morph::{
+    bind_morph_weights, morph_vertices, MorphTarget, MAX_MORPH_TARGETS,
+};
+pub use self::object::{ObjectTypes, SceneObject};
 pub use self::primitive::Primitive;
-pub use self::vertex::Vertex;
+pub use self::ray::{Ray, RayHit};
+pub use self::shader::{Shader, ShaderError, ShaderStage};
+pub use self::skin::{bind_joints, Skin};
+pub use self::texture::Texture;
+pub use self::vertex::{Attribute, BufferLayout, Vertex, VertexLayout};
@@ -0,0 +1,249 @@
+use backend::RenderBackend;
+use culling::Culling;
+use fuel_camera::{Projection, View};
+use fuel_types::{Rotation, Transform};
+use na::{Matrix4, Vector3, Vector4};
+use object::{ObjectTypes, SceneObject};
+use ray::{Ray, RayHit};
+use shader::{Shader, ShaderError};
+
+/// Lights sent to the shaders, the others are ignored.
+pub const MAX_LIGHTS: usize = 8;
+
+// Lit scenes without any light use this one
+const DEFAULT_LIGHT_DIRECTION: (f32, f32, f32) = (-0.5, -1., -0.3);
+
+/// Shape of the light, angles are in radians from the
+/// direction of the light.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+    Directional,
+    Point,
+    Spot {
+        inner_cone_angle: f32,
+        outer_cone_angle: f32,
+    },
+}
+
+impl LightKind {
+    fn to_uniform(self) -> i32 {
+        match self {
+            LightKind::Directional => 0,
+            LightKind::Point => 1,
+            LightKind::Spot { .. } => 2,
+        }
+    }
+}
+
+/// Light of the scene, following KHR_lights_punctual. Lights
+/// shine along their -z axis, from the origin of their transform.
+///
+/// Intensity is in lux for directional lights, in candela for
+/// the others. Without range, a light reaches the infinity.
+#[derive(Clone, Debug)]
+pub struct Light {
+    pub name: Option<String>,
+    pub kind: LightKind,
+    pub color: Vector3<f32>,
+    pub intensity: f32,
+    pub range: Option<f32>,
+    pub enabled: bool,
+    transform: Transform,
+}
+
+impl Light {
+    pub fn new(kind: LightKind, color: Vector3<f32>, intensity: f32) -> Self {
+        Light {
+            name: None,
+            kind,
+            color,
+            intensity,
+            range: None,
+            enabled: true,
+            transform: Transform::default(),
+        }
+    }
+
+    pub fn directional(color: Vector3<f32>, intensity: f32) -> Self {
+        Light::new(LightKind::Directional, color, intensity)
+    }
+
+    pub fn point(color: Vector3<f32>, intensity: f32) -> Self {
+        Light::new(LightKind::Point, color, intensity)
+    }
+
+    /// Light fades out between the inner and the outer angle.
+    pub fn spot(
+        color: Vector3<f32>,
+        intensity: f32,
+        inner_cone_angle: f32,
+        outer_cone_angle: f32,
+    ) -> Self {
+        let kind = LightKind::Spot {
+            inner_cone_angle,
+            outer_cone_angle,
+        };
+        Light::new(kind, color, intensity)
+    }
+
+    pub fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_range(mut self, range: f32) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Point the light towards `direction`, in the space
+    /// of its parent.
+    pub fn set_direction(&mut self, direction: Vector3<f32>) {
+        let up = if direction.cross(&Vector3::y()).norm() > 1e-6 {
+            Vector3::y()
+        } else {
+            Vector3::z()
+        };
+        self.transform.rotation = Rotation::look_at(direction, up);
+    }
+
+    /// The light placed by the matrix of its transform in the world.
+    pub fn to_source(&self, world: &Matrix4<f32>) -> LightSource {
+        let position = world * Vector4::new(0., 0., 0., 1.);
+        let direction = world * Vector4::new(0., 0., -1., 0.);
+        let direction = Vector3::new(direction.x, direction.y, direction.z);
+
+        LightSource {
+            kind: self.kind,
+            color: self.color,
+            intensity: self.intensity,
+            range: self.range,
+            position: Vector3::new(position.x, position.y, position.z)
+                / position.w,
+            direction: if direction.norm() > 0. {
+                direction.normalize()
+            } else {
+                -Vector3::z()
+            },
+        }
+    }
+}
+
+impl SceneObject for Light {
+    fn set_position(&mut self, x: f32, y: f32, z: f32) {
+        self.transform.position.set(x, y, z);
+    }
+
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn get_type(&self) -> ObjectTypes {
+        ObjectTypes::LIGHT
+    }
+
+    // Lights are not drawn, they only light the others
+    fn render(
+        &mut self,
+        _backend: &mut RenderBackend,
+        _proj: Projection,
+        _view: View,
+        _world: &Matrix4<f32>,
+        _lights: &[LightSource],
+        _culling: &mut Culling,
+    ) -> Result<(), ShaderError> {
+        Ok(())
+    }
+
+    fn set_color(&self, _name: &str, _color: Vector3<f32>) {}
+    fn set_scale(&mut self, x: f32, y: f32, z: f32) {
+        self.transform.scale.set(x, y, z);
+    }
+
+    fn get_light_sources(&self, world: &Matrix4<f32>) -> Vec<LightSource> {
+        if self.enabled {
+            vec![self.to_source(world)]
+        } else {
+            vec![]
+        }
+    }
+
+    fn intersect_ray(
+        &self,
+        _ray: &Ray,
+        _world: &Matrix4<f32>,
+    ) -> Option<RayHit> {
+        None
+    }
+
+    fn release(&mut self) {}
+}
+
+/// Light in world space, as given to the shaders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightSource {
+    pub kind: LightKind,
+    pub color: Vector3<f32>,
+    pub intensity: f32,
+    pub range: Option<f32>,
+    pub position: Vector3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Default for LightSource {
+    fn default() -> Self {
+        let (x, y, z) = DEFAULT_LIGHT_DIRECTION;
+        LightSource {
+            kind: LightKind::Directional,
+            color: Vector3::repeat(1.),
+            intensity: 1.,
+            range: None,
+            position: Vector3::zeros(),
+            direction: Vector3::new(x, y, z).normalize(),
+        }
+    }
+}
+
+/// Send the lights to the PBR shader, which must be in use.
+/// Without lights, the default one is used.
+pub fn bind_lights(
+    backend: &mut RenderBackend,
+    shader: &Shader,
+    lights: &[LightSource],
+) {
+    let default = [LightSource::default()];
+    let lights = if lights.is_empty() { &default } else { lights };
+    let count = lights.len().min(MAX_LIGHTS);
+    shader.set_int(backend, "light_count", count as i32);
+
+    for (index, light) in lights.iter().take(count).enumerate() {
+        let name = |field: &str| format!("lights[{}].{}", index, field);
+        // Zero means no range in the shader
+        let range = light.range.unwrap_or(0.);
+        let (inner_cone_cos, outer_cone_cos) = match light.kind {
+            LightKind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            } => (inner_cone_angle.cos(), outer_cone_angle.cos()),
+            _ => (1., 0.),
+        };
+
+        shader.set_int(backend, &name("kind"), light.kind.to_uniform());
+        shader.set_color(backend, &name("color"), light.color);
+        shader.set_float(backend, &name("intensity"), light.intensity);
+        shader.set_float(backend, &name("range"), range);
+        shader.set_vec3(backend, &name("position"), light.position);
+        shader.set_vec3(backend, &name("direction"), light.direction);
+        shader.set_float(backend, &name("inner_cone_cos"), inner_cone_cos);
+        shader.set_float(backend, &name("outer_cone_cos"), outer_cone_cos);
+    }
+}
@@ -0,0 +1,147 @@
+use backend::RenderBackend;
+use na::{Vector3, Vector4};
+use shader::Shader;
+use texture::Texture;
+
+/// How the alpha of the base color is interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlphaMode {
+    Opaque,
+    Mask,
+    Blend,
+}
+
+impl Default for AlphaMode {
+    fn default() -> Self {
+        AlphaMode::Opaque
+    }
+}
+
+impl AlphaMode {
+    /// Value of the `alpha_mode` uniform.
+    pub fn to_uniform(self) -> i32 {
+        match self {
+            AlphaMode::Opaque => 0,
+            AlphaMode::Mask => 1,
+            AlphaMode::Blend => 2,
+        }
+    }
+}
+
+/// Texture used by a material, `index` points to one of
+/// the textures of the Model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaterialTexture {
+    pub index: usize,
+    pub tex_coord: u32,
+}
+
+/// PBR metallic-roughness material, as described by glTF.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: Option<String>,
+    pub base_color_factor: Vector4<f32>,
+    pub base_color_texture: Option<MaterialTexture>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub metallic_roughness_texture: Option<MaterialTexture>,
+    pub normal_texture: Option<MaterialTexture>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<MaterialTexture>,
+    pub occlusion_strength: f32,
+    pub emissive_texture: Option<MaterialTexture>,
+    pub emissive_factor: Vector3<f32>,
+    pub alpha_mode: AlphaMode,
+    pub alpha_cutoff: f32,
+    pub double_sided: bool,
+}
+
+// Default values from the glTF specification
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: None,
+            base_color_factor: Vector4::new(1., 1., 1., 1.),
+            base_color_texture: None,
+            metallic_factor: 1.,
+            roughness_factor: 1.,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            normal_scale: 1.,
+            occlusion_texture: None,
+            occlusion_strength: 1.,
+            emissive_texture: None,
+            emissive_factor: Vector3::zeros(),
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+impl Material {
+    /// Textures with the name used in the shader, in
+    /// the order of their texture unit.
+    pub fn textures(&self) -> [(&'static str, Option<MaterialTexture>); 5] {
+        [
+            ("base_color", self.base_color_texture),
+            ("metallic_roughness", self.metallic_roughness_texture),
+            ("normal", self.normal_texture),
+            ("occlusion", self.occlusion_texture),
+            ("emissive", self.emissive_texture),
+        ]
+    }
+
+    /// Send the material to the PBR shader, which must be in use.
+    pub fn bind(
+        &self,
+        backend: &mut RenderBackend,
+        shader: &Shader,
+        textures: &mut [Texture],
+    ) {
+        shader.set_vec4(backend, "base_color_factor", self.base_color_factor);
+        shader.set_float(backend, "metallic_factor", self.metallic_factor);
+        shader.set_float(backend, "roughness_factor", self.roughness_factor);
+        shader.set_float(backend, "normal_scale", self.normal_scale);
+        shader.set_float(
+            backend,
+            "occlusion_strength",
+            self.occlusion_strength,
+        );
+        shader.set_color(backend, "emissive_factor", self.emissive_factor);
+        shader.set_int(backend, "alpha_mode", self.alpha_mode.to_uniform());
+        shader.set_float(backend, "alpha_cutoff", self.alpha_cutoff);
+
+        for (unit, (name, material_texture)) in
+            self.textures().iter().enumerate()
+        {
+            let texture = material_texture.and_then(|material_texture| {
+                textures
+                    .get_mut(material_texture.index)
+                    .map(|texture| (material_texture, texture))
+            });
+
+            let has_texture = format!("has_{}_texture", name);
+            match texture {
+                Some((material_texture, texture)) => {
+                    texture.bind(backend, unit as u32);
+                    shader.set_bool(backend, &has_texture, true);
+                    shader.set_int(
+                        backend,
+                        &format!("{}_texture", name),
+                        unit as i32,
+                    );
+                    shader.set_int(
+                        backend,
+                        &format!("{}_tex_coord", name),
+                        material_texture.tex_coord as i32,
+                    );
+                }
+                None => shader.set_bool(backend, &has_texture, false),
+            }
+        }
+
+        backend.set_face_culling(!self.double_sided);
+        backend.set_blending(self.alpha_mode == AlphaMode::Blend);
+    }
+}
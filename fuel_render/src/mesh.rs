@@ -1,23 +1,71 @@
+use animation::{Animation, AnimationPlayer, Pose};
+use backend::RenderBackend;
+use bounds::Aabb;
+use culling::Culling;
 use fuel_camera::{Projection, View};
-use fuel_core::{ObjectTypes, SceneObject};
 use fuel_types::Transform;
-use na::Vector3;
+use light::{Light, LightSource};
+use na::{Matrix4, Vector3, Vector4};
+use object::{ObjectTypes, SceneObject};
 use primitive::Primitive;
+use ray::{Ray, RayHit};
+use shader::ShaderError;
+use skin::Skin;
+use texture::Texture;
 
 pub type Meshes = Vec<Mesh>;
 
 /// Model contains a list of Mesh that contains
 /// a list of Primitive that contains a list of Vertex.
 ///
-/// When we create a new Primitive, we setup automatically
-/// openGL with his vertices. We don't keep it in memory.
+/// A new Primitive keeps its vertices in memory, they are sent
+/// to the graphic card the first time it is rendered.
 ///
 /// Transform contains the position, scale and the rotation
-/// of a Model.  When we update a Model, we also update the shader
-/// inside the primitives.
+/// of a Model, relative to its parent in the scene.  When we
+/// update a Model, we also update the shader inside the primitives.
+///
+/// Materials of the primitives refer to the textures of their Model.
+///
+/// Meshes are placed by a tree of nodes, a mesh used by several
+/// nodes is drawn once for each of them. Lights of the Model are
+/// placed the same way, they light the whole scene.
+///
+/// Animations move the nodes from their rest pose, the one they
+/// were given, as the scene is updated. They are played one at a
+/// time, or two blended together. Skinned meshes follow the
+/// nodes used as joints by their skin.
+///
+/// Morph targets are weighted for each node, by the weights of
+/// the node or by default the ones of its mesh. Animations can
+/// change them too.
 pub struct Model {
     transform: Transform,
     meshes: Meshes,
+    textures: Vec<Texture>,
+    lights: Vec<Light>,
+    skins: Vec<Skin>,
+    animations: Vec<Animation>,
+    player: AnimationPlayer,
+    nodes: Vec<Node>,
+    rest_pose: Pose,
+    roots: Vec<usize>,
+}
+
+/// Node of a Model, its transform is relative to the parent
+/// node, or to the Model for roots. `mesh`, `light`, `skin`
+/// and `children` are indices in the Model.
+///
+/// Empty `weights` use the ones of the mesh.
+#[derive(Clone, Debug, Default)]
+pub struct Node {
+    pub name: Option<String>,
+    pub transform: Transform,
+    pub mesh: Option<usize>,
+    pub light: Option<usize>,
+    pub skin: Option<usize>,
+    pub weights: Vec<f32>,
+    pub children: Vec<usize>,
 }
 
 impl SceneObject for Model {
@@ -25,37 +73,513 @@ impl SceneObject for Model {
         self.transform.position.set(x, y, z);
     }
 
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
     fn get_type(&self) -> ObjectTypes {
         ObjectTypes::MODEL
     }
 
-    fn render(&self, proj: Projection, view: View) {
-        self.meshes.iter().for_each(|mesh| {
-            mesh.draw(proj, view, &self.transform);
-        });
+    fn render(
+        &mut self,
+        backend: &mut RenderBackend,
+        proj: Projection,
+        view: View,
+        world: &Matrix4<f32>,
+        lights: &[LightSource],
+        culling: &mut Culling,
+    ) -> Result<(), ShaderError> {
+        let textures = &mut self.textures;
+        let meshes = &mut self.meshes;
+        let skins = &self.skins;
+        let matrices = get_node_matrices(&self.nodes, &self.roots, world);
+        let mut node_matrices = vec![None; self.nodes.len()];
+        for (node, model) in &matrices {
+            node_matrices[*node] = Some(*model);
+        }
+
+        for (node, model) in &matrices {
+            let node = &self.nodes[*node];
+            let mesh = match node.mesh.and_then(|mesh| meshes.get_mut(mesh)) {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let joint_matrices = node
+                .skin
+                .and_then(|skin| skins.get(skin))
+                .map(|skin| skin.get_joint_matrices(model, &node_matrices))
+                .unwrap_or_default();
+            let morph_weights = if node.weights.is_empty() {
+                mesh.weights.clone()
+            } else {
+                node.weights.clone()
+            };
+
+            mesh.draw(
+                backend,
+                proj,
+                view,
+                model,
+                lights,
+                &joint_matrices,
+                &morph_weights,
+                textures,
+                culling,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, dt: f32) {
+        if self.player.animation.is_none() && self.player.blend.is_none() {
+            return;
+        }
+
+        self.player.advance(dt, &self.animations);
+        let pose = self.player.get_pose(&self.animations, &self.rest_pose);
+        let poses = pose.transforms.into_iter().zip(pose.weights);
+        for (node, (transform, weights)) in self.nodes.iter_mut().zip(poses) {
+            node.transform = transform;
+            node.weights = weights;
+        }
+    }
+
+    fn as_model(&self) -> Option<&Model> {
+        Some(self)
+    }
+
+    fn as_model_mut(&mut self) -> Option<&mut Model> {
+        Some(self)
     }
 
     fn set_color(&self, _name: &str, _color: Vector3<f32>) {}
-    fn set_scale(&mut self, _scale: f32) {}
+    fn set_scale(&mut self, x: f32, y: f32, z: f32) {
+        self.transform.scale.set(x, y, z);
+    }
+
+    fn get_light_sources(&self, world: &Matrix4<f32>) -> Vec<LightSource> {
+        get_node_matrices(&self.nodes, &self.roots, world)
+            .into_iter()
+            .filter_map(|(node, model)| {
+                let light = self.lights.get(self.nodes[node].light?)?;
+                if light.enabled {
+                    Some(light.to_source(&model))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn release(&mut self) {
+        for mesh in &mut self.meshes {
+            mesh.release();
+        }
+        for texture in &mut self.textures {
+            texture.release();
+        }
+    }
+
+    fn intersect_ray(&self, ray: &Ray, world: &Matrix4<f32>) -> Option<RayHit> {
+        let hits = self.get_mesh_matrices(world).into_iter().filter_map(
+            |(mesh, model)| {
+                let primitives = self.meshes.get(mesh)?.get_primitives();
+                let inverse = model.try_inverse()?;
+                // The local ray keeps the distances of the world
+                let local_ray = ray.transform(&inverse);
+                let hit = RayHit::closest(primitives.iter().filter_map(
+                    |primitive| primitive.intersect_ray(&local_ray),
+                ))?;
+
+                let normal = hit.normal;
+                let normal = inverse.transpose()
+                    * Vector4::new(normal.x, normal.y, normal.z, 0.);
+                Some(RayHit {
+                    point: ray.point_at(hit.distance),
+                    normal: Vector3::new(normal.x, normal.y, normal.z)
+                        .normalize(),
+                    distance: hit.distance,
+                })
+            },
+        );
+
+        RayHit::closest(hits)
+    }
 }
 
 impl Model {
-    pub fn new(transform: Transform, meshes: Meshes) -> Self {
-        Self { transform, meshes }
+    /// Every mesh is drawn once, at the origin of the Model.
+    pub fn new(
+        transform: Transform,
+        meshes: Meshes,
+        textures: Vec<Texture>,
+    ) -> Self {
+        let nodes: Vec<Node> = (0..meshes.len())
+            .map(|mesh| Node {
+                mesh: Some(mesh),
+                ..Default::default()
+            })
+            .collect();
+        let roots = (0..nodes.len()).collect();
+        let rest_pose = get_pose(&nodes);
+
+        Self {
+            transform,
+            meshes,
+            textures,
+            lights: vec![],
+            skins: vec![],
+            animations: vec![],
+            player: AnimationPlayer::default(),
+            nodes,
+            rest_pose,
+            roots,
+        }
+    }
+
+    /// Lights used by the nodes.
+    pub fn with_lights(mut self, lights: Vec<Light>) -> Self {
+        self.lights = lights;
+        self
+    }
+
+    /// Skins used by the nodes.
+    pub fn with_skins(mut self, skins: Vec<Skin>) -> Self {
+        self.skins = skins;
+        self
+    }
+
+    /// Animations of the nodes, none is played at first.
+    pub fn with_animations(mut self, animations: Vec<Animation>) -> Self {
+        self.animations = animations;
+        self
+    }
+
+    /// Replace the nodes placing the meshes, `roots` are
+    /// indices in `nodes`.
+    pub fn with_nodes(mut self, nodes: Vec<Node>, roots: Vec<usize>) -> Self {
+        self.rest_pose = get_pose(&nodes);
+        self.nodes = nodes;
+        self.roots = roots;
+        self
+    }
+
+    pub fn get_nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn get_roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    pub fn get_meshes(&self) -> &[Mesh] {
+        &self.meshes
+    }
+
+    pub fn get_textures(&self) -> &[Texture] {
+        &self.textures
+    }
+
+    pub fn get_lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn get_lights_mut(&mut self) -> &mut [Light] {
+        &mut self.lights
+    }
+
+    pub fn get_skins(&self) -> &[Skin] {
+        &self.skins
+    }
+
+    pub fn get_animations(&self) -> &[Animation] {
+        &self.animations
+    }
+
+    /// Index of the first animation with this name.
+    pub fn find_animation(&self, name: &str) -> Option<usize> {
+        self.animations.iter().position(|animation| {
+            animation.name.as_ref().map(|n| &n[..]) == Some(name)
+        })
+    }
+
+    pub fn get_player(&self) -> &AnimationPlayer {
+        &self.player
+    }
+
+    /// Play one of the animations alone from its start, returns
+    /// false if the Model doesn't have it.
+    pub fn play(&mut self, animation: usize) -> bool {
+        if animation >= self.animations.len() {
+            return false;
+        }
+        self.player.play(animation);
+        true
+    }
+
+    /// Mix an animation with the one played, a `weight` of 1
+    /// only shows the new one. Returns false if the Model
+    /// doesn't have it.
+    pub fn blend(&mut self, animation: usize, weight: f32) -> bool {
+        if animation >= self.animations.len() {
+            return false;
+        }
+        self.player.blend(animation, weight);
+        true
+    }
+
+    pub fn set_blend_weight(&mut self, weight: f32) {
+        self.player.set_blend_weight(weight);
+    }
+
+    /// Fade to another animation in `duration` seconds, returns
+    /// false if the Model doesn't have it.
+    pub fn cross_fade(&mut self, animation: usize, duration: f32) -> bool {
+        if animation >= self.animations.len() {
+            return false;
+        }
+        self.player.cross_fade(animation, duration);
+        true
+    }
+
+    /// Weights of the morph targets of the mesh of a node, they
+    /// are kept until an animation changes them. Returns false
+    /// if the Model doesn't have the node.
+    pub fn set_morph_weights(&mut self, node: usize, weights: &[f32]) -> bool {
+        match self.nodes.get_mut(node) {
+            Some(node) => node.weights = weights.to_vec(),
+            None => return false,
+        }
+        if let Some(rest) = self.rest_pose.weights.get_mut(node) {
+            *rest = weights.to_vec();
+        }
+        true
+    }
+
+    /// Weights of the morph targets of the mesh of a node.
+    pub fn get_morph_weights(&self, node: usize) -> &[f32] {
+        let node = match self.nodes.get(node) {
+            Some(node) => node,
+            None => return &[],
+        };
+        match node.mesh.and_then(|mesh| self.meshes.get(mesh)) {
+            Some(mesh) if node.weights.is_empty() => &mesh.weights,
+            _ => &node.weights,
+        }
+    }
+
+    /// Jump to a time of the animation played, in seconds.
+    pub fn seek(&mut self, time: f32) {
+        self.player.time = time;
+    }
+
+    pub fn pause(&mut self) {
+        self.player.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.player.resume();
+    }
+
+    /// Stop the animation, nodes keep their current pose.
+    pub fn stop(&mut self) {
+        self.player.stop();
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.player.set_looping(looping);
+    }
+
+    /// Speed of the animation, 1 by default. Negative speeds
+    /// play it backward.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.player.set_speed(speed);
+    }
+
+    /// Every mesh drawn by the nodes with its matrix, relative
+    /// to `world`. A mesh comes once for each node using it.
+    pub fn get_mesh_matrices(
+        &self,
+        world: &Matrix4<f32>,
+    ) -> Vec<(usize, Matrix4<f32>)> {
+        get_mesh_matrices(&self.nodes, &self.roots, world)
+    }
+
+    /// Box around the primitives drawn by the nodes, in the
+    /// space of the Model.
+    pub fn get_bounding_box(&self) -> Option<Aabb> {
+        let meshes = &self.meshes;
+        self.get_mesh_matrices(&Matrix4::identity())
+            .into_iter()
+            .flat_map(|(mesh, model)| {
+                let primitives = meshes
+                    .get(mesh)
+                    .map(|mesh| mesh.get_primitives())
+                    .unwrap_or(&[]);
+                primitives.iter().filter_map(move |primitive| {
+                    let aabb = primitive.get_bounding_box()?;
+                    Some(aabb.transform(&model))
+                })
+            })
+            .fold(None, |union: Option<Aabb>, aabb| {
+                Some(union.map_or(aabb, |union| union.union(&aabb)))
+            })
+    }
+}
+
+fn get_pose(nodes: &[Node]) -> Pose {
+    Pose {
+        transforms: nodes.iter().map(|node| node.transform.clone()).collect(),
+        weights: nodes.iter().map(|node| node.weights.clone()).collect(),
+    }
+}
+
+// Depth first, so parents are drawn before their children
+fn get_mesh_matrices(
+    nodes: &[Node],
+    roots: &[usize],
+    world: &Matrix4<f32>,
+) -> Vec<(usize, Matrix4<f32>)> {
+    get_node_matrices(nodes, roots, world)
+        .into_iter()
+        .filter_map(|(node, model)| Some((nodes[node].mesh?, model)))
+        .collect()
+}
+
+// Every node with its matrix, parents before their children.
+// Nodes are visited once, so cycles of invalid files end.
+fn get_node_matrices(
+    nodes: &[Node],
+    roots: &[usize],
+    world: &Matrix4<f32>,
+) -> Vec<(usize, Matrix4<f32>)> {
+    let mut matrices = vec![];
+    let mut visited = vec![false; nodes.len()];
+    let mut stack: Vec<(usize, Matrix4<f32>)> =
+        roots.iter().rev().map(|root| (*root, *world)).collect();
+    while let Some((index, parent)) = stack.pop() {
+        let node = match nodes.get(index) {
+            Some(node) if !visited[index] => node,
+            _ => continue,
+        };
+        visited[index] = true;
+        let model = parent * node.transform.to_matrix();
+
+        matrices.push((index, model));
+        stack.extend(node.children.iter().rev().map(|child| (*child, model)));
     }
+    matrices
 }
 
+/// `weights` are the default weights of the morph targets of
+/// the primitives.
 pub struct Mesh {
     primitives: Vec<Primitive>,
+    weights: Vec<f32>,
 }
 
 impl Mesh {
     pub fn new(primitives: Vec<Primitive>) -> Self {
-        Self { primitives }
+        Self {
+            primitives,
+            weights: vec![],
+        }
     }
-    fn draw(&self, proj: Projection, view: View, transform: &Transform) {
-        self.primitives.iter().for_each(|primitive| {
-            primitive.shader_config(proj, view, transform);
-        })
+
+    pub fn with_weights(mut self, weights: Vec<f32>) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn get_weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn get_primitives(&self) -> &[Primitive] {
+        &self.primitives
+    }
+
+    pub fn release(&mut self) {
+        for primitive in &mut self.primitives {
+            primitive.release();
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw(
+        &mut self,
+        backend: &mut RenderBackend,
+        proj: Projection,
+        view: View,
+        model: &Matrix4<f32>,
+        lights: &[LightSource],
+        joint_matrices: &[Matrix4<f32>],
+        morph_weights: &[f32],
+        textures: &mut [Texture],
+        culling: &mut Culling,
+    ) -> Result<(), ShaderError> {
+        for primitive in &mut self.primitives {
+            // Bounds of the bind pose don't hold once skinned
+            let visible =
+                if primitive.is_skinned() && !joint_matrices.is_empty() {
+                    culling.should_draw(None, None, model)
+                } else {
+                    culling.should_draw(
+                        primitive.get_bounding_box(),
+                        primitive.get_bounding_sphere(),
+                        model,
+                    )
+                };
+            if visible {
+                primitive.shader_config(
+                    backend,
+                    proj,
+                    view,
+                    model,
+                    lights,
+                    joint_matrices,
+                    morph_weights,
+                    textures,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(x: f32, children: Vec<usize>) -> Node {
+        let mut node = Node {
+            children,
+            ..Default::default()
+        };
+        node.transform.position.set(x, 0., 0.);
+        node
+    }
+
+    #[test]
+    fn node_matrices_compose_parents_first() {
+        let nodes =
+            vec![node(1., vec![2]), node(10., vec![]), node(2., vec![])];
+        let matrices = get_node_matrices(&nodes, &[0, 1], &Matrix4::identity());
+        let order: Vec<usize> =
+            matrices.iter().map(|(node, _)| *node).collect();
+        assert_eq!(order, vec![0, 2, 1]);
+        assert!((matrices[1].1[(0, 3)] - 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn node_cycles_are_visited_once() {
+        // 0 -> 1 -> 2 -> 0, and 1 is its own child
+        let nodes =
+            vec![node(1., vec![1]), node(1., vec![2, 1]), node(1., vec![0])];
+        let matrices = get_node_matrices(&nodes, &[0, 0], &Matrix4::identity());
+        let order: Vec<usize> =
+            matrices.iter().map(|(node, _)| *node).collect();
+        assert_eq!(order, vec![0, 1, 2]);
     }
 }
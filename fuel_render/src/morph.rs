@@ -0,0 +1,62 @@
+use backend::RenderBackend;
+use na::{Vector3, Vector4};
+use shader::Shader;
+use vertex::Vertex;
+
+/// Targets morphed by the vertex shader, primitives with more
+/// of them are morphed on the CPU.
+pub const MAX_MORPH_TARGETS: usize = 4;
+
+/// Shape a primitive is pulled towards, like a smile. Each list
+/// is empty, or has the difference added to each vertex when the
+/// weight of the target is 1.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MorphTarget {
+    pub positions: Vec<Vector3<f32>>,
+    pub normals: Vec<Vector3<f32>>,
+    pub tangents: Vec<Vector3<f32>>,
+}
+
+/// Vertices moved by the targets, scaled by their weights.
+/// Missing weights are 0.
+pub fn morph_vertices(
+    vertices: &[Vertex],
+    targets: &[MorphTarget],
+    weights: &[f32],
+) -> Vec<Vertex> {
+    let mut morphed = vertices.to_vec();
+    let weighted = targets.iter().zip(weights).filter(|(_, w)| **w != 0.);
+    for (target, weight) in weighted {
+        for (vertex, delta) in morphed.iter_mut().zip(&target.positions) {
+            vertex.position += delta * *weight;
+        }
+        for (vertex, delta) in morphed.iter_mut().zip(&target.normals) {
+            vertex.normal += delta * *weight;
+        }
+        for (vertex, delta) in morphed.iter_mut().zip(&target.tangents) {
+            let delta = delta * *weight;
+            vertex.tangent += Vector4::new(delta.x, delta.y, delta.z, 0.);
+        }
+    }
+
+    for vertex in &mut morphed {
+        if vertex.normal.norm() > 0. {
+            vertex.normal = vertex.normal.normalize();
+        }
+    }
+    morphed
+}
+
+/// Send the weights of the targets to the shader, which must
+/// be in use.
+pub fn bind_morph_weights(
+    backend: &mut RenderBackend,
+    shader: &Shader,
+    weights: &[f32],
+) {
+    for index in 0..MAX_MORPH_TARGETS {
+        let weight = weights.get(index).cloned().unwrap_or(0.);
+        let name = format!("morph_weights[{}]", index);
+        shader.set_float(backend, &name, weight);
+    }
+}
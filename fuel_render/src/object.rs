@@ -0,0 +1,64 @@
+use backend::RenderBackend;
+use culling::Culling;
+use fuel_camera::{Projection, View};
+use fuel_types::Transform;
+use light::LightSource;
+use mesh::Model;
+use na::{Matrix4, Vector3};
+use ray::{Ray, RayHit};
+use shader::ShaderError;
+
+// Enum of all type that an object
+// can have
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectTypes {
+    POLYGON,
+    LIGHT,
+    MODEL,
+}
+
+// Object to put in the scene
+// Position in the spacial scene with render method
+// is needed
+//
+// Transforms are local to the parent of the object,
+// the scene gives the world matrix when rendering,
+// with the lights of the scene and the culling deciding
+// what is drawn. Rendering fails when a shader can't
+// be setup
+pub trait SceneObject {
+    fn set_position(&mut self, f32, f32, f32);
+    fn get_transform(&self) -> &Transform;
+    fn get_type(&self) -> ObjectTypes;
+    fn render(
+        &mut self,
+        &mut RenderBackend,
+        Projection,
+        View,
+        &Matrix4<f32>,
+        &[LightSource],
+        &mut Culling,
+    ) -> Result<(), ShaderError>;
+    fn set_color(&self, name: &str, Vector3<f32>);
+    fn set_scale(&mut self, f32, f32, f32);
+    // Lights given to the scene, with the world matrix
+    fn get_light_sources(&self, &Matrix4<f32>) -> Vec<LightSource> {
+        vec![]
+    }
+    // Move the object forward in time by `dt` seconds, like
+    // its animations
+    fn update(&mut self, _dt: f32) {}
+    // The object itself when it is a Model, so its animations
+    // can be played once it is in the scene
+    fn as_model(&self) -> Option<&Model> {
+        None
+    }
+    fn as_model_mut(&mut self) -> Option<&mut Model> {
+        None
+    }
+    // Closest hit of a world space ray, with the world matrix
+    fn intersect_ray(&self, &Ray, &Matrix4<f32>) -> Option<RayHit>;
+    // Queue what was sent to the graphic card for deletion,
+    // it is sent again by the next render
+    fn release(&mut self);
+}
@@ -1,181 +1,404 @@
+use backend::{DeletionQueue, DrawMode, RenderBackend, Resource, TextureId};
+use bounds::{Aabb, BoundingSphere};
 use fuel_camera::{Projection, View};
-use fuel_types::{Position, Transform, EBO, VAO, VBO};
-use gl;
-use na::Isometry3;
-use shader::Shader;
-use std::mem;
-use std::os::raw::c_void;
-use std::ptr;
-use vertex::Vertex;
-
-macro_rules! offset_of {
-    ($ty:ty, $field:ident) => {
-        &(*(0 as *const $ty)).$field as *const _ as usize
-    };
-}
+use fuel_types::{EBO, VAO, VBO};
+use light::{bind_lights, LightSource};
+use material::Material;
+use morph::{
+    bind_morph_weights, morph_vertices, MorphTarget, MAX_MORPH_TARGETS,
+};
+use na::{Matrix4, Vector3};
+use ray::{Ray, RayHit};
+use shader::{Shader, ShaderError};
+use skin::bind_joints;
+use texture::Texture;
+use vertex::{Attribute, BufferLayout, Vertex, VertexLayout};
 
+/// Vertices and indices are uploaded through the backend used to
+/// render them by the first draw. They are kept on the CPU side,
+/// to be picked, morphed or exported.
+///
+/// Only the attributes given at creation are uploaded, following
+/// the vertex layout of the primitive.
+///
+/// Primitives are drawn with the PBR shader, using their material.
+/// Skinned ones, with joints and weights, use the skinning vertex
+/// shader instead.
+///
+/// Morph targets are morphed by the vertex shader when they
+/// fit in its attributes, without tangents. Otherwise the
+/// vertices are morphed on the CPU and uploaded again when the
+/// weights change.
+///
+/// Vertices are drawn as triangles by default, point clouds and
+/// wireframes can be drawn with another mode.
+///
+/// Bounding volumes are in model space, computed from the
+/// positions of the vertices. They hold the targets with a
+/// weight up to 1.
+///
+/// What was uploaded is deleted when the primitive is dropped.
 pub struct Primitive {
     pub vao: VAO,
-    pub vbo: VBO,
-    pub shader: Shader,
+    pub vbos: Vec<VBO>,
+    pub shader: Option<Shader>,
     pub ebo: Option<EBO>,
     pub num_indices: u32,
     pub num_vertices: u32,
+    pub material: Material,
+    draw_mode: DrawMode,
+    layout: VertexLayout,
+    vertices: Vec<Vertex>,
+    indices: Option<Vec<u32>>,
+    bounding_box: Option<Aabb>,
+    bounding_sphere: Option<BoundingSphere>,
+    morph_targets: Vec<MorphTarget>,
+    // Weights of the vertices uploaded, when morphed on the CPU
+    uploaded_weights: Vec<f32>,
+    joint_texture: Option<TextureId>,
+    deletion_queue: Option<DeletionQueue>,
 }
 
 impl Primitive {
-    pub fn new(vertices: &[Vertex], indices: Option<Vec<u32>>) -> Self {
+    pub fn new(
+        vertices: Vec<Vertex>,
+        indices: Option<Vec<u32>>,
+        attributes: &[Attribute],
+    ) -> Self {
         let num_indices = indices.as_ref().map(|i| i.len()).unwrap_or(0) as u32;
+        let (bounding_box, bounding_sphere) = {
+            let positions = vertices.iter().map(|vertex| &vertex.position);
+            (
+                Aabb::from_points(positions.clone()),
+                BoundingSphere::from_points(positions),
+            )
+        };
 
-        let mut primitive = Primitive {
+        Primitive {
             vao: 0,
-            vbo: 0,
+            vbos: vec![],
             ebo: None,
             num_indices,
             num_vertices: vertices.len() as u32,
-            shader: Shader::new("cube"),
-        };
+            shader: None,
+            material: Material::default(),
+            draw_mode: DrawMode::Triangles,
+            layout: VertexLayout::new(attributes, BufferLayout::default()),
+            vertices,
+            indices,
+            bounding_box,
+            bounding_sphere,
+            morph_targets: vec![],
+            uploaded_weights: vec![],
+            joint_texture: None,
+            deletion_queue: None,
+        }
+    }
+
+    /// Choose how the vertices are spread over vertex buffers.
+    /// Has to be called before the primitive is setup.
+    pub fn with_buffer_layout(mut self, buffer_layout: BufferLayout) -> Self {
+        self.layout =
+            VertexLayout::new(&self.layout.attributes(), buffer_layout);
+        self
+    }
+
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    pub fn with_draw_mode(mut self, draw_mode: DrawMode) -> Self {
+        self.draw_mode = draw_mode;
+        self
+    }
+
+    pub fn get_draw_mode(&self) -> DrawMode {
+        self.draw_mode
+    }
+
+    /// Use a known box instead of the one of the vertices,
+    /// like the bounds given by a file.
+    pub fn with_bounding_box(mut self, bounding_box: Aabb) -> Self {
+        self.bounding_box = Some(bounding_box);
+        self
+    }
+
+    /// Targets of the vertices, each of their lists is empty
+    /// or as long as the vertices. Has to be called before the
+    /// primitive is setup.
+    pub fn with_morph_targets(mut self, targets: Vec<MorphTarget>) -> Self {
+        let morphed: Vec<Vector3<f32>> = targets
+            .iter()
+            .flat_map(|target| {
+                self.vertices
+                    .iter()
+                    .zip(&target.positions)
+                    .map(|(vertex, delta)| vertex.position + delta)
+            })
+            .collect();
+        if let Some(aabb) = Aabb::from_points(&morphed) {
+            self.bounding_box = Some(
+                self.bounding_box
+                    .map_or(aabb, |bounding_box| bounding_box.union(&aabb)),
+            );
+        }
+        self.bounding_sphere = BoundingSphere::from_points(
+            self.vertices
+                .iter()
+                .map(|vertex| &vertex.position)
+                .chain(&morphed),
+        );
+
+        let on_gpu = targets.len() <= MAX_MORPH_TARGETS
+            && targets.iter().all(|target| target.tangents.is_empty());
+        if on_gpu {
+            let mut attributes = self.layout.attributes();
+            for (index, target) in targets.iter().enumerate() {
+                if !target.positions.is_empty() {
+                    attributes.push(Attribute::MorphPosition(index));
+                }
+                if !target.normals.is_empty() {
+                    attributes.push(Attribute::MorphNormal(index));
+                }
+            }
+            self.layout =
+                VertexLayout::new(&attributes, self.layout.buffer_layout);
+        }
+
+        self.morph_targets = targets;
+        self
+    }
+
+    pub fn get_vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
 
-        unsafe {
-            primitive.setup(&vertices, indices);
+    pub fn get_indices(&self) -> Option<&[u32]> {
+        self.indices.as_ref().map(|indices| &indices[..])
+    }
+
+    pub fn get_morph_targets(&self) -> &[MorphTarget] {
+        &self.morph_targets
+    }
+
+    /// Targets are morphed by the vertex shader.
+    pub fn morphs_on_gpu(&self) -> bool {
+        self.layout
+            .attributes()
+            .iter()
+            .any(|attribute| match attribute {
+                Attribute::MorphPosition(_) | Attribute::MorphNormal(_) => true,
+                _ => false,
+            })
+    }
+
+    pub fn get_layout(&self) -> &VertexLayout {
+        &self.layout
+    }
+
+    pub fn get_bounding_box(&self) -> Option<&Aabb> {
+        self.bounding_box.as_ref()
+    }
+
+    pub fn get_bounding_sphere(&self) -> Option<&BoundingSphere> {
+        self.bounding_sphere.as_ref()
+    }
+
+    /// Closest triangle hit by a ray in model space, bounding
+    /// volumes are tested first. Only primitives drawn as
+    /// triangles can be hit.
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<RayHit> {
+        if self.draw_mode != DrawMode::Triangles {
+            return None;
+        }
+        if let Some(sphere) = &self.bounding_sphere {
+            ray.intersects_sphere(sphere)?;
         }
-        primitive
+        if let Some(aabb) = &self.bounding_box {
+            ray.intersects_aabb(aabb)?;
+        }
+
+        let indices = self.indices.as_ref();
+        let count =
+            indices.map_or(self.vertices.len(), |indices| indices.len());
+        let position = |index: usize| {
+            let index = indices.map_or(Some(index), |indices| {
+                indices.get(index).map(|index| *index as usize)
+            })?;
+            self.vertices.get(index).map(|vertex| vertex.position)
+        };
+
+        RayHit::closest((0..count / 3).filter_map(|triangle| {
+            let a = position(triangle * 3)?;
+            let b = position(triangle * 3 + 1)?;
+            let c = position(triangle * 3 + 2)?;
+            ray.intersects_triangle(&a, &b, &c)
+        }))
+    }
+
+    pub fn is_setup(&self) -> bool {
+        self.shader.is_some()
+    }
+
+    /// Vertices follow the joints of a skin.
+    pub fn is_skinned(&self) -> bool {
+        self.layout.has(Attribute::Joints0)
+            && self.layout.has(Attribute::Weights0)
     }
 
+    /// Draw the primitive, `joint_matrices` deform skinned ones
+    /// and `morph_weights` the ones with morph targets. They are
+    /// ignored by the others.
+    ///
+    /// The primitive is setup by its first draw, which fails if
+    /// its shader can't be read, compiled or linked.
+    #[allow(clippy::too_many_arguments)]
     pub fn shader_config(
-        &self,
+        &mut self,
+        backend: &mut RenderBackend,
         proj: Projection,
         view: View,
-        transform: &Transform,
-    ) {
-        self.shader.use_program();
-        // Building model
-        let (position, rotation, scale) = transform.get();
-        let mut model = Isometry3::new(position, rotation).to_homogeneous();
-        model = model.append_scaling(scale);
-        self.shader.set_mvp(proj * view * model);
-
-        unsafe {
-            gl::BindVertexArray(self.vao);
-
-            if self.ebo.is_some() {
-                gl::DrawElements(
-                    gl::TRIANGLES,
-                    self.num_indices as i32,
-                    gl::UNSIGNED_INT,
-                    ptr::null(),
-                );
-            } else {
-                gl::DrawArrays(gl::TRIANGLES, 0, self.num_vertices as i32);
-            }
+        model: &Matrix4<f32>,
+        lights: &[LightSource],
+        joint_matrices: &[Matrix4<f32>],
+        morph_weights: &[f32],
+        textures: &mut [Texture],
+    ) -> Result<(), ShaderError> {
+        if !self.is_setup() {
+            self.setup(backend)?;
+        }
+        let morphs_on_cpu =
+            !self.morph_targets.is_empty() && !self.morphs_on_gpu();
+        if morphs_on_cpu && self.uploaded_weights[..] != *morph_weights {
+            self.upload_morphed(backend, morph_weights);
+        }
+
+        let shader = self.shader.as_ref().expect("Primitive is not setup.");
+        shader.use_program(backend);
+        shader.set_mvp(backend, proj * view * model);
+        shader.set_matrix4(backend, "model", *model);
+        let normal_matrix = model
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity)
+            .transpose();
+        shader.set_matrix4(backend, "normal_matrix", normal_matrix);
+        let camera_position = view
+            .try_inverse()
+            .map(|camera| {
+                Vector3::new(camera[(0, 3)], camera[(1, 3)], camera[(2, 3)])
+            })
+            .unwrap_or_else(Vector3::zeros);
+        shader.set_vec3(backend, "camera_position", camera_position);
 
-            gl::BindVertexArray(self.vao);
-            gl::ActiveTexture(gl::TEXTURE0);
+        bind_lights(backend, shader, lights);
+        if self.is_skinned() {
+            bind_joints(
+                backend,
+                shader,
+                joint_matrices,
+                &mut self.joint_texture,
+            );
+        }
+        if self.morphs_on_gpu() {
+            bind_morph_weights(backend, shader, morph_weights);
+        }
+
+        let has_tangents = self.layout.has(Attribute::Tangent);
+        shader.set_bool(backend, "has_tangents", has_tangents);
+        let has_color_0 = self.layout.has(Attribute::Color0);
+        shader.set_bool(backend, "has_color_0", has_color_0);
+
+        self.material.bind(backend, shader, textures);
+
+        if self.ebo.is_some() {
+            backend.draw_elements(self.vao, self.draw_mode, self.num_indices);
+        } else {
+            backend.draw_arrays(self.vao, self.draw_mode, self.num_vertices);
         }
+        Ok(())
     }
 
-    pub unsafe fn setup(
-        &mut self,
-        vertices: &[Vertex],
-        indices: Option<Vec<u32>>,
-    ) {
-        let mut array: Vec<f32> = vec![];
-        vertices.iter().for_each(|vert| {
-            let pos = vert.position;
-            array.push(pos.x);
-            array.push(pos.y);
-            array.push(pos.z);
-        });
-
-        vertices.iter().for_each(|vertex| {
-            println!("{:?}", vertex.position);
-        });
+    // Morph the vertices and replace the ones uploaded
+    fn upload_morphed(&mut self, backend: &mut RenderBackend, weights: &[f32]) {
+        let vertices =
+            morph_vertices(&self.vertices, &self.morph_targets, weights);
+        for (stream, vbo) in self.layout.streams.iter().zip(&self.vbos) {
+            backend.update_vertex_buffer(*vbo, &stream.pack(&vertices, &[]));
+        }
+        self.uploaded_weights = weights.to_vec();
+    }
 
-        // Generate our array then load it
-        gl::GenVertexArrays(1, &mut self.vao);
-        gl::BindVertexArray(self.vao);
+    /// Queue what was uploaded for deletion, the primitive is
+    /// setup again by its next draw.
+    pub fn release(&mut self) {
+        self.free();
+    }
 
-        // Generate buffer then load it
-        gl::GenBuffers(1, &mut self.vbo);
-        gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+    // Queue what was uploaded for deletion, the shader queues
+    // its own program
+    fn free(&mut self) {
+        self.shader = None;
+        self.uploaded_weights.clear();
+        let queue = match &self.deletion_queue {
+            Some(queue) => queue,
+            None => return,
+        };
 
-        if indices.is_some() {
-            let mut ebo = 0;
-            gl::GenBuffers(1, &mut ebo);
-            self.ebo = Some(ebo);
+        if let Some(ebo) = self.ebo.take() {
+            queue.push(Resource::Buffer(ebo));
+        }
+        if let Some(texture) = self.joint_texture.take() {
+            queue.push(Resource::Texture(texture));
+        }
+        for vbo in self.vbos.drain(..) {
+            queue.push(Resource::Buffer(vbo));
+        }
+        if self.vao != 0 {
+            queue.push(Resource::VertexArray(self.vao));
+            self.vao = 0;
         }
+    }
 
-        let size = (array.len() * mem::size_of::<u32>()) as isize;
-        let data = &array[0] as *const f32 as *const c_void;
+    pub fn setup(
+        &mut self,
+        backend: &mut RenderBackend,
+    ) -> Result<(), ShaderError> {
+        // The shader comes first, nothing is uploaded if it fails
+        let shader = if self.is_skinned() {
+            Shader::with_stages(backend, "skinned", "pbr")?
+        } else {
+            Shader::new(backend, "pbr")?
+        };
 
-        println!("size: {}", size);
-        println!("data: {:?}", data);
+        // Resources of a previous setup are replaced
+        self.free();
 
-        gl::BufferData(
-            gl::ARRAY_BUFFER,
-            size,
-            data,
-            // Tell the GPU if our data are likely to change frequently
-            gl::STATIC_DRAW,
-        );
+        // Generate our array then load it
+        self.deletion_queue = Some(backend.get_deletion_queue());
+        self.vao = backend.create_vertex_array();
 
-        if let Some(ebo) = self.ebo {
-            let indices = indices.expect("Error GL while setting up ebo.");
-            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-            let size = (indices.len() * mem::size_of::<u32>()) as isize;
-            let data = &indices[0] as *const u32 as *const c_void;
-            gl::BufferData(
-                gl::ELEMENT_ARRAY_BUFFER,
-                size,
-                data,
-                gl::STATIC_DRAW,
-            );
+        for stream in &self.layout.streams {
+            let data = stream.pack(&self.vertices, &self.morph_targets);
+            let vbo = backend.create_vertex_buffer(self.vao, &data);
+
+            for attribute in stream.vertex_attributes() {
+                backend.set_vertex_attribute(self.vao, vbo, attribute);
+            }
+            self.vbos.push(vbo);
         }
 
-        let size = mem::size_of::<Position>() as i32;
-        // Position
-        gl::VertexAttribPointer(
-            0,
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            size,
-            offset_of!(Vertex, position) as *const c_void,
-        );
-        gl::EnableVertexAttribArray(0);
-
-        // Normal
-        // gl::EnableVertexAttribArray(1);
-        // gl::VertexAttribPointer(
-        //     0,
-        //     3,
-        //     gl::FLOAT,
-        //     gl::FALSE,
-        //     size,
-        //     offset_of!(Vertex, normal) as *const c_void,
-        // );
-
-        // // Tex_coord_0
-        gl::EnableVertexAttribArray(1);
-        gl::VertexAttribPointer(
-            1,
-            2,
-            gl::FLOAT,
-            gl::FALSE,
-            size,
-            offset_of!(Vertex, tex_coord_0) as *const c_void,
-        );
-        // // Tex_coord_1
-        // gl::EnableVertexAttribArray(2);
-        // gl::VertexAttribPointer(
-        //     2,
-        //     2,
-        //     gl::FLOAT,
-        //     gl::FALSE,
-        //     size,
-        //     offset_of!(Vertex, tex_coord_1) as *const c_void,
-        // );
-
-        gl::BindVertexArray(0);
+        if let Some(indices) = &self.indices {
+            self.ebo = Some(backend.create_index_buffer(self.vao, indices));
+        }
+
+        self.shader = Some(shader);
+        Ok(())
+    }
+}
+
+impl Drop for Primitive {
+    fn drop(&mut self) {
+        self.free();
     }
 }
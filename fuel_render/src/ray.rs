@@ -0,0 +1,304 @@
+use bounds::{Aabb, BoundingSphere};
+use na::{Matrix4, Vector3, Vector4};
+
+// Below it, a ray is parallel to an axis of a box
+const EPSILON: f32 = 1e-7;
+
+// Below it, a ray is parallel to a triangle. Relative to the
+// lengths of the direction and the edges, so any scale works.
+const PARALLEL_EPSILON: f32 = 1e-6;
+
+/// Half-line, its points are `origin + t * direction` with
+/// `t` positive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+/// Where a ray hits a surface. The distance is the `t` of
+/// the ray, a length when its direction is a unit vector.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub point: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+impl RayHit {
+    /// Hit with the smallest distance.
+    pub fn closest(hits: impl IntoIterator<Item = RayHit>) -> Option<RayHit> {
+        hits.into_iter().fold(None, |closest, hit| match closest {
+            Some(closest) if closest.distance <= hit.distance => Some(closest),
+            _ => Some(hit),
+        })
+    }
+}
+
+impl Ray {
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
+        Ray { origin, direction }
+    }
+
+    /// Ray going through a position of the window, from the
+    /// near plane, in world space. Positions start from the top
+    /// left corner, like the cursor.
+    pub fn from_screen(
+        position: (f32, f32),
+        win_dimensions: (f32, f32),
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+    ) -> Option<Self> {
+        let inverse = (projection * view).try_inverse()?;
+        let x = 2. * position.0 / win_dimensions.0 - 1.;
+        let y = 1. - 2. * position.1 / win_dimensions.1;
+        let unproject = |z: f32| {
+            let point = inverse * Vector4::new(x, y, z, 1.);
+            Vector3::new(point.x, point.y, point.z) / point.w
+        };
+
+        let near = unproject(-1.);
+        let far = unproject(1.);
+        Some(Ray::new(near, (far - near).normalize()))
+    }
+
+    pub fn point_at(&self, t: f32) -> Vector3<f32> {
+        self.origin + self.direction * t
+    }
+
+    /// Same ray in another space, `t` of the points is kept as
+    /// the direction isn't normalized.
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> Ray {
+        let (origin, direction) = (self.origin, self.direction);
+        let origin = matrix * Vector4::new(origin.x, origin.y, origin.z, 1.);
+        let direction =
+            matrix * Vector4::new(direction.x, direction.y, direction.z, 0.);
+        Ray::new(
+            Vector3::new(origin.x, origin.y, origin.z),
+            Vector3::new(direction.x, direction.y, direction.z),
+        )
+    }
+
+    /// First `t` inside the sphere, 0 when the origin is in it.
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let to_origin = self.origin - sphere.center;
+        let a = self.direction.dot(&self.direction);
+        let b = 2. * to_origin.dot(&self.direction);
+        let c = to_origin.dot(&to_origin) - sphere.radius * sphere.radius;
+
+        let discriminant = b * b - 4. * a * c;
+        if discriminant < 0. || a == 0. {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (near, far) = ((-b - root) / (2. * a), (-b + root) / (2. * a));
+        if far < 0. {
+            None
+        } else {
+            Some(near.max(0.))
+        }
+    }
+
+    /// First `t` inside the box, 0 when the origin is in it.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut near = 0f32;
+        let mut far = ::std::f32::INFINITY;
+
+        for axis in 0..3 {
+            let (origin, direction) = (self.origin[axis], self.direction[axis]);
+            let (min, max) = (aabb.min[axis], aabb.max[axis]);
+
+            if direction.abs() < EPSILON {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+
+    /// Both sides of the triangle are hit, the normal faces
+    /// the origin of the ray.
+    pub fn intersects_triangle(
+        &self,
+        a: &Vector3<f32>,
+        b: &Vector3<f32>,
+        c: &Vector3<f32>,
+    ) -> Option<RayHit> {
+        // Möller–Trumbore, with barycentric coordinates
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+        let normal_2 = self.direction.cross(&edge_2);
+        let determinant = edge_1.dot(&normal_2);
+        let scale = self.direction.norm() * edge_1.norm() * edge_2.norm();
+        if determinant.abs() <= PARALLEL_EPSILON * scale {
+            return None;
+        }
+
+        let inverse = 1. / determinant;
+        let to_origin = self.origin - a;
+        let weight_1 = to_origin.dot(&normal_2) * inverse;
+        if weight_1 < 0. || weight_1 > 1. {
+            return None;
+        }
+        let normal_1 = to_origin.cross(&edge_1);
+        let weight_2 = self.direction.dot(&normal_1) * inverse;
+        if weight_2 < 0. || weight_1 + weight_2 > 1. {
+            return None;
+        }
+        let distance = edge_2.dot(&normal_1) * inverse;
+        if distance < 0. {
+            return None;
+        }
+
+        let normal = edge_1.cross(&edge_2).normalize();
+        let normal = if normal.dot(&self.direction) > 0. {
+            -normal
+        } else {
+            normal
+        };
+        Some(RayHit {
+            point: self.point_at(distance),
+            normal,
+            distance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use na::{Isometry3, Perspective3, Point3};
+
+    fn close(a: &Vector3<f32>, b: &Vector3<f32>) -> bool {
+        (a - b).norm() < 1e-4
+    }
+
+    fn triangle(scale: f32) -> [Vector3<f32>; 3] {
+        [
+            Vector3::new(-1., -1., 0.) * scale,
+            Vector3::new(1., -1., 0.) * scale,
+            Vector3::new(0., 1., 0.) * scale,
+        ]
+    }
+
+    #[test]
+    fn ray_hits_a_triangle_from_both_sides() {
+        let [a, b, c] = triangle(1.);
+        let ray = Ray::new(Vector3::new(0., 0., 5.), -Vector3::z());
+        let hit = ray.intersects_triangle(&a, &b, &c).unwrap();
+        assert!((hit.distance - 5.).abs() < 1e-5);
+        assert!(close(&hit.point, &Vector3::zeros()));
+        assert!(close(&hit.normal, &Vector3::z()));
+
+        let ray = Ray::new(Vector3::new(0., 0., -5.), Vector3::z());
+        let hit = ray.intersects_triangle(&a, &b, &c).unwrap();
+        assert!(close(&hit.normal, &-Vector3::z()));
+    }
+
+    #[test]
+    fn ray_misses_a_triangle() {
+        let [a, b, c] = triangle(1.);
+        // Beside it, behind it and parallel to it
+        let beside = Ray::new(Vector3::new(2., 0., 5.), -Vector3::z());
+        let behind = Ray::new(Vector3::new(0., 0., 5.), Vector3::z());
+        let parallel = Ray::new(Vector3::new(-5., 0., 0.), Vector3::x());
+        assert!(beside.intersects_triangle(&a, &b, &c).is_none());
+        assert!(behind.intersects_triangle(&a, &b, &c).is_none());
+        assert!(parallel.intersects_triangle(&a, &b, &c).is_none());
+    }
+
+    #[test]
+    fn triangle_test_does_not_depend_on_scale() {
+        for &scale in &[1e-4, 1., 1e4] {
+            let [a, b, c] = triangle(scale);
+            let origin = Vector3::new(0., 0., 5. * scale);
+            let ray = Ray::new(origin, -Vector3::z() * scale);
+            let hit = ray.intersects_triangle(&a, &b, &c).unwrap();
+            assert!((hit.distance - 5.).abs() < 1e-3, "scale {}", scale);
+        }
+        // Degenerate triangles are never hit
+        let ray = Ray::new(Vector3::new(0., 0., 5.), -Vector3::z());
+        let point = Vector3::zeros();
+        assert!(ray.intersects_triangle(&point, &point, &point).is_none());
+    }
+
+    #[test]
+    fn ray_hits_and_misses_a_box() {
+        let aabb =
+            Aabb::new(Vector3::new(-1., -1., -1.), Vector3::new(1., 1., 1.));
+        let ray = Ray::new(Vector3::new(0., 0., 5.), -Vector3::z());
+        assert!((ray.intersects_aabb(&aabb).unwrap() - 4.).abs() < 1e-5);
+        // Along an axis, from the inside and behind it
+        let axis = Ray::new(Vector3::new(0.5, 0.5, 5.), -Vector3::z());
+        let inside = Ray::new(Vector3::zeros(), Vector3::x());
+        let behind = Ray::new(Vector3::new(0., 0., 5.), Vector3::z());
+        let beside = Ray::new(Vector3::new(2., 0., 5.), -Vector3::z());
+        assert!((axis.intersects_aabb(&aabb).unwrap() - 4.).abs() < 1e-5);
+        assert_eq!(inside.intersects_aabb(&aabb), Some(0.));
+        assert!(behind.intersects_aabb(&aabb).is_none());
+        assert!(beside.intersects_aabb(&aabb).is_none());
+    }
+
+    #[test]
+    fn ray_hits_and_misses_a_sphere() {
+        let sphere = BoundingSphere::new(Vector3::new(0., 0., -2.), 1.);
+        let ray = Ray::new(Vector3::new(0., 0., 5.), -Vector3::z());
+        assert!((ray.intersects_sphere(&sphere).unwrap() - 6.).abs() < 1e-5);
+        let inside = Ray::new(Vector3::new(0., 0., -2.), Vector3::y());
+        let behind = Ray::new(Vector3::new(0., 0., 5.), Vector3::z());
+        let beside = Ray::new(Vector3::new(0., 2., 5.), -Vector3::z());
+        assert_eq!(inside.intersects_sphere(&sphere), Some(0.));
+        assert!(behind.intersects_sphere(&sphere).is_none());
+        assert!(beside.intersects_sphere(&sphere).is_none());
+    }
+
+    #[test]
+    fn closest_hit_has_the_smallest_distance() {
+        let hit = |distance| RayHit {
+            point: Vector3::zeros(),
+            normal: Vector3::z(),
+            distance,
+        };
+        let closest = RayHit::closest(vec![hit(3.), hit(1.), hit(2.)]);
+        assert_eq!(closest, Some(hit(1.)));
+        assert_eq!(RayHit::closest(vec![]), None);
+    }
+
+    #[test]
+    fn screen_center_is_unprojected_along_the_view() {
+        let projection =
+            Perspective3::new(800. / 600., 1., 0.1, 100.).to_homogeneous();
+        let view = Isometry3::look_at_rh(
+            &Point3::new(0., 0., 3.),
+            &Point3::origin(),
+            &Vector3::y(),
+        )
+        .to_homogeneous();
+        let dimensions = (800., 600.);
+
+        let ray =
+            Ray::from_screen((400., 300.), dimensions, &projection, &view)
+                .unwrap();
+        assert!(close(&ray.origin, &Vector3::new(0., 0., 2.9)));
+        assert!(close(&ray.direction, &-Vector3::z()));
+
+        // The top left corner is up and to the left
+        let ray =
+            Ray::from_screen((0., 0.), dimensions, &projection, &view).unwrap();
+        assert!(ray.direction.x < 0. && ray.direction.y > 0.);
+        assert!((ray.direction.norm() - 1.).abs() < 1e-5);
+
+        let singular = Matrix4::zeros();
+        let ray = Ray::from_screen((0., 0.), dimensions, &singular, &view);
+        assert!(ray.is_none());
+    }
+}
@@ -1,156 +1,198 @@
-use std::ffi::CString;
+use std::error;
+use std::fmt;
 use std::fs::File;
+use std::io;
 use std::io::Read;
 use std::path::PathBuf;
-use std::ptr;
-use std::str;
 
-use gl;
-use gl::types::*;
-use na::{Matrix4, Vector3};
+use backend::{DeletionQueue, ProgramId, RenderBackend, Resource, Uniform};
+use na::{Matrix4, Vector3, Vector4};
 
-#[derive(Clone, Debug)]
-pub struct Shader {
-    pub id: u32,
+/// Stage of the pipeline a shader is compiled for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
 }
 
-fn get_paths(shader_name: &str) -> (String, String) {
-    let vertex = format!("{}.vs", shader_name);
-    let fragment = format!("{}.fs", shader_name);
-
-    (vertex, fragment)
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "vertex"),
+            ShaderStage::Fragment => write!(f, "fragment"),
+        }
+    }
 }
 
-impl Shader {
-    pub fn new(shader_name: &str) -> Shader {
-        let mut shader = Shader { id: 0 };
+/// Why a shader program couldn't be created, with the
+/// info log given by the driver.
+#[derive(Debug)]
+pub enum ShaderError {
+    Io(PathBuf, io::Error),
+    Compile { stage: ShaderStage, log: String },
+    Link(String),
+}
 
-        let (vertex_path, fragment_path) = get_paths(shader_name);
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderError::Io(path, error) => {
+                write!(f, "Failed to read {}: {}", path.display(), error)
+            }
+            ShaderError::Compile { stage, log } => {
+                write!(f, "Failed to compile {} shader:\n{}", stage, log)
+            }
+            ShaderError::Link(log) => {
+                write!(f, "Failed to link shader program:\n{}", log)
+            }
+        }
+    }
+}
 
-        let vertex_shader =
-            shader.compile_shader(gl::VERTEX_SHADER, &vertex_path);
-        let fragment_shader =
-            shader.compile_shader(gl::FRAGMENT_SHADER, &fragment_path);
+impl error::Error for ShaderError {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self {
+            ShaderError::Io(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
 
-        unsafe {
-            let shader_program_id = gl::CreateProgram();
-            gl::AttachShader(shader_program_id, vertex_shader);
-            gl::AttachShader(shader_program_id, fragment_shader);
-            gl::LinkProgram(shader_program_id);
+/// Program of the backend that created it, deleted when the
+/// shader is dropped.
+#[derive(Debug)]
+pub struct Shader {
+    pub id: ProgramId,
+    deletion_queue: DeletionQueue,
+}
 
-            gl::DeleteShader(vertex_shader);
-            gl::DeleteShader(fragment_shader);
-            shader.id = shader_program_id;
-        }
+fn read_source(file_path: &str) -> Result<String, ShaderError> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // TODO Need proper path here
+    path.push("../src/assets/shaders");
+    path.push(file_path);
+    let mut shader_string = String::new();
+
+    // Transform file to string and store it in a variable
+    File::open(&path)
+        .and_then(|mut shader_file| {
+            shader_file.read_to_string(&mut shader_string)
+        })
+        .map_err(|error| ShaderError::Io(path, error))?;
+
+    Ok(shader_string)
+}
 
-        shader
+impl Shader {
+    /// Program made of `shader_name.vs` and `shader_name.fs`.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        backend: &mut RenderBackend,
+        shader_name: &str,
+    ) -> Result<Self, ShaderError> {
+        Shader::with_stages(backend, shader_name, shader_name)
     }
 
-    pub fn use_program(&self) {
-        unsafe { gl::UseProgram(self.id) }
+    /// Program made of `vertex_name.vs` and `fragment_name.fs`,
+    /// so stages can be shared between programs.
+    pub fn with_stages(
+        backend: &mut RenderBackend,
+        vertex_name: &str,
+        fragment_name: &str,
+    ) -> Result<Self, ShaderError> {
+        let vertex_source = read_source(&format!("{}.vs", vertex_name))?;
+        let fragment_source = read_source(&format!("{}.fs", fragment_name))?;
+
+        let id = backend.create_program(&vertex_source, &fragment_source)?;
+        Ok(Shader {
+            id,
+            deletion_queue: backend.get_deletion_queue(),
+        })
     }
 
-    #[allow(dead_code)]
-    pub fn set_bool(&self, var_name: &str, value: bool) {
-        let shader_variable = self.get_location(var_name);
-        unsafe { gl::Uniform1i(shader_variable, value as i32) }
+    pub fn use_program(&self, backend: &mut RenderBackend) {
+        backend.use_program(self.id)
     }
 
-    #[allow(dead_code)]
-    pub fn set_int(&self, var_name: &str, value: i32) {
-        let shader_variable = self.get_location(var_name);
-        unsafe { gl::Uniform1i(shader_variable, value) }
+    pub fn set_bool(
+        &self,
+        backend: &mut RenderBackend,
+        var_name: &str,
+        value: bool,
+    ) {
+        backend.set_uniform(self.id, var_name, &Uniform::Bool(value))
     }
 
-    #[allow(dead_code)]
-    pub fn set_float(&self, var_name: &str, value: f32) {
-        let shader_variable = self.get_location(var_name);
-        unsafe { gl::Uniform1f(shader_variable, value) }
+    pub fn set_int(
+        &self,
+        backend: &mut RenderBackend,
+        var_name: &str,
+        value: i32,
+    ) {
+        backend.set_uniform(self.id, var_name, &Uniform::Int(value))
     }
 
-    pub fn set_color(&self, var_name: &str, rgb: Vector3<f32>) {
-        let shader_variable = self.get_location(var_name);
-        unsafe { gl::Uniform3f(shader_variable, rgb.x, rgb.y, rgb.z) }
+    pub fn set_float(
+        &self,
+        backend: &mut RenderBackend,
+        var_name: &str,
+        value: f32,
+    ) {
+        backend.set_uniform(self.id, var_name, &Uniform::Float(value))
     }
 
-    pub fn set_mvp(&self, mvp: Matrix4<f32>) {
-        self.set_matrix4("mvp", mvp.as_slice());
+    pub fn set_color(
+        &self,
+        backend: &mut RenderBackend,
+        var_name: &str,
+        rgb: Vector3<f32>,
+    ) {
+        backend.set_uniform(self.id, var_name, &Uniform::Vec3(rgb))
     }
 
-    #[allow(dead_code)]
-    pub fn set_transform(&self, transform: Matrix4<f32>) {
-        self.set_matrix4("transform", transform.as_slice());
-    }
-
-    pub fn set_matrix4(&self, var_name: &str, transform: &[f32]) {
-        let shader_variable = self.get_location(var_name);
-        unsafe {
-            gl::UniformMatrix4fv(
-                shader_variable,
-                1,
-                gl::FALSE,
-                transform.as_ptr(),
-            );
-        }
+    pub fn set_vec3(
+        &self,
+        backend: &mut RenderBackend,
+        var_name: &str,
+        value: Vector3<f32>,
+    ) {
+        backend.set_uniform(self.id, var_name, &Uniform::Vec3(value))
     }
 
-    fn get_location(&self, var_name: &str) -> GLint {
-        let var_name = CString::new(var_name).unwrap();
-        unsafe { gl::GetUniformLocation(self.id, var_name.as_ptr()) }
+    pub fn set_vec4(
+        &self,
+        backend: &mut RenderBackend,
+        var_name: &str,
+        value: Vector4<f32>,
+    ) {
+        backend.set_uniform(self.id, var_name, &Uniform::Vec4(value))
     }
 
-    unsafe fn check_shader_compile_error(&self, shader: GLuint) {
-        const CAPACITY: usize = 1024;
-        let mut success = i32::from(gl::FALSE);
-        let mut info_log = Vec::with_capacity(CAPACITY);
-        info_log.set_len(CAPACITY - 1);
+    pub fn set_mvp(&self, backend: &mut RenderBackend, mvp: Matrix4<f32>) {
+        self.set_matrix4(backend, "mvp", mvp);
+    }
 
-        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+    #[allow(dead_code)]
+    pub fn set_transform(
+        &self,
+        backend: &mut RenderBackend,
+        transform: Matrix4<f32>,
+    ) {
+        self.set_matrix4(backend, "transform", transform);
+    }
 
-        if success != i32::from(gl::TRUE) {
-            gl::GetShaderInfoLog(
-                shader,
-                CAPACITY as i32,
-                ptr::null_mut(),
-                info_log.as_mut_ptr() as *mut GLchar,
-            );
-            println!(
-                "ERROR::SHADER::VERTEX::COMPILATION_FAILED\n{}",
-                str::from_utf8(&info_log).unwrap()
-            );
-        }
+    pub fn set_matrix4(
+        &self,
+        backend: &mut RenderBackend,
+        var_name: &str,
+        transform: Matrix4<f32>,
+    ) {
+        backend.set_uniform(self.id, var_name, &Uniform::Mat4(transform))
     }
+}
 
-    fn compile_shader(&self, shader_type: GLenum, file_path: &str) -> u32 {
-        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        // TODO Need proper path here
-        path.push("../src/assets/shaders");
-        path.push(file_path);
-        let mut shader_file = File::open(path).unwrap();
-        let mut shader_string = String::new();
-
-        // Transform file to string and store it in a variable
-        shader_file
-            .read_to_string(&mut shader_string)
-            .expect("Failed to read vertex shader file");
-
-        // convert to C compatible string
-        let shader_source_string =
-            CString::new(shader_string.as_bytes()).unwrap();
-
-        unsafe {
-            let shader = gl::CreateShader(shader_type);
-
-            gl::ShaderSource(
-                shader,
-                1,
-                &shader_source_string.as_ptr(),
-                ptr::null(),
-            );
-            gl::CompileShader(shader);
-            self.check_shader_compile_error(shader);
-
-            shader
-        }
+impl Drop for Shader {
+    fn drop(&mut self) {
+        self.deletion_queue.push(Resource::Program(self.id));
     }
 }
@@ -0,0 +1,82 @@
+use backend::{RenderBackend, TextureId};
+use na::Matrix4;
+use shader::Shader;
+
+// Texture unit of the joint matrices, after the ones of the
+// materials
+const JOINT_TEXTURE_UNIT: u32 = 5;
+
+/// Joints deforming a skinned mesh. `joints` are indices in the
+/// nodes of the Model, each with the inverse of its matrix when
+/// the mesh was bound to the skeleton.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Skin {
+    pub name: Option<String>,
+    pub joints: Vec<usize>,
+    pub inverse_bind_matrices: Vec<Matrix4<f32>>,
+}
+
+impl Skin {
+    /// Matrices moving the vertices from the bind pose to the
+    /// current pose of the joints, relative to the mesh drawn
+    /// with `model`. `node_matrices` are the matrices of the
+    /// nodes of the Model, in the same space as `model`.
+    pub fn get_joint_matrices(
+        &self,
+        model: &Matrix4<f32>,
+        node_matrices: &[Option<Matrix4<f32>>],
+    ) -> Vec<Matrix4<f32>> {
+        let inverse = model.try_inverse().unwrap_or_else(Matrix4::identity);
+        self.joints
+            .iter()
+            .enumerate()
+            .map(|(index, joint)| {
+                let joint = node_matrices
+                    .get(*joint)
+                    .and_then(|matrix| *matrix)
+                    .unwrap_or(*model);
+                let inverse_bind = self
+                    .inverse_bind_matrices
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(Matrix4::identity);
+                inverse * joint * inverse_bind
+            })
+            .collect()
+    }
+}
+
+/// Send the joint matrices to the skinning shader, which must
+/// be in use. Without joints, vertices are not skinned.
+///
+/// Matrices are sent in a data texture, a row for each joint with
+/// a texel for each column, so skins aren't limited by uniforms.
+/// `texture` is created by the first call, then updated.
+pub fn bind_joints(
+    backend: &mut RenderBackend,
+    shader: &Shader,
+    joint_matrices: &[Matrix4<f32>],
+    texture: &mut Option<TextureId>,
+) {
+    shader.set_bool(backend, "has_skin", !joint_matrices.is_empty());
+    if joint_matrices.is_empty() {
+        return;
+    }
+
+    let data: Vec<f32> = joint_matrices
+        .iter()
+        .flat_map(|matrix| matrix.as_slice().to_vec())
+        .collect();
+    let height = joint_matrices.len() as u32;
+    let id = match *texture {
+        Some(id) => {
+            backend.update_data_texture(id, 4, height, &data);
+            id
+        }
+        None => backend.create_data_texture(4, height, &data),
+    };
+    *texture = Some(id);
+
+    backend.bind_texture(JOINT_TEXTURE_UNIT, id);
+    shader.set_int(backend, "joint_matrices", JOINT_TEXTURE_UNIT as i32);
+}
@@ -1,178 +1,177 @@
-use gl;
-use gl::types::*;
+use backend::{
+    DeletionQueue, Filter, RenderBackend, Resource, Sampler, TextureId, Wrap,
+};
 use gltf;
-use gltf::image::Source;
-
-use image;
-use image::GenericImage;
-use image::ImageFormat::{JPEG, PNG};
-use std::mem;
-use std::os::raw::c_void;
-use std::path::PathBuf;
-
-const TEXTURE_ASSET_FOLDER: &str = "src/assets/textures";
-
-#[derive(Clone, Debug, Default)]
+use gltf::image::Format;
+use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+
+/// Image used by the materials of a Model, in RGBA with 8 bits
+/// per channel. Pixels are kept in memory and sent to the graphic
+/// card the first time the texture is bound.
+///
+/// The uploaded texture is deleted when the Texture is dropped,
+/// a clone is uploaded again by its own first bind.
+#[derive(Debug, Default)]
 pub struct Texture {
-    pub glTF_index: usize,
     pub name: Option<String>,
-    pub gl_id: u32,
-    pub tex_coord: u32,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    pub sampler: Sampler,
+    id: Option<TextureId>,
+    deletion_queue: Option<DeletionQueue>,
 }
 
 impl Texture {
-    pub fn from_gltf(
-        gltf_texture: gltf::Texture,
-        buffers: &Vec<gltf::buffer::Data>,
-    ) {
-        let glTF_index = gltf_texture.index();
-        let data = match gltf_texture.source().source() {
-            Source::View { view, mime_type } => {
-                let img = match mime_type {
-                    "image/jpeg" => {
-                        // let data = view.buffer().source();
-                    }
-                    "image/png" => {
-                        // image::load_from_memory_with_format(view.buffer(), PNG)
-                    }
-                    _ => println!("prob"),
-                };
-            }
-            Source::Uri { uri, .. } => println!("Uri not supported."),
-            _ => println!("Texture format not supported."),
-        };
-
-        let mut texture_id = 0;
-        unsafe {
-            gl::GenTextures(1, &mut texture_id);
-            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+    pub fn new(
+        name: Option<String>,
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    ) -> Self {
+        Texture {
+            name,
+            width,
+            height,
+            pixels,
+            sampler: Sampler::default(),
+            id: None,
+            deletion_queue: None,
         }
+    }
 
-        Texture {
-            ..Default::default()
-        };
+    /// Has to be called before the texture is setup.
+    pub fn with_sampler(mut self, sampler: Sampler) -> Self {
+        self.sampler = sampler;
+        self
     }
+
+    pub fn is_setup(&self) -> bool {
+        self.id.is_some()
+    }
+
+    pub fn setup(&mut self, backend: &mut RenderBackend) {
+        self.id = Some(backend.create_texture(
+            self.width,
+            self.height,
+            &self.pixels,
+            &self.sampler,
+        ));
+        self.deletion_queue = Some(backend.get_deletion_queue());
+    }
+
+    /// Queue the uploaded texture for deletion, it is setup again
+    /// when bound.
+    pub fn release(&mut self) {
+        self.free();
+    }
+
+    // Queue the uploaded texture for deletion
+    fn free(&mut self) {
+        if let (Some(id), Some(queue)) = (self.id.take(), &self.deletion_queue)
+        {
+            queue.push(Resource::Texture(id));
+        }
+    }
+
+    pub fn bind(&mut self, backend: &mut RenderBackend, unit: u32) {
+        if !self.is_setup() {
+            self.setup(backend);
+        }
+
+        let id = self.id.expect("Texture is not setup.");
+        backend.bind_texture(unit, id);
+    }
+
+    /// Images are the ones decoded by `gltf::import`, whatever
+    /// their source is (buffer view, data URI or file).
+    pub fn from_gltf(
+        gltf_texture: &gltf::Texture,
+        images: &[gltf::image::Data],
+    ) -> Self {
+        let source = gltf_texture.source();
+        let image = &images[source.index()];
+        let name = gltf_texture
+            .name()
+            .or_else(|| source.name())
+            .map(|name| name.to_owned());
+
+        Texture::new(name, image.width, image.height, to_rgba(image))
+            .with_sampler(get_sampler(&gltf_texture.sampler()))
+    }
+}
+
+impl Clone for Texture {
+    fn clone(&self) -> Self {
+        Texture::new(
+            self.name.clone(),
+            self.width,
+            self.height,
+            self.pixels.clone(),
+        )
+        .with_sampler(self.sampler)
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        self.free();
+    }
+}
+
+fn to_rgba(image: &gltf::image::Data) -> Vec<u8> {
+    let channels = match image.format {
+        Format::R8 => 1,
+        Format::R8G8 => 2,
+        Format::R8G8B8 => 3,
+        Format::R8G8B8A8 => return image.pixels.clone(),
+    };
+
+    image
+        .pixels
+        .chunks(channels)
+        .flat_map(|pixel| match image.format {
+            // Grayscale, with alpha for two channels
+            Format::R8 => vec![pixel[0], pixel[0], pixel[0], 255],
+            Format::R8G8 => vec![pixel[0], pixel[0], pixel[0], pixel[1]],
+            _ => vec![pixel[0], pixel[1], pixel[2], 255],
+        })
+        .collect()
 }
 
-// impl Texture {
-//     #[allow(dead_code)]
-//     pub fn new(texture_name: &str, stride: GLsizei) -> Texture {
-//         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-//         path.push(TEXTURE_ASSET_FOLDER);
-//         path.push(texture_name);
-
-//         let img = image::open(&path).expect("Failed to load texture");
-//         let img_data = img.raw_pixels();
-
-//         let mut texture = Texture {
-//             id: 0,
-//             stride,
-//             path: Box::new(path),
-//             raw_pixels: img_data,
-//             width: img.width() as i32,
-//             height: img.height() as i32,
-//             is_texture_activated: true,
-//         };
-
-//         texture.set_texture();
-//         texture
-//     }
-
-//     #[allow(dead_code)]
-//     pub fn set_texture(&mut self) {
-//         unsafe { self.setup() }
-//     }
-
-//     #[allow(dead_code)]
-//     pub fn set_texture_off(&mut self) {
-//         self.is_texture_activated = false
-//     }
-
-//     #[allow(dead_code)]
-//     pub fn set_texture_true(&mut self) {
-//         self.is_texture_activated = true
-//     }
-
-//     #[allow(dead_code)]
-//     // Draw our stuff
-//     pub fn render(&mut self) {
-//         let mut texture_id: u32 = 0;
-
-//         if self.is_texture_activated {
-//             texture_id = self.id;
-//         };
-
-//         unsafe {
-//             gl::BindTexture(gl::TEXTURE_2D, texture_id);
-//         }
-//     }
-// }
-
-// impl TextureSetup for Texture {
-//     // Setup our texture with openGL
-//     unsafe fn setup(&mut self) {
-//         let mut texture_id = self.id;
-//         gl::GenTextures(1, &mut texture_id);
-//         gl::BindTexture(gl::TEXTURE_2D, texture_id);
-
-//         gl::VertexAttribPointer(
-//             1,
-//             2,
-//             gl::FLOAT,
-//             gl::FALSE,
-//             self.stride,
-//             (3 * mem::size_of::<GLfloat>()) as *const c_void,
-//         );
-//         gl::EnableVertexAttribArray(1);
-
-//         gl::TexParameteri(
-//             gl::TEXTURE_2D,
-//             gl::TEXTURE_WRAP_S,
-//             gl::MIRRORED_REPEAT as i32,
-//         );
-
-//         gl::TexParameteri(
-//             gl::TEXTURE_2D,
-//             gl::TEXTURE_WRAP_T,
-//             gl::MIRRORED_REPEAT as i32,
-//         );
-
-//         // Texture filter paramenters
-//         gl::TexParameteri(
-//             gl::TEXTURE_2D,
-//             gl::TEXTURE_MIN_FILTER,
-//             gl::NEAREST as i32,
-//         );
-
-//         gl::TexParameteri(
-//             gl::TEXTURE_2D,
-//             gl::TEXTURE_MIN_FILTER,
-//             gl::LINEAR as i32,
-//         );
-
-//         gl::TexParameteri(
-//             gl::TEXTURE_2D,
-//             gl::TEXTURE_MIN_FILTER,
-//             gl::NEAREST_MIPMAP_LINEAR as i32,
-//         );
-
-//         gl::TexImage2D(
-//             gl::TEXTURE_2D,
-//             0,
-//             gl::RGB as i32,
-//             self.width,
-//             self.height,
-//             0,
-//             gl::RGB,
-//             gl::UNSIGNED_BYTE,
-//             &self.raw_pixels[0] as *const u8 as *const c_void,
-//         );
-
-//         gl::GenerateMipmap(gl::TEXTURE_2D);
-//         self.id = texture_id;
-//     }
-// }
-
-// pub trait TextureSetup {
-//     unsafe fn setup(&mut self);
-// }
+// Undefined filters are up to the implementation
+fn get_sampler(sampler: &gltf::texture::Sampler) -> Sampler {
+    let wrap = |wrap: WrappingMode| match wrap {
+        WrappingMode::Repeat => Wrap::Repeat,
+        WrappingMode::MirroredRepeat => Wrap::MirroredRepeat,
+        WrappingMode::ClampToEdge => Wrap::ClampToEdge,
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => Filter::Nearest,
+        _ => Filter::Linear,
+    };
+    let (min_filter, mipmap_filter) = match sampler.min_filter() {
+        Some(MinFilter::Nearest) => (Filter::Nearest, None),
+        Some(MinFilter::Linear) => (Filter::Linear, None),
+        Some(MinFilter::NearestMipmapNearest) => {
+            (Filter::Nearest, Some(Filter::Nearest))
+        }
+        Some(MinFilter::LinearMipmapNearest) => {
+            (Filter::Linear, Some(Filter::Nearest))
+        }
+        Some(MinFilter::NearestMipmapLinear) => {
+            (Filter::Nearest, Some(Filter::Linear))
+        }
+        Some(MinFilter::LinearMipmapLinear) | None => {
+            (Filter::Linear, Some(Filter::Linear))
+        }
+    };
+
+    Sampler {
+        mag_filter,
+        min_filter,
+        mipmap_filter,
+        wrap_s: wrap(sampler.wrap_s()),
+        wrap_t: wrap(sampler.wrap_t()),
+    }
+}
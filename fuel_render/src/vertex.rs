@@ -1,11 +1,35 @@
-use na::{Vector2, Vector3};
+use morph::{MorphTarget, MAX_MORPH_TARGETS};
+use na::{Vector2, Vector3, Vector4};
+use std::mem;
 
-#[derive(Debug)]
+const MORPH_POSITIONS: [&str; MAX_MORPH_TARGETS] = [
+    "morph_position_0",
+    "morph_position_1",
+    "morph_position_2",
+    "morph_position_3",
+];
+const MORPH_NORMALS: [&str; MAX_MORPH_TARGETS] = [
+    "morph_normal_0",
+    "morph_normal_1",
+    "morph_normal_2",
+    "morph_normal_3",
+];
+
+/// The w of the tangent is the sign of the bitangent,
+/// `cross(normal, tangent.xyz) * w`, as in glTF.
+/// Colors are linear RGBA, white by default.
+/// Vertices of skinned meshes follow up to four joints of the
+/// skin, indices in `joints_0` and their weights in `weights_0`.
+#[derive(Debug, Clone)]
 pub struct Vertex {
     pub position: Vector3<f32>,
     pub normal: Vector3<f32>,
     pub tex_coord_0: Vector2<f32>,
     pub tex_coord_1: Vector2<f32>,
+    pub tangent: Vector4<f32>,
+    pub color_0: Vector4<f32>,
+    pub joints_0: Vector4<u16>,
+    pub weights_0: Vector4<f32>,
 }
 
 impl Default for Vertex {
@@ -15,6 +39,10 @@ impl Default for Vertex {
             normal: Vector3::zeros(),
             tex_coord_0: Vector2::zeros(),
             tex_coord_1: Vector2::zeros(),
+            tangent: Vector4::zeros(),
+            color_0: Vector4::repeat(1.),
+            joints_0: Vector4::zeros(),
+            weights_0: Vector4::zeros(),
         }
     }
 }
@@ -25,3 +53,430 @@ impl Vertex {
         [pos.x, pos.y, pos.z]
     }
 }
+
+/// Each field of a Vertex that can be sent to the shaders, and
+/// the differences of the morph targets morphed by the shaders.
+/// Targets are below `MAX_MORPH_TARGETS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Attribute {
+    Position,
+    TexCoord0,
+    Normal,
+    TexCoord1,
+    Tangent,
+    Color0,
+    Joints0,
+    Weights0,
+    MorphPosition(usize),
+    MorphNormal(usize),
+}
+
+impl Attribute {
+    /// Name of the attribute, same as the Vertex field.
+    pub fn name(self) -> &'static str {
+        match self {
+            Attribute::Position => "position",
+            Attribute::TexCoord0 => "tex_coord_0",
+            Attribute::Normal => "normal",
+            Attribute::TexCoord1 => "tex_coord_1",
+            Attribute::Tangent => "tangent",
+            Attribute::Color0 => "color_0",
+            Attribute::Joints0 => "joints_0",
+            Attribute::Weights0 => "weights_0",
+            Attribute::MorphPosition(target) => MORPH_POSITIONS[target],
+            Attribute::MorphNormal(target) => MORPH_NORMALS[target],
+        }
+    }
+
+    /// Location of the attribute in the vertex shaders.
+    pub fn location(self) -> u32 {
+        match self {
+            Attribute::Position => 0,
+            Attribute::TexCoord0 => 1,
+            Attribute::Normal => 2,
+            Attribute::TexCoord1 => 3,
+            Attribute::Tangent => 4,
+            Attribute::Color0 => 5,
+            Attribute::Joints0 => 6,
+            Attribute::Weights0 => 7,
+            Attribute::MorphPosition(target) => 8 + target as u32,
+            Attribute::MorphNormal(target) => {
+                8 + (MAX_MORPH_TARGETS + target) as u32
+            }
+        }
+    }
+
+    pub fn components(self) -> usize {
+        match self {
+            Attribute::Position
+            | Attribute::Normal
+            | Attribute::MorphPosition(_)
+            | Attribute::MorphNormal(_) => 3,
+            Attribute::TexCoord0 | Attribute::TexCoord1 => 2,
+            Attribute::Tangent
+            | Attribute::Color0
+            | Attribute::Joints0
+            | Attribute::Weights0 => 4,
+        }
+    }
+
+    /// Joint indices are sent as floats too, they are exact
+    /// up to 2^24.
+    pub fn kind(self) -> AttributeType {
+        AttributeType::Float
+    }
+
+    /// Size of the attribute in bytes.
+    pub fn size(self) -> usize {
+        self.components() * self.kind().size()
+    }
+
+    // `index` of the vertex in its primitive, for the targets
+    fn write(
+        self,
+        index: usize,
+        vertex: &Vertex,
+        targets: &[MorphTarget],
+        data: &mut Vec<f32>,
+    ) {
+        let delta = |deltas: Option<&Vec<Vector3<f32>>>| {
+            deltas
+                .and_then(|deltas| deltas.get(index))
+                .cloned()
+                .unwrap_or_else(Vector3::zeros)
+        };
+        match self {
+            Attribute::Position => data.extend(vertex.position.iter()),
+            Attribute::TexCoord0 => data.extend(vertex.tex_coord_0.iter()),
+            Attribute::Normal => data.extend(vertex.normal.iter()),
+            Attribute::TexCoord1 => data.extend(vertex.tex_coord_1.iter()),
+            Attribute::Tangent => data.extend(vertex.tangent.iter()),
+            Attribute::Color0 => data.extend(vertex.color_0.iter()),
+            Attribute::Joints0 => data
+                .extend(vertex.joints_0.iter().map(|joint| f32::from(*joint))),
+            Attribute::Weights0 => data.extend(vertex.weights_0.iter()),
+            Attribute::MorphPosition(target) => data.extend(
+                delta(targets.get(target).map(|target| &target.positions))
+                    .iter(),
+            ),
+            Attribute::MorphNormal(target) => data.extend(
+                delta(targets.get(target).map(|target| &target.normals)).iter(),
+            ),
+        }
+    }
+}
+
+/// Type of each component of an attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeType {
+    Float,
+}
+
+impl AttributeType {
+    pub fn size(self) -> usize {
+        match self {
+            AttributeType::Float => mem::size_of::<f32>(),
+        }
+    }
+}
+
+/// Where an attribute is read from inside a vertex buffer.
+/// Stride and offset are in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub name: &'static str,
+    pub location: u32,
+    pub components: usize,
+    pub kind: AttributeType,
+    pub stride: usize,
+    pub offset: usize,
+}
+
+/// How attributes are spread over vertex buffers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferLayout {
+    /// One buffer, attributes of a vertex are next to each other.
+    Interleaved,
+    /// One buffer per attribute.
+    MultiStream,
+}
+
+impl Default for BufferLayout {
+    fn default() -> Self {
+        BufferLayout::Interleaved
+    }
+}
+
+/// Attributes sharing the same vertex buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stream {
+    pub attributes: Vec<Attribute>,
+    pub stride: usize,
+}
+
+impl Stream {
+    fn new(attributes: Vec<Attribute>) -> Self {
+        let stride = attributes.iter().map(|a| a.size()).sum();
+        Stream { attributes, stride }
+    }
+
+    /// Description of each attribute inside the buffer.
+    pub fn vertex_attributes(&self) -> Vec<VertexAttribute> {
+        let mut offset = 0;
+        self.attributes
+            .iter()
+            .map(|attribute| {
+                let vertex_attribute = VertexAttribute {
+                    name: attribute.name(),
+                    location: attribute.location(),
+                    components: attribute.components(),
+                    kind: attribute.kind(),
+                    stride: self.stride,
+                    offset,
+                };
+                offset += attribute.size();
+                vertex_attribute
+            })
+            .collect()
+    }
+
+    /// Pack vertices into the data of the buffer, with the
+    /// differences of the morph targets of their primitive.
+    pub fn pack(
+        &self,
+        vertices: &[Vertex],
+        targets: &[MorphTarget],
+    ) -> Vec<f32> {
+        let floats = self.stride / AttributeType::Float.size();
+        let mut data = Vec::with_capacity(vertices.len() * floats);
+        vertices.iter().enumerate().for_each(|(index, vertex)| {
+            self.attributes.iter().for_each(|attribute| {
+                attribute.write(index, vertex, targets, &mut data)
+            });
+        });
+        data
+    }
+}
+
+/// Describes how the vertices of a Primitive are uploaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VertexLayout {
+    pub buffer_layout: BufferLayout,
+    pub streams: Vec<Stream>,
+}
+
+impl VertexLayout {
+    pub fn new(attributes: &[Attribute], buffer_layout: BufferLayout) -> Self {
+        let mut attributes = attributes.to_vec();
+        attributes.sort();
+        attributes.dedup();
+
+        let streams = match buffer_layout {
+            BufferLayout::Interleaved => vec![Stream::new(attributes)],
+            BufferLayout::MultiStream => attributes
+                .into_iter()
+                .map(|attribute| Stream::new(vec![attribute]))
+                .collect(),
+        };
+
+        VertexLayout {
+            buffer_layout,
+            streams,
+        }
+    }
+
+    pub fn attributes(&self) -> Vec<Attribute> {
+        let mut attributes: Vec<Attribute> = self
+            .streams
+            .iter()
+            .flat_map(|stream| stream.attributes.iter().cloned())
+            .collect();
+        attributes.sort();
+        attributes
+    }
+
+    pub fn has(&self, attribute: Attribute) -> bool {
+        self.streams
+            .iter()
+            .any(|stream| stream.attributes.contains(&attribute))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_attributes() -> Vec<Attribute> {
+        let mut attributes = vec![
+            Attribute::Position,
+            Attribute::TexCoord0,
+            Attribute::Normal,
+            Attribute::TexCoord1,
+            Attribute::Tangent,
+            Attribute::Color0,
+            Attribute::Joints0,
+            Attribute::Weights0,
+        ];
+        for target in 0..MAX_MORPH_TARGETS {
+            attributes.push(Attribute::MorphPosition(target));
+        }
+        for target in 0..MAX_MORPH_TARGETS {
+            attributes.push(Attribute::MorphNormal(target));
+        }
+        attributes
+    }
+
+    fn vertex(x: f32) -> Vertex {
+        Vertex {
+            position: Vector3::new(x, 1., 2.),
+            normal: Vector3::z(),
+            tex_coord_0: Vector2::new(0.25, 0.75),
+            joints_0: Vector4::new(1, 2, 3, 4),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn locations_cover_0_to_15() {
+        let locations: Vec<u32> = all_attributes()
+            .into_iter()
+            .map(|attribute| attribute.location())
+            .collect();
+        assert_eq!(locations, (0..16).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn interleaved_layout_has_one_sorted_stream() {
+        let attributes =
+            [Attribute::Normal, Attribute::Position, Attribute::Normal];
+        let layout = VertexLayout::new(&attributes, BufferLayout::Interleaved);
+        assert_eq!(layout.streams.len(), 1);
+        assert_eq!(
+            layout.attributes(),
+            vec![Attribute::Position, Attribute::Normal]
+        );
+        assert!(layout.has(Attribute::Normal));
+        assert!(!layout.has(Attribute::TexCoord0));
+
+        let stream = &layout.streams[0];
+        assert_eq!(stream.stride, 24);
+        let vertex_attributes = stream.vertex_attributes();
+        assert_eq!(vertex_attributes[0].name, "position");
+        assert_eq!(vertex_attributes[0].location, 0);
+        assert_eq!(vertex_attributes[0].offset, 0);
+        assert_eq!(vertex_attributes[1].name, "normal");
+        assert_eq!(vertex_attributes[1].location, 2);
+        assert_eq!(vertex_attributes[1].offset, 12);
+        assert!(vertex_attributes.iter().all(|a| a.stride == 24));
+    }
+
+    #[test]
+    fn interleaved_offsets_follow_the_sizes() {
+        let layout =
+            VertexLayout::new(&all_attributes(), BufferLayout::Interleaved);
+        let stream = &layout.streams[0];
+        let size: usize = all_attributes().iter().map(|a| a.size()).sum();
+        assert_eq!(stream.stride, size);
+
+        let mut offset = 0;
+        for (vertex_attribute, attribute) in
+            stream.vertex_attributes().iter().zip(all_attributes())
+        {
+            assert_eq!(vertex_attribute.location, attribute.location());
+            assert_eq!(vertex_attribute.components, attribute.components());
+            assert_eq!(vertex_attribute.offset, offset);
+            offset += attribute.size();
+        }
+        assert_eq!(offset, stream.stride);
+    }
+
+    #[test]
+    fn split_layout_has_one_stream_per_attribute() {
+        let attributes = [
+            Attribute::TexCoord0,
+            Attribute::Position,
+            Attribute::MorphNormal(1),
+        ];
+        let layout = VertexLayout::new(&attributes, BufferLayout::MultiStream);
+        assert_eq!(layout.streams.len(), 3);
+        let expected = [
+            (Attribute::Position, 12, 0),
+            (Attribute::TexCoord0, 8, 1),
+            (Attribute::MorphNormal(1), 12, 13),
+        ];
+        for (stream, &(attribute, stride, location)) in
+            layout.streams.iter().zip(&expected)
+        {
+            assert_eq!(stream.attributes, vec![attribute]);
+            assert_eq!(stream.stride, stride);
+            let vertex_attributes = stream.vertex_attributes();
+            assert_eq!(vertex_attributes.len(), 1);
+            assert_eq!(vertex_attributes[0].location, location);
+            assert_eq!(vertex_attributes[0].offset, 0);
+            assert_eq!(vertex_attributes[0].stride, stride);
+        }
+    }
+
+    #[test]
+    fn pack_interleaves_the_vertices() {
+        let attributes = [
+            Attribute::Position,
+            Attribute::TexCoord0,
+            Attribute::Joints0,
+        ];
+        let layout = VertexLayout::new(&attributes, BufferLayout::Interleaved);
+        let data = layout.streams[0].pack(&[vertex(0.), vertex(5.)], &[]);
+        let expected = [
+            // Position, tex coord and joints of each vertex
+            0., 1., 2., 0.25, 0.75, 1., 2., 3., 4., 5., 1., 2., 0.25, 0.75, 1.,
+            2., 3., 4.,
+        ];
+        assert_eq!(data.len() * 4, layout.streams[0].stride * 2);
+        assert!(data
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+
+    #[test]
+    fn pack_splits_the_streams() {
+        let attributes = [Attribute::Position, Attribute::Color0];
+        let layout = VertexLayout::new(&attributes, BufferLayout::MultiStream);
+        let vertices = [vertex(0.), vertex(5.)];
+        let positions = layout.streams[0].pack(&vertices, &[]);
+        let colors = layout.streams[1].pack(&vertices, &[]);
+        assert_eq!(positions.len(), 6);
+        assert!((positions[3] - 5.).abs() < 1e-6);
+        assert_eq!(colors.len(), 8);
+        assert!(colors.iter().all(|value| (value - 1.).abs() < 1e-6));
+    }
+
+    #[test]
+    fn pack_writes_the_morph_differences() {
+        let target = MorphTarget {
+            positions: vec![Vector3::x(), Vector3::y()],
+            normals: vec![],
+            tangents: vec![],
+        };
+        let attributes =
+            [Attribute::MorphPosition(0), Attribute::MorphNormal(0)];
+        let layout = VertexLayout::new(&attributes, BufferLayout::Interleaved);
+        let data = layout.streams[0].pack(&[vertex(0.), vertex(5.)], &[target]);
+        // Missing normals are zeros
+        let expected = [
+            // Position and normal differences of each vertex
+            1., 0., 0., 0., 0., 0., 0., 1., 0., 0., 0., 0.,
+        ];
+        assert_eq!(data.len(), expected.len());
+        assert!(data
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| (a - b).abs() < 1e-6));
+
+        // Missing targets too
+        let attributes = [Attribute::MorphPosition(3)];
+        let layout = VertexLayout::new(&attributes, BufferLayout::Interleaved);
+        let data = layout.streams[0].pack(&[vertex(0.)], &[]);
+        assert_eq!(data, vec![0.; 3]);
+    }
+}
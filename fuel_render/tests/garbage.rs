@@ -0,0 +1,149 @@
+extern crate fuel_render;
+extern crate nalgebra as na;
+
+use fuel_render::backend::Command;
+use fuel_render::{
+    bind_joints, Attribute, HeadlessBackend, Primitive, RenderBackend, Shader,
+    Texture, Vertex,
+};
+use na::{Matrix4, Vector3};
+
+fn triangle() -> Primitive {
+    let vertices = vec![
+        Vector3::new(0., 0., 0.),
+        Vector3::new(1., 0., 0.),
+        Vector3::new(0., 1., 0.),
+    ]
+    .into_iter()
+    .map(|position| Vertex {
+        position,
+        ..Default::default()
+    })
+    .collect();
+    Primitive::new(vertices, Some(vec![0, 1, 2]), &[Attribute::Position])
+}
+
+// Commands deleting what the given commands created
+fn deletions(commands: &[Command]) -> Vec<Command> {
+    commands
+        .iter()
+        .filter_map(|command| match command {
+            Command::CreateVertexArray(vao) => {
+                Some(Command::DeleteVertexArray(*vao))
+            }
+            Command::CreateVertexBuffer { vbo, .. } => {
+                Some(Command::DeleteBuffer(*vbo))
+            }
+            Command::CreateIndexBuffer { ebo, .. } => {
+                Some(Command::DeleteBuffer(*ebo))
+            }
+            Command::CreateProgram { program, .. } => {
+                Some(Command::DeleteProgram(*program))
+            }
+            Command::CreateTexture { texture, .. } => {
+                Some(Command::DeleteTexture(*texture))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn dropped_resources_are_deleted_by_collect_garbage() {
+    let mut backend = HeadlessBackend::new();
+    let start = backend.live_resources();
+
+    let mut primitive = triangle();
+    primitive.setup(&mut backend).unwrap();
+    let shader = Shader::new(&mut backend, "pbr").unwrap();
+    let mut texture = Texture::new(None, 1, 1, vec![255; 4]);
+    texture.bind(&mut backend, 0);
+
+    let created = backend.take_commands();
+    let expected = deletions(&created);
+    // Vertex array, vertex and index buffers, two programs, texture
+    assert_eq!(expected.len(), 6);
+    assert_eq!(backend.live_resources().total(), start.total() + 6);
+
+    drop(primitive);
+    drop(shader);
+    drop(texture);
+    // Nothing is deleted before the backend collects the garbage
+    assert!(backend.commands().is_empty());
+    assert_eq!(backend.get_deletion_queue().len(), 6);
+
+    backend.collect_garbage();
+    assert_eq!(backend.live_resources(), start);
+    assert!(backend.get_deletion_queue().is_empty());
+    let deleted = backend.commands();
+    assert_eq!(deleted.len(), expected.len());
+    for command in &expected {
+        assert!(deleted.contains(command), "{:?} not deleted", command);
+    }
+}
+
+#[test]
+fn setup_again_replaces_the_uploaded_resources() {
+    let mut backend = HeadlessBackend::new();
+    let mut primitive = triangle();
+    primitive.setup(&mut backend).unwrap();
+    let live = backend.live_resources();
+
+    primitive.setup(&mut backend).unwrap();
+    backend.collect_garbage();
+    assert_eq!(backend.live_resources(), live);
+}
+
+#[test]
+fn release_only_queues_its_own_resources() {
+    let mut backend = HeadlessBackend::new();
+    let mut released = triangle();
+    released.setup(&mut backend).unwrap();
+    let created = backend.take_commands();
+    let mut dropped = triangle();
+    dropped.setup(&mut backend).unwrap();
+    backend.take_commands();
+
+    drop(dropped);
+    let pending = backend.get_deletion_queue().len();
+    released.release();
+    assert!(!released.is_setup());
+    // Nothing is deleted before the frame collects the garbage
+    assert!(backend.commands().is_empty());
+    assert_eq!(
+        backend.get_deletion_queue().len(),
+        pending + deletions(&created).len()
+    );
+}
+
+#[test]
+fn joint_matrices_reuse_their_texture() {
+    let mut backend = HeadlessBackend::new();
+    let shader = Shader::new(&mut backend, "pbr").unwrap();
+    let joints = vec![Matrix4::identity(); 200];
+    let mut texture = None;
+    bind_joints(&mut backend, &shader, &joints, &mut texture);
+    bind_joints(&mut backend, &shader, &joints, &mut texture);
+
+    let created = backend
+        .commands()
+        .iter()
+        .filter(|command| match command {
+            Command::CreateDataTexture { width, height, .. } => {
+                assert_eq!((*width, *height), (4, 200));
+                true
+            }
+            _ => false,
+        })
+        .count();
+    let updated = backend
+        .commands()
+        .iter()
+        .filter(|command| match command {
+            Command::UpdateDataTexture { .. } => true,
+            _ => false,
+        })
+        .count();
+    assert_eq!((created, updated), (1, 1));
+    assert!(texture.is_some());
+}
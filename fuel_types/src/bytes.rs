@@ -0,0 +1,65 @@
+//! Numbers in the bytes of 3D files, shared by the importer and
+//! the exporter.
+
+/// Unsigned integer stored in `bytes`, with the most significant
+/// byte first when `big_endian`.
+pub fn read_uint(bytes: &[u8], big_endian: bool) -> u64 {
+    let read = |value: u64, byte: &u8| value << 8 | u64::from(*byte);
+    if big_endian {
+        bytes.iter().fold(0, read)
+    } else {
+        bytes.iter().rev().fold(0, read)
+    }
+}
+
+pub fn read_f32(bytes: &[u8], big_endian: bool) -> f32 {
+    f32::from_bits(read_uint(bytes, big_endian) as u32)
+}
+
+pub fn read_f64(bytes: &[u8], big_endian: bool) -> f64 {
+    f64::from_bits(read_uint(bytes, big_endian))
+}
+
+/// Little endian, as in glTF buffers and .glb files.
+pub fn write_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend((0..4).map(|byte| (value >> (byte * 8)) as u8));
+}
+
+pub fn write_f32(bytes: &mut Vec<u8>, value: f32) {
+    write_u32(bytes, value.to_bits());
+}
+
+/// Pad `bytes` with `padding` up to a multiple of 4.
+pub fn align(bytes: &mut Vec<u8>, padding: u8) {
+    while bytes.len() % 4 != 0 {
+        bytes.push(padding);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn written_numbers_are_read_back() {
+        let mut bytes = vec![];
+        write_u32(&mut bytes, 0x0102_0304);
+        assert_eq!(bytes, [4, 3, 2, 1]);
+        assert_eq!(read_uint(&bytes, false), 0x0102_0304);
+        assert_eq!(read_uint(&bytes, true), 0x0403_0201);
+
+        write_f32(&mut bytes, -1.5);
+        assert!((read_f32(&bytes[4..], false) + 1.5).abs() < 1e-6);
+        let double = [0x3f, 0xd0, 0, 0, 0, 0, 0, 0];
+        assert!((read_f64(&double, true) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn align_pads_to_words() {
+        let mut bytes = vec![1, 2, 3, 4, 5];
+        align(&mut bytes, b' ');
+        assert_eq!(bytes, [1, 2, 3, 4, 5, b' ', b' ', b' ']);
+        align(&mut bytes, 0);
+        assert_eq!(bytes.len(), 8);
+    }
+}
@@ -1,5 +1,6 @@
 extern crate nalgebra as na;
 
+pub mod bytes;
 mod position;
 mod rotation;
 mod scale;
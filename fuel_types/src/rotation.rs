@@ -1,10 +1,17 @@
-use na::Vector3;
+use na::{Unit, UnitQuaternion, Vector3};
 
-#[derive(Default, Debug, Clone)]
+/// Orientation stored as a unit quaternion.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Rotation {
-    x: f32,
-    y: f32,
-    z: f32,
+    quaternion: UnitQuaternion<f32>,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation {
+            quaternion: UnitQuaternion::identity(),
+        }
+    }
 }
 
 impl Rotation {
@@ -12,17 +19,42 @@ impl Rotation {
         Default::default()
     }
 
-    pub fn new_with_default(x: f32, y: f32, z: f32) -> Self {
-        Rotation { x, y, z }
+    pub fn new_with_default(quaternion: UnitQuaternion<f32>) -> Self {
+        Rotation { quaternion }
+    }
+
+    /// Angles in radians around the x, y and z axes, applied
+    /// in this order.
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Self {
+        Rotation::new_with_default(UnitQuaternion::from_euler_angles(x, y, z))
+    }
+
+    /// Angle in radians, the axis doesn't have to be normalized.
+    pub fn from_axis_angle(axis: Vector3<f32>, angle: f32) -> Self {
+        Rotation::new_with_default(UnitQuaternion::from_axis_angle(
+            &Unit::new_normalize(axis),
+            angle,
+        ))
+    }
+
+    /// Rotation pointing the -z axis towards `direction`, the
+    /// way cameras and lights look in glTF.
+    pub fn look_at(direction: Vector3<f32>, up: Vector3<f32>) -> Self {
+        Rotation::new_with_default(
+            UnitQuaternion::look_at_rh(&direction, &up).inverse(),
+        )
+    }
+
+    pub fn get(&self) -> UnitQuaternion<f32> {
+        self.quaternion
     }
 
-    pub fn get(&self) -> Vector3<f32> {
-        Vector3::new(self.x, self.y, self.z)
+    /// Angles in radians around the x, y and z axes.
+    pub fn euler_angles(&self) -> (f32, f32, f32) {
+        self.quaternion.to_euler_angles()
     }
 
-    pub fn set(&mut self, x: f32, y: f32, z: f32) {
-        self.x = x;
-        self.y = y;
-        self.z = z;
+    pub fn set(&mut self, quaternion: UnitQuaternion<f32>) {
+        self.quaternion = quaternion;
     }
 }
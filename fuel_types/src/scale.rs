@@ -1,6 +1,10 @@
+use na::Vector3;
+
 #[derive(Debug, Clone)]
 pub struct Scale {
-    scale: f32,
+    x: f32,
+    y: f32,
+    z: f32,
 }
 
 impl Scale {
@@ -8,21 +12,31 @@ impl Scale {
         Default::default()
     }
 
-    pub fn new_with_default(scale: f32) -> Self {
-        Scale { scale }
+    pub fn new_with_default(x: f32, y: f32, z: f32) -> Self {
+        Scale { x, y, z }
+    }
+
+    pub fn uniform(scale: f32) -> Self {
+        Scale::new_with_default(scale, scale, scale)
+    }
+
+    pub fn get(&self) -> Vector3<f32> {
+        Vector3::new(self.x, self.y, self.z)
     }
 
-    pub fn get(&self) -> f32 {
-        self.scale
+    pub fn set(&mut self, x: f32, y: f32, z: f32) {
+        self.x = x;
+        self.y = y;
+        self.z = z;
     }
 
-    pub fn set(&mut self, scale: f32) {
-        self.scale = scale;
+    pub fn set_uniform(&mut self, scale: f32) {
+        self.set(scale, scale, scale);
     }
 }
 
 impl Default for Scale {
     fn default() -> Self {
-        Scale { scale: 1. }
+        Scale::uniform(1.)
     }
 }
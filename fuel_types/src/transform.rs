@@ -1,4 +1,4 @@
-use na::Vector3;
+use na::{Isometry3, Matrix4, Translation3, UnitQuaternion, Vector3};
 use position::Position;
 use rotation::Rotation;
 use scale::Scale;
@@ -11,9 +11,90 @@ pub struct Transform {
 }
 
 impl Transform {
-    pub fn get(&self) -> (Vector3<f32>, Vector3<f32>, f32) {
+    pub fn new(
+        position: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+        scale: Vector3<f32>,
+    ) -> Self {
+        Transform {
+            position: Position::new_with_default(
+                position.x, position.y, position.z,
+            ),
+            rotation: Rotation::new_with_default(rotation),
+            scale: Scale::new_with_default(scale.x, scale.y, scale.z),
+        }
+    }
+
+    /// Angles in radians around the x, y and z axes.
+    pub fn from_euler(position: Vector3<f32>, x: f32, y: f32, z: f32) -> Self {
+        Transform {
+            rotation: Rotation::from_euler(x, y, z),
+            ..Transform::from_position(position)
+        }
+    }
+
+    pub fn from_axis_angle(
+        position: Vector3<f32>,
+        axis: Vector3<f32>,
+        angle: f32,
+    ) -> Self {
+        Transform {
+            rotation: Rotation::from_axis_angle(axis, angle),
+            ..Transform::from_position(position)
+        }
+    }
+
+    /// Placed at `eye`, with the -z axis pointing towards `target`.
+    pub fn look_at(
+        eye: Vector3<f32>,
+        target: Vector3<f32>,
+        up: Vector3<f32>,
+    ) -> Self {
+        Transform {
+            rotation: Rotation::look_at(target - eye, up),
+            ..Transform::from_position(eye)
+        }
+    }
+
+    fn from_position(position: Vector3<f32>) -> Self {
+        let mut transform = Transform::default();
+        transform.position.set(position.x, position.y, position.z);
+        transform
+    }
+
+    pub fn get(&self) -> (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>) {
         (self.position.get(), self.rotation.get(), self.scale.get())
     }
+
+    /// Transform between this one, for a `factor` of 0, and
+    /// `other`, for 1. Rotations follow the shortest arc.
+    pub fn interpolate(&self, other: &Transform, factor: f32) -> Self {
+        let (position, rotation, scale) = self.get();
+        let (other_position, other_rotation, other_scale) = other.get();
+        let other_rotation = if rotation.coords.dot(&other_rotation.coords) < 0.
+        {
+            UnitQuaternion::new_unchecked(-other_rotation.unwrap())
+        } else {
+            other_rotation
+        };
+        let rotation = rotation
+            .try_slerp(&other_rotation, factor, 1e-6)
+            .unwrap_or_else(|| rotation.nlerp(&other_rotation, factor));
+
+        Transform::new(
+            position + (other_position - position) * factor,
+            rotation,
+            scale + (other_scale - scale) * factor,
+        )
+    }
+
+    /// Matrix scaling, then rotating, then translating.
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        let (position, rotation, scale) = self.get();
+        Isometry3::from_parts(Translation3::from_vector(position), rotation)
+            .to_homogeneous()
+            .prepend_nonuniform_scaling(&scale)
+    }
 }
 
 impl Default for Transform {
@@ -4,9 +4,14 @@ use std::collections::HashMap;
 #[derive(Default)]
 pub struct Control {
     pub is_running: bool,
+    // Size given by the last events, if it changed
     pub window_resized: Option<(u32, u32)>,
     pub is_mouse_right_pressed: bool,
+    pub is_mouse_middle_pressed: bool,
+    // Lines scrolled since the last events, positive forward
+    pub mouse_wheel: f32,
     pub keycode_pressed: HashMap<Option<VirtualKeyCode>, ElementState>,
+    // Pixels divided by the HiDPI factor
     pub cursor_position: (f32, f32),
 }
 
@@ -0,0 +1,33 @@
+use glutin;
+use std::error;
+use std::fmt;
+
+/// Why a window couldn't be opened.
+#[derive(Debug)]
+pub enum WindowError {
+    Creation(glutin::CreationError),
+}
+
+impl fmt::Display for WindowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WindowError::Creation(error) => {
+                write!(f, "Failed to create window: {}", error)
+            }
+        }
+    }
+}
+
+impl error::Error for WindowError {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self {
+            WindowError::Creation(error) => Some(error),
+        }
+    }
+}
+
+impl From<glutin::CreationError> for WindowError {
+    fn from(error: glutin::CreationError) -> Self {
+        WindowError::Creation(error)
+    }
+}
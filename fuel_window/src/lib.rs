@@ -2,19 +2,27 @@ extern crate gl;
 extern crate glutin;
 
 mod control;
+mod error;
+mod size;
 
 pub use control::Control;
+pub use error::WindowError;
 use glutin::Event::WindowEvent;
 use glutin::WindowEvent::{
-    CloseRequested, CursorMoved, KeyboardInput, MouseInput, Resized,
+    CloseRequested, CursorMoved, HiDPIFactorChanged, KeyboardInput, MouseInput,
+    MouseWheel, Resized,
 };
 use glutin::{
     ElementState, KeyboardInput as KInputEvents, MouseButton, MouseCursor,
-    VirtualKeyCode,
+    MouseScrollDelta, VirtualKeyCode,
 };
 use glutin::{EventsLoop, GlContext, GlWindow};
+pub use size::WindowSize;
 use std::time::{Duration, Instant};
 
+// Touchpads scroll by pixels, mouse wheels by lines
+const PIXELS_PER_LINE: f32 = 20.;
+
 fn duration_to_secs(dur: Duration) -> f64 {
     dur.as_secs() as f64 + f64::from(dur.subsec_nanos()) / 1_000_000_000.0
 }
@@ -23,7 +31,6 @@ pub struct Window {
     // OpenGL context and a Window with which it is associated
     pub gl_window: GlWindow,
     pub event_loop: EventsLoop,
-    #[allow(dead_code)]
     dimensions: (f32, f32),
 
     started_time: Instant,
@@ -32,34 +39,42 @@ pub struct Window {
 }
 
 impl Window {
-    pub fn new(title: &str, width: f32, height: f32) -> Window {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        title: &str,
+        width: f32,
+        height: f32,
+    ) -> Result<Self, WindowError> {
         let window_loop = EventsLoop::new();
         let win_conf = glutin::WindowBuilder::new()
             .with_title(title)
             .with_dimensions(width as u32, height as u32);
 
         let context = glutin::ContextBuilder::new().with_vsync(true);
-        let gl_window = GlWindow::new(win_conf, context, &window_loop).unwrap();
+        let gl_window = GlWindow::new(win_conf, context, &window_loop)?;
 
         // Hide cursor
         gl_window.set_cursor(MouseCursor::NoneCursor);
 
-        Window {
+        Ok(Window {
             started_time: Instant::now(),
             gl_window,
             event_loop: window_loop,
             dimensions: (width, height),
             delta_time: 0.,
             last_frame_time: 0.,
-        }
+        })
     }
 
     pub fn get_dt(&self) -> f32 {
         self.delta_time
     }
 
+    /// Position in the unit of `Control`, scaled to pixels.
     pub fn set_cursor_position(&self, position: (f32, f32)) {
-        let (last_pos_x, last_pos_y) = position;
+        let hidpi_factor = self.get_hidpi_factor();
+        let (last_pos_x, last_pos_y) =
+            (position.0 * hidpi_factor, position.1 * hidpi_factor);
         self.gl_window
             .window()
             .set_cursor_position(last_pos_x as i32, last_pos_y as i32)
@@ -80,18 +95,42 @@ impl Window {
         unsafe { self.gl_window.make_current().unwrap() }
     }
 
-    #[allow(dead_code)]
     pub fn get_dimensions(&self) -> (f32, f32) {
         self.dimensions
     }
 
+    pub fn get_hidpi_factor(&self) -> f32 {
+        self.gl_window.hidpi_factor()
+    }
+
+    /// Events and the inner size of the window are already in
+    /// pixels, the size of the frame buffer.
+    pub fn get_size(&self) -> WindowSize {
+        let hidpi_factor = self.get_hidpi_factor();
+        let (width, height) = self.dimensions;
+        let (width, height) = self
+            .gl_window
+            .get_inner_size()
+            .unwrap_or((width as u32, height as u32));
+
+        WindowSize {
+            dimensions: (
+                width as f32 / hidpi_factor,
+                height as f32 / hidpi_factor,
+            ),
+            framebuffer: (width, height),
+            hidpi_factor,
+        }
+    }
+
     pub fn clear_gl(&self) {
         self.gl_window.swap_buffers().unwrap();
     }
 
-    #[allow(dead_code)]
-    pub fn resize(&self, width: u32, height: u32) {
+    // Some platforms need the context to be resized with the window
+    pub fn resize(&mut self, width: u32, height: u32) {
         self.gl_window.resize(width, height);
+        self.dimensions = (width as f32, height as f32);
     }
 
     pub fn load_gl_methods(&self) {
@@ -100,9 +139,17 @@ impl Window {
         })
     }
 
-    pub fn on_resize(&self, control: &Control, mut cb: impl FnMut((u32, u32))) {
-        if let Some(size) = control.window_resized {
-            cb(size);
+    /// Resize the window to the size given by the events,
+    /// then give the new size to the callback. The viewport
+    /// and the cameras should follow it.
+    pub fn on_resize(
+        &mut self,
+        control: &Control,
+        mut cb: impl FnMut(WindowSize),
+    ) {
+        if let Some((width, height)) = control.window_resized {
+            self.resize(width, height);
+            cb(self.get_size());
         }
     }
 
@@ -114,6 +161,12 @@ impl Window {
         cb(control.cursor_position)
     }
 
+    pub fn on_mouse_wheel(&self, control: &Control, mut cb: impl FnMut(f32)) {
+        if control.mouse_wheel != 0. {
+            cb(control.mouse_wheel);
+        }
+    }
+
     pub fn on_pressed_key(
         &self,
         control: &Control,
@@ -126,6 +179,9 @@ impl Window {
     }
 
     pub fn pull_events(&mut self, control: &mut Control) {
+        control.mouse_wheel = 0.;
+        control.window_resized = None;
+        let gl_window = &self.gl_window;
         self.event_loop.poll_events(|glutin_events| {
             if let WindowEvent { event, .. } = glutin_events {
                 match event {
@@ -137,12 +193,38 @@ impl Window {
                         control.is_mouse_right_pressed =
                             state == ElementState::Pressed
                     }
+                    MouseInput {
+                        button: MouseButton::Middle,
+                        state,
+                        ..
+                    } => {
+                        control.is_mouse_middle_pressed =
+                            state == ElementState::Pressed
+                    }
+                    MouseWheel { delta, .. } => {
+                        control.mouse_wheel += match delta {
+                            MouseScrollDelta::LineDelta(_, y) => y,
+                            MouseScrollDelta::PixelDelta(_, y) => {
+                                y / PIXELS_PER_LINE
+                            }
+                        }
+                    }
                     CursorMoved {
                         position: (pos_x, pos_y),
                         ..
-                    } => if control.is_mouse_right_pressed {
-                        control.cursor_position = (pos_x as f32, pos_y as f32)
-                    },
+                    } => {
+                        if control.is_mouse_right_pressed
+                            || control.is_mouse_middle_pressed
+                        {
+                            // Same unit as the dimensions of the window
+                            let hidpi_factor =
+                                f64::from(gl_window.hidpi_factor());
+                            control.cursor_position = (
+                                (pos_x / hidpi_factor) as f32,
+                                (pos_y / hidpi_factor) as f32,
+                            )
+                        }
+                    }
                     KeyboardInput {
                         input:
                             KInputEvents {
@@ -163,6 +245,10 @@ impl Window {
                     },
                     CloseRequested => control.stop(),
                     Resized(w, h) => control.window_resized = Some((w, h)),
+                    // Same size, but a different frame buffer
+                    HiDPIFactorChanged(_) => {
+                        control.window_resized = gl_window.get_inner_size()
+                    }
                     _ => (),
                 }
             }
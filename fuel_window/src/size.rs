@@ -0,0 +1,11 @@
+/// Size of a window. On HiDPI screens the frame buffer has
+/// more pixels than the window, `hidpi_factor` times more.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowSize {
+    /// Frame buffer divided by the HiDPI factor, same unit as
+    /// the cursor position.
+    pub dimensions: (f32, f32),
+    /// In pixels, the size of the viewport.
+    pub framebuffer: (u32, u32),
+    pub hidpi_factor: f32,
+}
@@ -0,0 +1,247 @@
+#version 330 core
+// glTF metallic-roughness material, Cook-Torrance BRDF with
+// GGX distribution, Smith-Schlick geometry and Schlick fresnel.
+// Punctual lights follow KHR_lights_punctual.
+
+const float PI = 3.14159265359;
+const float GAMMA = 2.2;
+const vec3 AMBIENT = vec3(0.3);
+
+const int ALPHA_MASK = 1;
+const int ALPHA_BLEND = 2;
+
+const int MAX_LIGHTS = 8;
+const int LIGHT_DIRECTIONAL = 0;
+const int LIGHT_POINT = 1;
+const int LIGHT_SPOT = 2;
+
+struct Light {
+  int kind;
+  vec3 color;
+  float intensity;
+  // Zero for an infinite range
+  float range;
+  vec3 position;
+  vec3 direction;
+  float inner_cone_cos;
+  float outer_cone_cos;
+};
+
+in vec3 v_position;
+in vec3 v_normal;
+in vec2 v_tex_coord_0;
+in vec2 v_tex_coord_1;
+in vec4 v_tangent;
+in vec4 v_color_0;
+
+out vec4 FragColor;
+
+uniform vec3 camera_position;
+uniform bool has_tangents;
+uniform bool has_color_0;
+uniform int light_count;
+uniform Light lights[MAX_LIGHTS];
+
+uniform vec4 base_color_factor;
+uniform bool has_base_color_texture;
+uniform sampler2D base_color_texture;
+uniform int base_color_tex_coord;
+
+uniform float metallic_factor;
+uniform float roughness_factor;
+uniform bool has_metallic_roughness_texture;
+uniform sampler2D metallic_roughness_texture;
+uniform int metallic_roughness_tex_coord;
+
+uniform float normal_scale;
+uniform bool has_normal_texture;
+uniform sampler2D normal_texture;
+uniform int normal_tex_coord;
+
+uniform float occlusion_strength;
+uniform bool has_occlusion_texture;
+uniform sampler2D occlusion_texture;
+uniform int occlusion_tex_coord;
+
+uniform vec3 emissive_factor;
+uniform bool has_emissive_texture;
+uniform sampler2D emissive_texture;
+uniform int emissive_tex_coord;
+
+uniform int alpha_mode;
+uniform float alpha_cutoff;
+
+vec2 tex_coord(int set) {
+  return set == 1 ? v_tex_coord_1 : v_tex_coord_0;
+}
+
+vec3 to_linear(vec3 color) {
+  return pow(color, vec3(GAMMA));
+}
+
+// Tangent frame from the screen space derivatives, so normal
+// maps work on primitives without tangents.
+mat3 cotangent_frame(vec3 n, vec3 p, vec2 uv) {
+  vec3 dp1 = dFdx(p);
+  vec3 dp2 = dFdy(p);
+  vec2 duv1 = dFdx(uv);
+  vec2 duv2 = dFdy(uv);
+
+  vec3 dp2perp = cross(dp2, n);
+  vec3 dp1perp = cross(n, dp1);
+  vec3 t = dp2perp * duv1.x + dp1perp * duv2.x;
+  vec3 b = dp2perp * duv1.y + dp1perp * duv2.y;
+
+  float invmax = inversesqrt(max(max(dot(t, t), dot(b, b)), 1e-12));
+  return mat3(t * invmax, b * invmax, n);
+}
+
+vec3 get_normal() {
+  vec3 n = v_normal;
+  // Flat normal when the primitive has none
+  if (dot(n, n) < 1e-6) {
+    n = cross(dFdx(v_position), dFdy(v_position));
+  }
+  // Points without normal face the camera
+  if (dot(n, n) < 1e-12) {
+    n = camera_position - v_position;
+  }
+  n = normalize(n);
+  // Back faces are only drawn for double sided materials
+  if (!gl_FrontFacing) {
+    n = -n;
+  }
+
+  if (has_normal_texture) {
+    vec2 uv = tex_coord(normal_tex_coord);
+    vec3 t = texture(normal_texture, uv).rgb * 2.0 - 1.0;
+    t.xy *= normal_scale;
+    mat3 tbn;
+    if (has_tangents && dot(v_tangent.xyz, v_tangent.xyz) > 1e-6) {
+      vec3 tangent = normalize(v_tangent.xyz - n * dot(n, v_tangent.xyz));
+      vec3 bitangent = cross(n, tangent) * v_tangent.w;
+      tbn = mat3(tangent, bitangent, n);
+    } else {
+      tbn = cotangent_frame(n, v_position, uv);
+    }
+    n = normalize(tbn * t);
+  }
+
+  return n;
+}
+
+float distribution_ggx(float n_dot_h, float alpha) {
+  float alpha2 = alpha * alpha;
+  float d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+  return alpha2 / (PI * d * d);
+}
+
+float geometry_smith(float n_dot_v, float n_dot_l, float alpha) {
+  float k = alpha / 2.0;
+  float g_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+  float g_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+  return g_v * g_l;
+}
+
+vec3 fresnel_schlick(float v_dot_h, vec3 f0) {
+  return f0 + (1.0 - f0) * pow(1.0 - v_dot_h, 5.0);
+}
+
+// Direction towards the light and the light received
+vec3 get_radiance(Light light, out vec3 l) {
+  if (light.kind == LIGHT_DIRECTIONAL) {
+    l = normalize(-light.direction);
+    return light.color * light.intensity;
+  }
+
+  vec3 to_light = light.position - v_position;
+  float distance2 = max(dot(to_light, to_light), 1e-4);
+  l = to_light * inversesqrt(distance2);
+
+  float attenuation = 1.0 / distance2;
+  if (light.range > 0.0) {
+    float ratio = sqrt(distance2) / light.range;
+    attenuation *= clamp(1.0 - pow(ratio, 4.0), 0.0, 1.0);
+  }
+  if (light.kind == LIGHT_SPOT) {
+    float scale =
+        1.0 / max(light.inner_cone_cos - light.outer_cone_cos, 0.001);
+    float cd = dot(normalize(light.direction), -l);
+    float spot = clamp((cd - light.outer_cone_cos) * scale, 0.0, 1.0);
+    attenuation *= spot * spot;
+  }
+
+  return light.color * light.intensity * attenuation;
+}
+
+void main() {
+  vec4 base_color = base_color_factor;
+  if (has_base_color_texture) {
+    vec4 texel =
+        texture(base_color_texture, tex_coord(base_color_tex_coord));
+    base_color *= vec4(to_linear(texel.rgb), texel.a);
+  }
+  if (has_color_0) {
+    base_color *= v_color_0;
+  }
+
+  if (alpha_mode == ALPHA_MASK && base_color.a < alpha_cutoff) {
+    discard;
+  }
+
+  float metallic = metallic_factor;
+  float roughness = roughness_factor;
+  if (has_metallic_roughness_texture) {
+    vec4 texel = texture(
+        metallic_roughness_texture, tex_coord(metallic_roughness_tex_coord));
+    roughness *= texel.g;
+    metallic *= texel.b;
+  }
+  metallic = clamp(metallic, 0.0, 1.0);
+  roughness = clamp(roughness, 0.04, 1.0);
+  float alpha = roughness * roughness;
+
+  vec3 n = get_normal();
+  vec3 v = normalize(camera_position - v_position);
+  float n_dot_v = clamp(abs(dot(n, v)), 0.001, 1.0);
+
+  vec3 f0 = mix(vec3(0.04), base_color.rgb, metallic);
+  vec3 diffuse_color = mix(base_color.rgb * 0.96, vec3(0.0), metallic);
+
+  vec3 color = vec3(0.0);
+  for (int i = 0; i < light_count && i < MAX_LIGHTS; i++) {
+    vec3 l;
+    vec3 radiance = get_radiance(lights[i], l);
+    vec3 h = normalize(l + v);
+
+    float n_dot_l = clamp(dot(n, l), 0.0, 1.0);
+    float n_dot_h = clamp(dot(n, h), 0.0, 1.0);
+    float v_dot_h = clamp(dot(v, h), 0.0, 1.0);
+
+    vec3 f = fresnel_schlick(v_dot_h, f0);
+    float d = distribution_ggx(n_dot_h, alpha);
+    float g = geometry_smith(n_dot_v, max(n_dot_l, 0.001), alpha);
+
+    vec3 diffuse = (1.0 - f) * diffuse_color / PI;
+    vec3 specular = f * d * g / (4.0 * max(n_dot_l, 0.001) * n_dot_v);
+    color += PI * n_dot_l * radiance * (diffuse + specular);
+  }
+
+  vec3 ambient = AMBIENT * (diffuse_color + f0 * 0.5);
+  if (has_occlusion_texture) {
+    float occlusion =
+        texture(occlusion_texture, tex_coord(occlusion_tex_coord)).r;
+    ambient = mix(ambient, ambient * occlusion, occlusion_strength);
+  }
+  color += ambient;
+
+  vec3 emissive = emissive_factor;
+  if (has_emissive_texture) {
+    emissive *= to_linear(
+        texture(emissive_texture, tex_coord(emissive_tex_coord)).rgb);
+  }
+  color += emissive;
+
+  float out_alpha = alpha_mode == ALPHA_BLEND ? base_color.a : 1.0;
+  FragColor = vec4(pow(color, vec3(1.0 / GAMMA)), out_alpha);
+}
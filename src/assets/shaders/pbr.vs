@@ -0,0 +1,56 @@
+#version 330 core
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 tex_coord_0;
+layout(location = 2) in vec3 normal;
+layout(location = 3) in vec2 tex_coord_1;
+layout(location = 4) in vec4 tangent;
+layout(location = 5) in vec4 color_0;
+layout(location = 8) in vec3 morph_position_0;
+layout(location = 9) in vec3 morph_position_1;
+layout(location = 10) in vec3 morph_position_2;
+layout(location = 11) in vec3 morph_position_3;
+layout(location = 12) in vec3 morph_normal_0;
+layout(location = 13) in vec3 morph_normal_1;
+layout(location = 14) in vec3 morph_normal_2;
+layout(location = 15) in vec3 morph_normal_3;
+
+uniform mat4 mvp;
+uniform mat4 model;
+uniform mat4 normal_matrix;
+uniform float morph_weights[4];
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec2 v_tex_coord_0;
+out vec2 v_tex_coord_1;
+out vec4 v_tangent;
+out vec4 v_color_0;
+
+// Attributes of missing targets read as zeros
+vec3 get_morphed_position() {
+  return position + morph_weights[0] * morph_position_0 +
+         morph_weights[1] * morph_position_1 +
+         morph_weights[2] * morph_position_2 +
+         morph_weights[3] * morph_position_3;
+}
+
+vec3 get_morphed_normal() {
+  return normal + morph_weights[0] * morph_normal_0 +
+         morph_weights[1] * morph_normal_1 +
+         morph_weights[2] * morph_normal_2 +
+         morph_weights[3] * morph_normal_3;
+}
+
+void main() {
+  vec4 morphed_position = vec4(get_morphed_position(), 1.0);
+  vec4 world_position = model * morphed_position;
+  v_position = world_position.xyz / world_position.w;
+  v_normal = mat3(normal_matrix) * get_morphed_normal();
+  v_tex_coord_0 = tex_coord_0;
+  v_tex_coord_1 = tex_coord_1;
+  // Tangents are directions, the handedness is kept in w
+  v_tangent = vec4(mat3(model) * tangent.xyz, tangent.w);
+  v_color_0 = color_0;
+
+  gl_Position = mvp * morphed_position;
+}
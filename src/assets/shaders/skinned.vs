@@ -0,0 +1,88 @@
+#version 330 core
+// Same as pbr.vs, with vertices following the joints of a skin.
+// Each vertex is moved by up to four weighted joint matrices,
+// after its morph targets.
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 tex_coord_0;
+layout(location = 2) in vec3 normal;
+layout(location = 3) in vec2 tex_coord_1;
+layout(location = 4) in vec4 tangent;
+layout(location = 5) in vec4 color_0;
+layout(location = 6) in vec4 joints_0;
+layout(location = 7) in vec4 weights_0;
+layout(location = 8) in vec3 morph_position_0;
+layout(location = 9) in vec3 morph_position_1;
+layout(location = 10) in vec3 morph_position_2;
+layout(location = 11) in vec3 morph_position_3;
+layout(location = 12) in vec3 morph_normal_0;
+layout(location = 13) in vec3 morph_normal_1;
+layout(location = 14) in vec3 morph_normal_2;
+layout(location = 15) in vec3 morph_normal_3;
+
+uniform mat4 mvp;
+uniform mat4 model;
+uniform mat4 normal_matrix;
+uniform bool has_skin;
+// A row for each joint, with a texel for each column
+uniform sampler2D joint_matrices;
+uniform float morph_weights[4];
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec2 v_tex_coord_0;
+out vec2 v_tex_coord_1;
+out vec4 v_tangent;
+out vec4 v_color_0;
+
+// Attributes of missing targets read as zeros
+vec3 get_morphed_position() {
+  return position + morph_weights[0] * morph_position_0 +
+         morph_weights[1] * morph_position_1 +
+         morph_weights[2] * morph_position_2 +
+         morph_weights[3] * morph_position_3;
+}
+
+vec3 get_morphed_normal() {
+  return normal + morph_weights[0] * morph_normal_0 +
+         morph_weights[1] * morph_normal_1 +
+         morph_weights[2] * morph_normal_2 +
+         morph_weights[3] * morph_normal_3;
+}
+
+mat4 get_joint_matrix(float joint) {
+  int row = int(joint);
+  return mat4(texelFetch(joint_matrices, ivec2(0, row), 0),
+              texelFetch(joint_matrices, ivec2(1, row), 0),
+              texelFetch(joint_matrices, ivec2(2, row), 0),
+              texelFetch(joint_matrices, ivec2(3, row), 0));
+}
+
+mat4 get_skin_matrix() {
+  if (!has_skin) {
+    return mat4(1.0);
+  }
+  return weights_0.x * get_joint_matrix(joints_0.x) +
+         weights_0.y * get_joint_matrix(joints_0.y) +
+         weights_0.z * get_joint_matrix(joints_0.z) +
+         weights_0.w * get_joint_matrix(joints_0.w);
+}
+
+void main() {
+  mat4 skin = get_skin_matrix();
+  vec4 skinned_position = skin * vec4(get_morphed_position(), 1.0);
+  // Joints are expected to keep angles, like most rigs
+  vec3 skinned_normal = mat3(skin) * get_morphed_normal();
+  vec3 skinned_tangent = mat3(skin) * tangent.xyz;
+
+  vec4 world_position = model * skinned_position;
+  v_position = world_position.xyz / world_position.w;
+  v_normal = mat3(normal_matrix) * skinned_normal;
+  v_tex_coord_0 = tex_coord_0;
+  v_tex_coord_1 = tex_coord_1;
+  // Tangents are directions, the handedness is kept in w
+  v_tangent = vec4(mat3(model) * skinned_tangent, tangent.w);
+  v_color_0 = color_0;
+
+  gl_Position = mvp * skinned_position;
+}
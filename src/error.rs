@@ -0,0 +1,73 @@
+use fuel_core::SceneError;
+use fuel_exporter::ExportError;
+use fuel_importer::ImportError;
+use fuel_render::ShaderError;
+use fuel_window::WindowError;
+use std::error;
+use std::fmt;
+
+/// Every error of the engine, by subsystem.
+#[derive(Debug)]
+pub enum Error {
+    Import(ImportError),
+    Export(ExportError),
+    Scene(SceneError),
+    Shader(ShaderError),
+    Window(WindowError),
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Import(error) => error.fmt(f),
+            Error::Export(error) => error.fmt(f),
+            Error::Scene(error) => error.fmt(f),
+            Error::Shader(error) => error.fmt(f),
+            Error::Window(error) => error.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self {
+            Error::Import(error) => Some(error),
+            Error::Export(error) => Some(error),
+            Error::Scene(error) => Some(error),
+            Error::Shader(error) => Some(error),
+            Error::Window(error) => Some(error),
+        }
+    }
+}
+
+impl From<ImportError> for Error {
+    fn from(error: ImportError) -> Self {
+        Error::Import(error)
+    }
+}
+
+impl From<ExportError> for Error {
+    fn from(error: ExportError) -> Self {
+        Error::Export(error)
+    }
+}
+
+impl From<SceneError> for Error {
+    fn from(error: SceneError) -> Self {
+        Error::Scene(error)
+    }
+}
+
+impl From<ShaderError> for Error {
+    fn from(error: ShaderError) -> Self {
+        Error::Shader(error)
+    }
+}
+
+impl From<WindowError> for Error {
+    fn from(error: WindowError) -> Self {
+        Error::Window(error)
+    }
+}
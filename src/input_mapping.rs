@@ -1,14 +1,17 @@
 use fuel::fuel_camera::CameraMovement;
 
 use fuel::glutin::VirtualKeyCode;
-use fuel::{Control, Scene, Window};
+use fuel::{Control, RenderBackend, Scene, Window};
 
 pub fn process_input(
     win: &mut Window,
     scene: &mut Scene,
+    backend: &mut RenderBackend,
     control: &mut Control,
 ) {
-    win.on_resize(control, |(w, h)| win.resize(w, h));
+    win.on_resize(control, |size| {
+        scene.resize(backend, size.dimensions, size.framebuffer)
+    });
 
     win.on_cursor_position(control, |(x, y)| scene.camera.spin_direction(x, y));
 
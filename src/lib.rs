@@ -1,15 +1,26 @@
 pub extern crate fuel_camera;
 pub extern crate fuel_core;
+pub extern crate fuel_exporter;
+pub extern crate fuel_importer;
 pub extern crate fuel_render;
 pub extern crate fuel_types;
-pub extern crate fuel_importer;
 pub extern crate fuel_window;
 pub extern crate gl;
 pub extern crate glutin;
 pub extern crate nalgebra as na;
 
-pub use fuel_window::{Window, Control};
+mod error;
+
+pub use error::{Error, Result};
+
+pub use fuel_camera::{
+    Camera, FirstPerson, Lens, Orbit, OrbitDrag, ProjectionMode,
+};
+pub use fuel_core::{ObjectTypes, Scene, SceneObject};
+pub use fuel_exporter::Exporter;
 pub use fuel_importer::Importer;
-pub use fuel_camera::FirstPerson;
-pub use fuel_core::{ObjectTypes, Scene};
-pub use fuel_render::Model;
+pub use fuel_render::{
+    Animation, HeadlessBackend, Light, LightKind, Model, OpenGlBackend,
+    RenderBackend, SoftwareBackend,
+};
+pub use fuel_window::{Control, Window, WindowSize};
@@ -2,9 +2,9 @@ extern crate fuel;
 extern crate gltf;
 
 use fuel::Importer;
+use fuel::OpenGlBackend;
 use fuel::Scene;
-use fuel::{Control, Window};
-use std::error::Error;
+use fuel::{Control, Result, Window};
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -15,18 +15,19 @@ const TITLE: &str = "Fuel";
 const WINDOW_WIDTH: f32 = 800.;
 const WINDOW_HEIGHT: f32 = 600.;
 
-fn main() -> Result<(), Box<Error>> {
-    let mut win = Window::new(TITLE, WINDOW_WIDTH, WINDOW_HEIGHT);
+fn main() -> Result<()> {
+    let mut win = Window::new(TITLE, WINDOW_WIDTH, WINDOW_HEIGHT)?;
     let mut scene = Scene::new(WINDOW_WIDTH, WINDOW_HEIGHT, 45., 0.1, 100.);
     let mut control = Control::new();
 
     win.make_current();
     win.load_gl_methods();
+    let mut backend = OpenGlBackend::new();
     // win.set_cursor_position(scene.camera.last_pos);
 
     let cube_1 = Importer::from_gltf(
         "src/assets/meshes/samples/textured/BoxTextured.gltf",
-    );
+    )?;
     let id = scene.add(cube_1);
     scene.get_object(id).set_position(0., 0., 0.);
 
@@ -37,10 +38,10 @@ fn main() -> Result<(), Box<Error>> {
         scene.camera.set_dt(win.get_dt());
 
         win.pull_events(&mut control);
-        process_input(&mut win, &mut scene, &mut control);
+        process_input(&mut win, &mut scene, &mut backend, &mut control);
         // Render all components into the
         // current scene
-        scene.render();
+        scene.render(&mut backend)?;
         sleep(Duration::from_millis(16));
     }
 